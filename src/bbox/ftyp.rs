@@ -78,4 +78,8 @@ impl FileTypeBox {
     pub fn is_compatible_brand(&self, brand: &Byte4) -> bool {
         self.compatible_brands.contains(brand)
     }
+
+    pub fn has_brand(&self, brand: &str) -> bool {
+        self.major_brand == brand || self.compatible_brands.iter().any(|b| *b == brand)
+    }
 }
@@ -47,6 +47,10 @@ impl DataInformationBox {
         &self.box_header
     }
 
+    pub fn data_reference_box(&self) -> &DataReferenceBox {
+        &self.data_reference_box
+    }
+
     pub fn add_entry(&mut self, entry: DataEntryBox) {
         self.data_reference_box.add_entry(entry);
     }
@@ -111,6 +115,18 @@ impl DataReferenceBox {
         &self.full_box_header
     }
 
+    pub fn data_entries(&self) -> &Vec<DataEntryBox> {
+        &self.data_entries
+    }
+
+    // data_ref_index is 1-based, as in iloc and sample entries.
+    pub fn data_entry(&self, data_ref_index: u16) -> Option<&DataEntryBox> {
+        if data_ref_index == 0 {
+            return None;
+        }
+        self.data_entries.get(usize::from(data_ref_index) - 1)
+    }
+
     pub fn add_entry(&mut self, entry: DataEntryBox) {
         self.data_entries.push(entry);
     }
@@ -147,7 +163,7 @@ impl DataEntryBox {
     }
 
     pub fn new_url(is_self_contained: bool) -> Self {
-        Self::new("url ".parse().unwrap(), 0, 0)
+        Self::new("url ".parse().unwrap(), 0, u32::from(is_self_contained))
     }
 
     pub fn from_stream_header_urn<T: Stream>(
@@ -169,7 +185,8 @@ impl DataEntryBox {
         box_header: BoxHeader,
     ) -> Result<Self> {
         let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
-        let location = if (full_box_header.flags() & 1) != 0 {
+        // Flag 1 marks data in the same file, which has no location.
+        let location = if (full_box_header.flags() & 1) == 0 {
             stream.read_zero_term_string()
         } else {
             String::new()
@@ -185,6 +202,10 @@ impl DataEntryBox {
         &self.full_box_header
     }
 
+    pub fn is_self_contained(&self) -> bool {
+        self.full_box_header.box_type() == "url " && (self.full_box_header.flags() & 1) != 0
+    }
+
     pub fn location(&self) -> &String {
         &self.location
     }
@@ -201,3 +222,33 @@ impl DataEntryBox {
         self.name = name;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_dref() {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x30, 0x64, 0x72, 0x65, 0x66, // size, 'dref'
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, // version, flags, entry_count
+            0x00, 0x00, 0x00, 0x0C, 0x75, 0x72, 0x6C, 0x20, // size, 'url '
+            0x00, 0x00, 0x00, 0x01, // self-contained
+            0x00, 0x00, 0x00, 0x14, 0x75, 0x72, 0x6C, 0x20, // size, 'url '
+            0x00, 0x00, 0x00, 0x00, // version, flags
+            0x61, 0x2F, 0x62, 0x2E, 0x62, 0x69, 0x6E, 0x00, // "a/b.bin"
+        ];
+        let mut stream = BitStream::new(data);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let dref = DataReferenceBox::from_stream_header(&mut stream, header).unwrap();
+        assert!(dref.data_entry(0).is_none());
+        assert!(dref.data_entry(1).unwrap().is_self_contained());
+        assert_eq!(dref.data_entry(1).unwrap().location(), "");
+        let external = dref.data_entry(2).unwrap();
+        assert!(!external.is_self_contained());
+        assert_eq!(external.location(), "a/b.bin");
+        assert!(dref.data_entry(3).is_none());
+        assert!(stream.is_eof());
+    }
+}
@@ -32,8 +32,8 @@ impl GroupListBox {
     pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
         let mut entity_to_group_box_vector = Vec::new();
         while !stream.is_eof() {
-            let mut ex = stream.extract_from(&box_header)?;
-            let child_box_header = BoxHeader::from_stream(&mut ex)?;
+            let child_box_header = BoxHeader::from_stream(stream)?;
+            let mut ex = stream.extract_from(&child_box_header)?;
             entity_to_group_box_vector.push(EntityToGroupBox::new(&mut ex, child_box_header)?);
         }
         Ok(Self {
@@ -60,6 +60,8 @@ pub struct EntityToGroupBox {
     full_box_header: FullBoxHeader,
     group_id: u32,
     entity_ids: Vec<u32>,
+    // Group type specific fields following the entity ids.
+    payload: Vec<u8>,
 }
 
 impl EntityToGroupBox {
@@ -71,10 +73,12 @@ impl EntityToGroupBox {
         for _ in 0..entity_count {
             entity_ids.push(stream.read_4bytes()?.to_u32());
         }
+        let payload = stream.read_bytes(stream.num_bytes_left())?.to_vec();
         Ok(Self {
             full_box_header,
             group_id,
             entity_ids,
+            payload,
         })
     }
 
@@ -97,4 +101,43 @@ impl EntityToGroupBox {
     pub fn set_entity_ids(&mut self, ids: Vec<u32>) {
         self.entity_ids = ids;
     }
+
+    pub fn payload(&self) -> &Vec<u8> {
+        &self.payload
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_grpl_with_several_groups() {
+        let mut stream = BitStream::new(vec![
+            0x00, 0x00, 0x00, 0x39, 0x67, 0x72, 0x70, 0x6C, // size, 'grpl'
+            0x00, 0x00, 0x00, 0x18, 0x61, 0x6C, 0x74, 0x72, // size, 'altr'
+            0x00, 0x00, 0x00, 0x00, // version, flags
+            0x00, 0x00, 0x00, 0x0A, // group_id
+            0x00, 0x00, 0x00, 0x01, // num_entities_in_group
+            0x00, 0x00, 0x00, 0x01, // entity_id
+            0x00, 0x00, 0x00, 0x19, 0x70, 0x61, 0x6E, 0x6F, // size, 'pano'
+            0x00, 0x00, 0x00, 0x00, // version, flags
+            0x00, 0x00, 0x00, 0x0B, // group_id
+            0x00, 0x00, 0x00, 0x01, // num_entities_in_group
+            0x00, 0x00, 0x00, 0x02, // entity_id
+            0x01, // panorama_direction
+        ]);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let grpl = GroupListBox::from_stream_header(&mut stream, header).unwrap();
+        assert!(stream.is_eof());
+        let groups = grpl.entity_to_group_box_vector();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(*groups[0].full_box_header().box_type(), "altr");
+        assert_eq!(groups[0].group_id(), 10);
+        assert!(groups[0].payload().is_empty());
+        assert_eq!(*groups[1].full_box_header().box_type(), "pano");
+        assert_eq!(groups[1].entity_ids(), &vec![2]);
+        assert_eq!(groups[1].payload(), &vec![1]);
+    }
 }
@@ -0,0 +1,52 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+// Exposure of an image of an auto exposure bracketing set, in stops:
+// exposure_numerator / exposure_step.
+#[derive(Debug)]
+pub struct AutoExposureProperty {
+    full_box_header: FullBoxHeader,
+    exposure_step: i8,
+    exposure_numerator: i8,
+}
+
+impl Default for AutoExposureProperty {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("aebr".parse().unwrap(), 0, 0),
+            exposure_step: 1,
+            exposure_numerator: 0,
+        }
+    }
+}
+
+impl BBox for AutoExposureProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl AutoExposureProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        Ok(Self {
+            full_box_header,
+            exposure_step: stream.read_byte()? as i8,
+            exposure_numerator: stream.read_byte()? as i8,
+        })
+    }
+
+    pub fn exposure_step(&self) -> i8 {
+        self.exposure_step
+    }
+
+    pub fn exposure_numerator(&self) -> i8 {
+        self.exposure_numerator
+    }
+}
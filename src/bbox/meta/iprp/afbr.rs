@@ -0,0 +1,51 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+// Flash exposure of an image of a flash exposure bracketing set, in stops.
+#[derive(Debug)]
+pub struct FlashExposureProperty {
+    full_box_header: FullBoxHeader,
+    flash_exposure_numerator: i8,
+    flash_exposure_denominator: i8,
+}
+
+impl Default for FlashExposureProperty {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("afbr".parse().unwrap(), 0, 0),
+            flash_exposure_numerator: 0,
+            flash_exposure_denominator: 1,
+        }
+    }
+}
+
+impl BBox for FlashExposureProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl FlashExposureProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        Ok(Self {
+            full_box_header,
+            flash_exposure_numerator: stream.read_byte()? as i8,
+            flash_exposure_denominator: stream.read_byte()? as i8,
+        })
+    }
+
+    pub fn flash_exposure_numerator(&self) -> i8 {
+        self.flash_exposure_numerator
+    }
+
+    pub fn flash_exposure_denominator(&self) -> i8 {
+        self.flash_exposure_denominator
+    }
+}
@@ -0,0 +1,58 @@
+use crate::bbox::header::BoxHeader;
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+// ambient_illuminance is in units of 0.0001 lux and the ambient light
+// chromaticity in units of 0.00002.
+#[derive(Debug)]
+pub struct AmbientViewingEnvironmentProperty {
+    box_header: BoxHeader,
+    ambient_illuminance: u32,
+    ambient_light_x: u16,
+    ambient_light_y: u16,
+}
+
+impl Default for AmbientViewingEnvironmentProperty {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("amve".parse().unwrap()),
+            ambient_illuminance: 0,
+            ambient_light_x: 0,
+            ambient_light_y: 0,
+        }
+    }
+}
+
+impl BBox for AmbientViewingEnvironmentProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl AmbientViewingEnvironmentProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        Ok(Self {
+            box_header,
+            ambient_illuminance: stream.read_4bytes()?.to_u32(),
+            ambient_light_x: stream.read_2bytes()?.to_u16(),
+            ambient_light_y: stream.read_2bytes()?.to_u16(),
+        })
+    }
+
+    pub fn ambient_illuminance(&self) -> u32 {
+        self.ambient_illuminance
+    }
+
+    pub fn ambient_light_x(&self) -> u16 {
+        self.ambient_light_x
+    }
+
+    pub fn ambient_light_y(&self) -> u16 {
+        self.ambient_light_y
+    }
+}
@@ -0,0 +1,66 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+pub const AUX_TYPE_ALPHA: &str = "urn:mpeg:mpegB:cicp:systems:auxiliary:alpha";
+pub const AUX_TYPE_HEVC_ALPHA: &str = "urn:mpeg:hevc:2015:auxid:1";
+
+#[derive(Debug)]
+pub struct AuxiliaryTypeProperty {
+    full_box_header: FullBoxHeader,
+    aux_type: String,
+    aux_subtype: Vec<u8>,
+}
+
+impl Default for AuxiliaryTypeProperty {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("auxC".parse().unwrap(), 0, 0),
+            aux_type: String::new(),
+            aux_subtype: Vec::new(),
+        }
+    }
+}
+
+impl BBox for AuxiliaryTypeProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl AuxiliaryTypeProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let aux_type = stream.read_zero_term_string();
+        let mut aux_subtype = Vec::new();
+        while !stream.is_eof() {
+            aux_subtype.push(stream.read_byte()?);
+        }
+        Ok(Self {
+            full_box_header,
+            aux_type,
+            aux_subtype,
+        })
+    }
+
+    pub fn aux_type(&self) -> &str {
+        &self.aux_type
+    }
+
+    pub fn set_aux_type(&mut self, aux_type: String) {
+        self.aux_type = aux_type;
+    }
+
+    pub fn aux_subtype(&self) -> &Vec<u8> {
+        &self.aux_subtype
+    }
+
+    pub fn is_alpha(&self) -> bool {
+        self.aux_type == AUX_TYPE_ALPHA || self.aux_type == AUX_TYPE_HEVC_ALPHA
+    }
+}
@@ -0,0 +1,111 @@
+use crate::bbox::header::BoxHeader;
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+// Chromaticity coordinates are in units of 0.00002 and luminance in units
+// of 0.0000001 cd/m², as in the SEI message of H.265. Each part is optional.
+#[derive(Debug)]
+pub struct ContentColorVolumeProperty {
+    box_header: BoxHeader,
+    primaries: Option<[(i32, i32); 3]>,
+    min_luminance_value: Option<u32>,
+    max_luminance_value: Option<u32>,
+    avg_luminance_value: Option<u32>,
+}
+
+impl Default for ContentColorVolumeProperty {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("cclv".parse().unwrap()),
+            primaries: None,
+            min_luminance_value: None,
+            max_luminance_value: None,
+            avg_luminance_value: None,
+        }
+    }
+}
+
+impl BBox for ContentColorVolumeProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl ContentColorVolumeProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let mut res = Self {
+            box_header,
+            ..Self::default()
+        };
+        stream.read_bits(2)?;
+        let primaries_present = stream.read_flag()?;
+        let min_luminance_present = stream.read_flag()?;
+        let max_luminance_present = stream.read_flag()?;
+        let avg_luminance_present = stream.read_flag()?;
+        stream.read_bits(2)?;
+        if primaries_present {
+            let mut primaries = [(0, 0); 3];
+            for primary in primaries.iter_mut() {
+                *primary = (
+                    stream.read_4bytes()?.to_u32() as i32,
+                    stream.read_4bytes()?.to_u32() as i32,
+                );
+            }
+            res.primaries = Some(primaries);
+        }
+        if min_luminance_present {
+            res.min_luminance_value = Some(stream.read_4bytes()?.to_u32());
+        }
+        if max_luminance_present {
+            res.max_luminance_value = Some(stream.read_4bytes()?.to_u32());
+        }
+        if avg_luminance_present {
+            res.avg_luminance_value = Some(stream.read_4bytes()?.to_u32());
+        }
+        Ok(res)
+    }
+
+    pub fn primaries(&self) -> Option<&[(i32, i32); 3]> {
+        self.primaries.as_ref()
+    }
+
+    pub fn min_luminance_value(&self) -> Option<u32> {
+        self.min_luminance_value
+    }
+
+    pub fn max_luminance_value(&self) -> Option<u32> {
+        self.max_luminance_value
+    }
+
+    pub fn avg_luminance_value(&self) -> Option<u32> {
+        self.avg_luminance_value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_cclv_optional_fields() {
+        let mut stream = BitStream::new(vec![
+            0x00, 0x00, 0x00, 0x11, 0x63, 0x63, 0x6C, 0x76, // size, 'cclv'
+            0x14, // min and avg luminance present
+            0x00, 0x00, 0x03, 0xE8, // min_luminance_value
+            0x00, 0x98, 0x96, 0x80, // avg_luminance_value
+        ]);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let cclv = ContentColorVolumeProperty::from_stream_header(&mut stream, header).unwrap();
+        assert!(stream.is_eof());
+        assert!(cclv.primaries().is_none());
+        assert_eq!(cclv.min_luminance_value(), Some(1000));
+        assert_eq!(cclv.max_luminance_value(), None);
+        assert_eq!(cclv.avg_luminance_value(), Some(10_000_000));
+    }
+}
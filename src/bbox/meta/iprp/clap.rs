@@ -0,0 +1,91 @@
+use crate::bbox::header::BoxHeader;
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+#[derive(Debug)]
+pub struct CleanApertureProperty {
+    box_header: BoxHeader,
+    width_n: u32,
+    width_d: u32,
+    height_n: u32,
+    height_d: u32,
+    horizontal_offset_n: i32,
+    horizontal_offset_d: u32,
+    vertical_offset_n: i32,
+    vertical_offset_d: u32,
+}
+
+impl Default for CleanApertureProperty {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("clap".parse().unwrap()),
+            width_n: 0,
+            width_d: 1,
+            height_n: 0,
+            height_d: 1,
+            horizontal_offset_n: 0,
+            horizontal_offset_d: 1,
+            vertical_offset_n: 0,
+            vertical_offset_d: 1,
+        }
+    }
+}
+
+impl BBox for CleanApertureProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl CleanApertureProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        Ok(Self {
+            box_header,
+            width_n: stream.read_4bytes()?.to_u32(),
+            width_d: stream.read_4bytes()?.to_u32(),
+            height_n: stream.read_4bytes()?.to_u32(),
+            height_d: stream.read_4bytes()?.to_u32(),
+            horizontal_offset_n: stream.read_4bytes()?.to_u32() as i32,
+            horizontal_offset_d: stream.read_4bytes()?.to_u32(),
+            vertical_offset_n: stream.read_4bytes()?.to_u32() as i32,
+            vertical_offset_d: stream.read_4bytes()?.to_u32(),
+        })
+    }
+
+    pub fn width_n(&self) -> u32 {
+        self.width_n
+    }
+
+    pub fn width_d(&self) -> u32 {
+        self.width_d
+    }
+
+    pub fn height_n(&self) -> u32 {
+        self.height_n
+    }
+
+    pub fn height_d(&self) -> u32 {
+        self.height_d
+    }
+
+    pub fn horizontal_offset_n(&self) -> i32 {
+        self.horizontal_offset_n
+    }
+
+    pub fn horizontal_offset_d(&self) -> u32 {
+        self.horizontal_offset_d
+    }
+
+    pub fn vertical_offset_n(&self) -> i32 {
+        self.vertical_offset_n
+    }
+
+    pub fn vertical_offset_d(&self) -> u32 {
+        self.vertical_offset_d
+    }
+}
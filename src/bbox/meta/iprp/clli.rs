@@ -0,0 +1,50 @@
+use crate::bbox::header::BoxHeader;
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+// Both levels are in cd/m².
+#[derive(Debug)]
+pub struct ContentLightLevelProperty {
+    box_header: BoxHeader,
+    max_content_light_level: u16,
+    max_pic_average_light_level: u16,
+}
+
+impl Default for ContentLightLevelProperty {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("clli".parse().unwrap()),
+            max_content_light_level: 0,
+            max_pic_average_light_level: 0,
+        }
+    }
+}
+
+impl BBox for ContentLightLevelProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl ContentLightLevelProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        Ok(Self {
+            box_header,
+            max_content_light_level: stream.read_2bytes()?.to_u16(),
+            max_pic_average_light_level: stream.read_2bytes()?.to_u16(),
+        })
+    }
+
+    pub fn max_content_light_level(&self) -> u16 {
+        self.max_content_light_level
+    }
+
+    pub fn max_pic_average_light_level(&self) -> u16 {
+        self.max_pic_average_light_level
+    }
+}
@@ -0,0 +1,75 @@
+use crate::bbox::header::BoxHeader;
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+pub const COMPONENT_TYPE_MONOCHROME: u16 = 0;
+pub const COMPONENT_TYPE_Y: u16 = 1;
+pub const COMPONENT_TYPE_CB: u16 = 2;
+pub const COMPONENT_TYPE_CR: u16 = 3;
+pub const COMPONENT_TYPE_RED: u16 = 4;
+pub const COMPONENT_TYPE_GREEN: u16 = 5;
+pub const COMPONENT_TYPE_BLUE: u16 = 6;
+pub const COMPONENT_TYPE_ALPHA: u16 = 7;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentDefinition {
+    pub component_type: u16,
+    pub component_type_uri: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ComponentDefinitionBox {
+    box_header: BoxHeader,
+    components: Vec<ComponentDefinition>,
+}
+
+impl Default for ComponentDefinitionBox {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("cmpd".parse().unwrap()),
+            components: Vec::new(),
+        }
+    }
+}
+
+impl BBox for ComponentDefinitionBox {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl ComponentDefinitionBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let component_count = stream.read_4bytes()?.to_u32();
+        let mut components = Vec::new();
+        for _ in 0..component_count {
+            let component_type = stream.read_2bytes()?.to_u16();
+            let component_type_uri = if component_type >= 0x8000 {
+                Some(stream.read_zero_term_string())
+            } else {
+                None
+            };
+            components.push(ComponentDefinition {
+                component_type,
+                component_type_uri,
+            });
+        }
+        Ok(Self {
+            box_header,
+            components,
+        })
+    }
+
+    pub fn components(&self) -> &Vec<ComponentDefinition> {
+        &self.components
+    }
+
+    pub fn add_component(&mut self, component: ComponentDefinition) {
+        self.components.push(component);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::bbox::header::BoxHeader;
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+#[derive(Debug)]
+pub struct ColorInformationProperty {
+    box_header: BoxHeader,
+    color_type: Byte4,
+    color_primaries: u16,
+    transfer_characteristics: u16,
+    matrix_coefficients: u16,
+    full_range_flag: bool,
+    icc_profile: Vec<u8>,
+}
+
+impl Default for ColorInformationProperty {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("colr".parse().unwrap()),
+            color_type: "nclx".parse().unwrap(),
+            color_primaries: 2,
+            transfer_characteristics: 2,
+            matrix_coefficients: 2,
+            full_range_flag: false,
+            icc_profile: Vec::new(),
+        }
+    }
+}
+
+impl BBox for ColorInformationProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl ColorInformationProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let mut res = Self {
+            box_header,
+            color_type: stream.read_4bytes()?,
+            ..Self::default()
+        };
+        if res.color_type == "nclx" {
+            res.color_primaries = stream.read_2bytes()?.to_u16();
+            res.transfer_characteristics = stream.read_2bytes()?.to_u16();
+            res.matrix_coefficients = stream.read_2bytes()?.to_u16();
+            res.full_range_flag = stream.read_flag()?;
+            stream.read_bits(7)?;
+        } else if res.color_type == "rICC" || res.color_type == "prof" {
+            while !stream.is_eof() {
+                res.icc_profile.push(stream.read_byte()?);
+            }
+        }
+        Ok(res)
+    }
+
+    pub fn color_type(&self) -> &Byte4 {
+        &self.color_type
+    }
+
+    pub fn is_nclx(&self) -> bool {
+        self.color_type == "nclx"
+    }
+
+    pub fn color_primaries(&self) -> u16 {
+        self.color_primaries
+    }
+
+    pub fn transfer_characteristics(&self) -> u16 {
+        self.transfer_characteristics
+    }
+
+    pub fn matrix_coefficients(&self) -> u16 {
+        self.matrix_coefficients
+    }
+
+    pub fn full_range_flag(&self) -> bool {
+        self.full_range_flag
+    }
+
+    pub fn icc_profile(&self) -> &Vec<u8> {
+        &self.icc_profile
+    }
+}
@@ -0,0 +1,51 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+// Focus distance of an image of a focus bracketing set, in metres.
+#[derive(Debug)]
+pub struct FocusProperty {
+    full_box_header: FullBoxHeader,
+    focus_distance_numerator: u16,
+    focus_distance_denominator: u16,
+}
+
+impl Default for FocusProperty {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("fobr".parse().unwrap(), 0, 0),
+            focus_distance_numerator: 0,
+            focus_distance_denominator: 1,
+        }
+    }
+}
+
+impl BBox for FocusProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl FocusProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        Ok(Self {
+            full_box_header,
+            focus_distance_numerator: stream.read_2bytes()?.to_u16(),
+            focus_distance_denominator: stream.read_2bytes()?.to_u16(),
+        })
+    }
+
+    pub fn focus_distance_numerator(&self) -> u16 {
+        self.focus_distance_numerator
+    }
+
+    pub fn focus_distance_denominator(&self) -> u16 {
+        self.focus_distance_denominator
+    }
+}
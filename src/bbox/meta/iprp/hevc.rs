@@ -46,6 +46,206 @@ impl HevcConfigurationBox {
     }
 }
 
+#[derive(Debug)]
+pub struct LHevcConfigurationBox {
+    box_header: BoxHeader,
+    lhevc_config: LHevcDecoderConfigurationRecord,
+}
+
+impl Default for LHevcConfigurationBox {
+    fn default() -> Self {
+        Self::new(LHevcDecoderConfigurationRecord::default())
+    }
+}
+
+impl BBox for LHevcConfigurationBox {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl LHevcConfigurationBox {
+    pub fn new(lhevc_config: LHevcDecoderConfigurationRecord) -> Self {
+        Self {
+            box_header: BoxHeader::new("lhvC".parse().unwrap()),
+            lhevc_config,
+        }
+    }
+
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        Ok(Self {
+            box_header,
+            lhevc_config: LHevcDecoderConfigurationRecord::from_stream(stream)?,
+        })
+    }
+
+    pub fn config(&self) -> &LHevcDecoderConfigurationRecord {
+        &self.lhevc_config
+    }
+}
+
+#[derive(Debug)]
+pub struct LHevcDecoderConfigurationRecord {
+    configuration_version: u8,
+    min_spatial_segmentation_idc: u16,
+    parallelism_type: u8,
+    num_temporal_layers: u8,
+    temporal_id_nested: u8,
+    length_size_minus1: u8,
+    nal_array: Vec<NalArray>,
+}
+
+impl Default for LHevcDecoderConfigurationRecord {
+    fn default() -> Self {
+        Self {
+            configuration_version: 1,
+            min_spatial_segmentation_idc: 0,
+            parallelism_type: 0,
+            num_temporal_layers: 0,
+            temporal_id_nested: 0,
+            length_size_minus1: 0,
+            nal_array: Vec::new(),
+        }
+    }
+}
+
+impl DecoderConfigurationRecord for LHevcDecoderConfigurationRecord {
+    fn configuration_map(&self) -> ConfigurationMap {
+        parameter_set_configuration_map(&self.nal_array)
+    }
+}
+
+impl LHevcDecoderConfigurationRecord {
+    fn from_stream<T: Stream>(stream: &mut T) -> Result<Self> {
+        let configuration_version = stream.read_byte()?;
+        stream.read_bits(4)?;
+        let min_spatial_segmentation_idc = stream.read_bits(12)? as u16;
+        stream.read_bits(6)?;
+        let parallelism_type = stream.read_bits(2)? as u8;
+        stream.read_bits(2)?;
+        let num_temporal_layers = stream.read_bits(3)? as u8;
+        let temporal_id_nested = stream.read_bits(1)? as u8;
+        let length_size_minus1 = stream.read_bits(2)? as u8;
+        let nal_array = read_nal_arrays(stream)?;
+        Ok(Self {
+            configuration_version,
+            min_spatial_segmentation_idc,
+            parallelism_type,
+            num_temporal_layers,
+            temporal_id_nested,
+            length_size_minus1,
+            nal_array,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct HevcTileConfigurationBox {
+    box_header: BoxHeader,
+    tile_config: HevcTileTierLevelConfigurationRecord,
+}
+
+impl Default for HevcTileConfigurationBox {
+    fn default() -> Self {
+        Self::new(HevcTileTierLevelConfigurationRecord::default())
+    }
+}
+
+impl BBox for HevcTileConfigurationBox {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl HevcTileConfigurationBox {
+    pub fn new(tile_config: HevcTileTierLevelConfigurationRecord) -> Self {
+        Self {
+            box_header: BoxHeader::new("hvtC".parse().unwrap()),
+            tile_config,
+        }
+    }
+
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        Ok(Self {
+            box_header,
+            tile_config: HevcTileTierLevelConfigurationRecord::from_stream(stream)?,
+        })
+    }
+
+    pub fn config(&self) -> &HevcTileTierLevelConfigurationRecord {
+        &self.tile_config
+    }
+}
+
+#[derive(Debug)]
+pub struct HevcTileTierLevelConfigurationRecord {
+    configuration_version: u8,
+    general_profile_space: u8,
+    general_tier_flag: u8,
+    general_profile_idc: u8,
+    general_profile_compatibility_flags: u32,
+    general_constraint_indicator_flags: [u8; 6],
+    general_level_idc: u8,
+}
+
+impl Default for HevcTileTierLevelConfigurationRecord {
+    fn default() -> Self {
+        Self {
+            configuration_version: 1,
+            general_profile_space: 0,
+            general_tier_flag: 0,
+            general_profile_idc: 0,
+            general_profile_compatibility_flags: 0,
+            general_constraint_indicator_flags: [0; 6],
+            general_level_idc: 0,
+        }
+    }
+}
+
+impl HevcTileTierLevelConfigurationRecord {
+    fn from_stream<T: Stream>(stream: &mut T) -> Result<Self> {
+        let configuration_version = stream.read_byte()?;
+        let general_profile_space = stream.read_bits(2)? as u8;
+        let general_tier_flag = stream.read_bits(1)? as u8;
+        let general_profile_idc = stream.read_bits(5)? as u8;
+        let general_profile_compatibility_flags = stream.read_4bytes()?.to_u32();
+        let mut general_constraint_indicator_flags = [0u8; 6];
+        for flag in general_constraint_indicator_flags.iter_mut() {
+            *flag = stream.read_byte()?;
+        }
+        let general_level_idc = stream.read_byte()?;
+        Ok(Self {
+            configuration_version,
+            general_profile_space,
+            general_tier_flag,
+            general_profile_idc,
+            general_profile_compatibility_flags,
+            general_constraint_indicator_flags,
+            general_level_idc,
+        })
+    }
+
+    pub fn general_profile_idc(&self) -> u8 {
+        self.general_profile_idc
+    }
+
+    pub fn general_tier_flag(&self) -> u8 {
+        self.general_tier_flag
+    }
+
+    pub fn general_level_idc(&self) -> u8 {
+        self.general_level_idc
+    }
+}
+
 #[derive(Debug)]
 pub struct HevcDecoderConfigurationRecord {
     configuration_version: u8,
@@ -107,20 +307,7 @@ impl Default for HevcDecoderConfigurationRecord {
 
 impl DecoderConfigurationRecord for HevcDecoderConfigurationRecord {
     fn configuration_map(&self) -> ConfigurationMap {
-        let mut m = ConfigurationMap::default();
-        m.insert(
-            DecoderParameterType::HevcSPS,
-            self.get_one_parameter_set(HevcNalUnitType::Sps),
-        );
-        m.insert(
-            DecoderParameterType::HevcPPS,
-            self.get_one_parameter_set(HevcNalUnitType::Pps),
-        );
-        m.insert(
-            DecoderParameterType::HevcVPS,
-            self.get_one_parameter_set(HevcNalUnitType::Vps),
-        );
-        m
+        parameter_set_configuration_map(&self.nal_array)
     }
 }
 
@@ -177,62 +364,84 @@ impl HevcDecoderConfigurationRecord {
             length_size_minus1,
             nal_array: Vec::new(),
         };
-        let num_arrays = stream.read_byte()?;
-        for _ in 0..num_arrays {
-            let array_completeness = stream.read_bits(1)? != 0;
-            stream.read_bits(1)?;
-            let nal_unit_type = HevcNalUnitType::from_u8(stream.read_bits(6)? as u8);
-            let num_nalus = stream.read_2bytes()?.to_u16();
-            for _ in 0..num_nalus {
-                let nal_size = stream.read_2bytes()?.to_usize();
-                let nal_data = stream.read_bytes(nal_size)?;
-                res.add_nal_unit(nal_data, nal_unit_type, array_completeness);
-            }
-        }
+        res.nal_array = read_nal_arrays(stream)?;
         Ok(res)
     }
+}
 
-    fn add_nal_unit(
-        &mut self,
-        nal_unit: &[u8],
-        nal_unit_type: HevcNalUnitType,
-        array_completeness: bool,
-    ) {
-        let start_code_len = find_start_code_len(nal_unit);
-        let v = nal_unit[start_code_len..].to_vec();
-        match self
-            .nal_array
-            .iter_mut()
-            .find(|unit| unit.nal_unit_type == nal_unit_type)
-        {
-            Some(n) => {
-                n.nal_list.push(v);
-            }
-            None => {
-                let tmp = NalArray {
-                    array_completeness,
-                    nal_unit_type,
-                    nal_list: vec![v],
-                };
-                self.nal_array.push(tmp);
-            }
-        };
+fn read_nal_arrays<T: Stream>(stream: &mut T) -> Result<Vec<NalArray>> {
+    let mut nal_array = Vec::new();
+    let num_arrays = stream.read_byte()?;
+    for _ in 0..num_arrays {
+        let array_completeness = stream.read_bits(1)? != 0;
+        stream.read_bits(1)?;
+        let nal_unit_type = HevcNalUnitType::from_u8(stream.read_bits(6)? as u8);
+        let num_nalus = stream.read_2bytes()?.to_u16();
+        for _ in 0..num_nalus {
+            let nal_size = stream.read_2bytes()?.to_usize();
+            let nal_data = stream.read_bytes(nal_size)?;
+            add_nal_unit(&mut nal_array, nal_data, nal_unit_type, array_completeness);
+        }
     }
+    Ok(nal_array)
+}
+
+fn add_nal_unit(
+    nal_array: &mut Vec<NalArray>,
+    nal_unit: &[u8],
+    nal_unit_type: HevcNalUnitType,
+    array_completeness: bool,
+) {
+    let start_code_len = find_start_code_len(nal_unit);
+    let v = nal_unit[start_code_len..].to_vec();
+    match nal_array
+        .iter_mut()
+        .find(|unit| unit.nal_unit_type == nal_unit_type)
+    {
+        Some(n) => {
+            n.nal_list.push(v);
+        }
+        None => {
+            let tmp = NalArray {
+                array_completeness,
+                nal_unit_type,
+                nal_list: vec![v],
+            };
+            nal_array.push(tmp);
+        }
+    };
+}
 
-    fn get_one_parameter_set(&self, nal_unit_type: HevcNalUnitType) -> Vec<u8> {
-        let mut res = Vec::<u8>::new();
-        for array in &self.nal_array {
-            if array.nal_unit_type == nal_unit_type && !array.nal_list.is_empty() {
-                res.push(0);
-                res.push(0);
-                res.push(0);
-                res.push(1);
-                let mut d = array.nal_list[0].clone();
-                res.append(&mut d);
-            }
+fn get_one_parameter_set(nal_array: &[NalArray], nal_unit_type: HevcNalUnitType) -> Vec<u8> {
+    let mut res = Vec::<u8>::new();
+    for array in nal_array {
+        if array.nal_unit_type == nal_unit_type && !array.nal_list.is_empty() {
+            res.push(0);
+            res.push(0);
+            res.push(0);
+            res.push(1);
+            let mut d = array.nal_list[0].clone();
+            res.append(&mut d);
         }
-        res
     }
+    res
+}
+
+fn parameter_set_configuration_map(nal_array: &[NalArray]) -> ConfigurationMap {
+    let mut m = ConfigurationMap::default();
+    m.insert(
+        DecoderParameterType::HevcSPS,
+        get_one_parameter_set(nal_array, HevcNalUnitType::Sps),
+    );
+    m.insert(
+        DecoderParameterType::HevcPPS,
+        get_one_parameter_set(nal_array, HevcNalUnitType::Pps),
+    );
+    m.insert(
+        DecoderParameterType::HevcVPS,
+        get_one_parameter_set(nal_array, HevcNalUnitType::Vps),
+    );
+    m
 }
 
 fn find_start_code_len(data: &[u8]) -> usize {
@@ -404,3 +613,25 @@ struct NalArray {
     nal_unit_type: HevcNalUnitType,
     nal_list: Vec<Vec<u8>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_hvtc() {
+        let mut stream = BitStream::new(vec![
+            0x00, 0x00, 0x00, 0x15, 0x68, 0x76, 0x74, 0x43, // size, 'hvtC'
+            0x01, 0x21, 0x60, 0x00, 0x00, 0x00, // version, tier 1, profile 1, compatibility
+            0x90, 0x00, 0x00, 0x00, 0x00, 0x00, // constraint flags
+            0x5D, // level
+        ]);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let hvtc = HevcTileConfigurationBox::from_stream_header(&mut stream, header).unwrap();
+        assert!(stream.is_eof());
+        assert_eq!(hvtc.config().general_tier_flag(), 1);
+        assert_eq!(hvtc.config().general_profile_idc(), 1);
+        assert_eq!(hvtc.config().general_level_idc(), 93);
+    }
+}
@@ -0,0 +1,51 @@
+use crate::bbox::header::BoxHeader;
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+#[derive(Debug)]
+pub struct ImageMirrorProperty {
+    box_header: BoxHeader,
+    axis: u8,
+}
+
+impl Default for ImageMirrorProperty {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl BBox for ImageMirrorProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl ImageMirrorProperty {
+    pub fn new(axis: u8) -> Self {
+        Self {
+            box_header: BoxHeader::new("imir".parse().unwrap()),
+            axis,
+        }
+    }
+
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        stream.read_bits(7)?;
+        let axis = stream.read_bits(1)? as u8;
+        Ok(Self { box_header, axis })
+    }
+
+    // 0 mirrors about a vertical axis (left-right), 1 about a horizontal
+    // axis (top-bottom).
+    pub fn axis(&self) -> u8 {
+        self.axis
+    }
+
+    pub fn set_axis(&mut self, axis: u8) {
+        self.axis = axis;
+    }
+}
@@ -0,0 +1,54 @@
+use crate::bbox::header::BoxHeader;
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+#[derive(Debug)]
+pub struct ImageRotationProperty {
+    box_header: BoxHeader,
+    angle: u8,
+}
+
+impl Default for ImageRotationProperty {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl BBox for ImageRotationProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl ImageRotationProperty {
+    pub fn new(angle: u8) -> Self {
+        Self {
+            box_header: BoxHeader::new("irot".parse().unwrap()),
+            angle,
+        }
+    }
+
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        stream.read_bits(6)?;
+        let angle = stream.read_bits(2)? as u8;
+        Ok(Self { box_header, angle })
+    }
+
+    // Anti-clockwise rotation in units of 90 degrees.
+    pub fn angle(&self) -> u8 {
+        self.angle
+    }
+
+    pub fn set_angle(&mut self, angle: u8) {
+        self.angle = angle;
+    }
+
+    pub fn angle_in_degrees(&self) -> u32 {
+        u32::from(self.angle) * 90
+    }
+}
@@ -0,0 +1,88 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+// Scales the image by the given fractions of its width and height.
+#[derive(Debug)]
+pub struct ImageScalingProperty {
+    full_box_header: FullBoxHeader,
+    target_width_numerator: u16,
+    target_width_denominator: u16,
+    target_height_numerator: u16,
+    target_height_denominator: u16,
+}
+
+impl Default for ImageScalingProperty {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("iscl".parse().unwrap(), 0, 0),
+            target_width_numerator: 1,
+            target_width_denominator: 1,
+            target_height_numerator: 1,
+            target_height_denominator: 1,
+        }
+    }
+}
+
+impl BBox for ImageScalingProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl ImageScalingProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        Ok(Self {
+            full_box_header,
+            target_width_numerator: stream.read_2bytes()?.to_u16(),
+            target_width_denominator: stream.read_2bytes()?.to_u16(),
+            target_height_numerator: stream.read_2bytes()?.to_u16(),
+            target_height_denominator: stream.read_2bytes()?.to_u16(),
+        })
+    }
+
+    pub fn target_width_numerator(&self) -> u16 {
+        self.target_width_numerator
+    }
+
+    pub fn target_width_denominator(&self) -> u16 {
+        self.target_width_denominator
+    }
+
+    pub fn target_height_numerator(&self) -> u16 {
+        self.target_height_numerator
+    }
+
+    pub fn target_height_denominator(&self) -> u16 {
+        self.target_height_denominator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_iscl() {
+        let mut stream = BitStream::new(vec![
+            0x00, 0x00, 0x00, 0x14, 0x69, 0x73, 0x63, 0x6C, // size, 'iscl'
+            0x00, 0x00, 0x00, 0x00, // version, flags
+            0x00, 0x01, 0x00, 0x02, // target width 1/2
+            0x00, 0x03, 0x00, 0x04, // target height 3/4
+        ]);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let iscl = ImageScalingProperty::from_stream_header(&mut stream, header).unwrap();
+        assert!(stream.is_eof());
+        assert_eq!(iscl.target_width_numerator(), 1);
+        assert_eq!(iscl.target_width_denominator(), 2);
+        assert_eq!(iscl.target_height_numerator(), 3);
+        assert_eq!(iscl.target_height_denominator(), 4);
+    }
+}
@@ -0,0 +1,109 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LayerInfo {
+    pub layer_id: u8,
+    pub min_sub_layer_id: u8,
+    pub max_sub_layer_id: u8,
+    pub sub_layer_presence_flags: u8,
+}
+
+#[derive(Debug)]
+pub struct LayerInformationProperty {
+    full_box_header: FullBoxHeader,
+    layers: Vec<LayerInfo>,
+}
+
+impl Default for LayerInformationProperty {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("linf".parse().unwrap(), 0, 0),
+            layers: Vec::new(),
+        }
+    }
+}
+
+impl BBox for LayerInformationProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl LayerInformationProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        stream.read_bits(2)?;
+        let num_layers_in_track = stream.read_bits(6)?;
+        let mut layers = Vec::new();
+        for _ in 0..num_layers_in_track {
+            stream.read_bits(4)?;
+            let layer_id = stream.read_bits(6)? as u8;
+            let min_sub_layer_id = stream.read_bits(3)? as u8;
+            let max_sub_layer_id = stream.read_bits(3)? as u8;
+            stream.read_bits(1)?;
+            let sub_layer_presence_flags = stream.read_bits(7)? as u8;
+            layers.push(LayerInfo {
+                layer_id,
+                min_sub_layer_id,
+                max_sub_layer_id,
+                sub_layer_presence_flags,
+            });
+        }
+        Ok(Self {
+            full_box_header,
+            layers,
+        })
+    }
+
+    pub fn layers(&self) -> &Vec<LayerInfo> {
+        &self.layers
+    }
+
+    pub fn add_layer(&mut self, layer: LayerInfo) {
+        self.layers.push(layer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_linf() {
+        let mut stream = BitStream::new(vec![
+            0x00, 0x00, 0x00, 0x13, 0x6C, 0x69, 0x6E, 0x66, // size, 'linf'
+            0x00, 0x00, 0x00, 0x00, // version, flags
+            0x02, // num_layers_in_track
+            0x00, 0x02, 0x07, // layer 0, sub layers 0..2, presence 0b0000111
+            0x00, 0x4B, 0x03, // layer 1, sub layers 1..3, presence 0b0000011
+        ]);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let linf = LayerInformationProperty::from_stream_header(&mut stream, header).unwrap();
+        assert!(stream.is_eof());
+        assert_eq!(
+            linf.layers(),
+            &vec![
+                LayerInfo {
+                    layer_id: 0,
+                    min_sub_layer_id: 0,
+                    max_sub_layer_id: 2,
+                    sub_layer_presence_flags: 0x07,
+                },
+                LayerInfo {
+                    layer_id: 1,
+                    min_sub_layer_id: 1,
+                    max_sub_layer_id: 3,
+                    sub_layer_presence_flags: 0x03,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use crate::bbox::header::BoxHeader;
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+// Chromaticity coordinates are in units of 0.00002 and luminance in units
+// of 0.0001 cd/m², as in the SEI message of H.265. The display primaries
+// are in green, blue, red order.
+#[derive(Debug)]
+pub struct MasteringDisplayColorVolumeProperty {
+    box_header: BoxHeader,
+    display_primaries: [(u16, u16); 3],
+    white_point: (u16, u16),
+    max_display_mastering_luminance: u32,
+    min_display_mastering_luminance: u32,
+}
+
+impl Default for MasteringDisplayColorVolumeProperty {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("mdcv".parse().unwrap()),
+            display_primaries: [(0, 0); 3],
+            white_point: (0, 0),
+            max_display_mastering_luminance: 0,
+            min_display_mastering_luminance: 0,
+        }
+    }
+}
+
+impl BBox for MasteringDisplayColorVolumeProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl MasteringDisplayColorVolumeProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let mut res = Self {
+            box_header,
+            ..Self::default()
+        };
+        for primary in res.display_primaries.iter_mut() {
+            *primary = (
+                stream.read_2bytes()?.to_u16(),
+                stream.read_2bytes()?.to_u16(),
+            );
+        }
+        res.white_point = (
+            stream.read_2bytes()?.to_u16(),
+            stream.read_2bytes()?.to_u16(),
+        );
+        res.max_display_mastering_luminance = stream.read_4bytes()?.to_u32();
+        res.min_display_mastering_luminance = stream.read_4bytes()?.to_u32();
+        Ok(res)
+    }
+
+    pub fn display_primaries(&self) -> &[(u16, u16); 3] {
+        &self.display_primaries
+    }
+
+    pub fn white_point(&self) -> (u16, u16) {
+        self.white_point
+    }
+
+    pub fn max_display_mastering_luminance(&self) -> u32 {
+        self.max_display_mastering_luminance
+    }
+
+    pub fn min_display_mastering_luminance(&self) -> u32 {
+        self.min_display_mastering_luminance
+    }
+}
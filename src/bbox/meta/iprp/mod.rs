@@ -1,5 +1,27 @@
+pub mod aebr;
+pub mod afbr;
+pub mod amve;
+pub mod auxc;
+pub mod cclv;
+pub mod clap;
+pub mod clli;
+pub mod cmpd;
+pub mod colr;
+pub mod fobr;
 pub mod hevc;
+pub mod imir;
+pub mod irot;
+pub mod iscl;
 pub mod ispe;
+pub mod linf;
+pub mod mdcv;
+pub mod oinf;
+pub mod pasp;
+pub mod rloc;
+pub mod tols;
+pub mod uncc;
+pub mod vvc;
+pub mod wbbr;
 
 use std::collections::HashMap;
 
@@ -7,8 +29,30 @@ use crate::bbox::header::{BoxHeader, FullBoxHeader};
 use crate::bbox::BBox;
 use crate::bit::{Byte4, Stream};
 use crate::{HeifError, Result};
-use hevc::HevcConfigurationBox;
+use aebr::AutoExposureProperty;
+use afbr::FlashExposureProperty;
+use amve::AmbientViewingEnvironmentProperty;
+use auxc::AuxiliaryTypeProperty;
+use cclv::ContentColorVolumeProperty;
+use clap::CleanApertureProperty;
+use clli::ContentLightLevelProperty;
+use cmpd::ComponentDefinitionBox;
+use colr::ColorInformationProperty;
+use fobr::FocusProperty;
+use hevc::{HevcConfigurationBox, HevcTileConfigurationBox, LHevcConfigurationBox};
+use imir::ImageMirrorProperty;
+use irot::ImageRotationProperty;
+use iscl::ImageScalingProperty;
 use ispe::ImageSpatialExtentsProperty;
+use linf::LayerInformationProperty;
+use mdcv::MasteringDisplayColorVolumeProperty;
+use oinf::OperatingPointsInformationProperty;
+use pasp::PixelAspectRatioProperty;
+use rloc::RelativeLocationProperty;
+use tols::TargetOlsProperty;
+use uncc::UncompressedFrameConfigBox;
+use vvc::VvcConfigurationBox;
+use wbbr::WhiteBalanceProperty;
 
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub enum DecoderParameterType {
@@ -17,6 +61,10 @@ pub enum DecoderParameterType {
     HevcVPS,
     HevcSPS,
     HevcPPS,
+    VvcVPS,
+    VvcSPS,
+    VvcPPS,
+    VvcAPS,
     AudioSpecificConfig,
 }
 
@@ -107,20 +155,37 @@ impl ItemPropertiesBox {
 
     fn get_property_type(&self, property: &Box<BBox>) -> PropertyType {
         match property.box_type().to_string().as_str() {
+            "aebr" => PropertyType::AEBR,
+            "afbr" => PropertyType::AFBR,
+            "amve" => PropertyType::AMVE,
             "auxC" => PropertyType::AUXC,
             "avcC" => PropertyType::AVCC,
+            "cclv" => PropertyType::CCLV,
             "clap" => PropertyType::CLAP,
+            "clli" => PropertyType::CLLI,
+            "cmpd" => PropertyType::CMPD,
             "colr" => PropertyType::COLR,
+            "fobr" => PropertyType::FOBR,
             "free" => PropertyType::FREE,
             "hvcC" => PropertyType::HVCC,
+            "hvtC" => PropertyType::HVTC,
             "imir" => PropertyType::IMIR,
             "irot" => PropertyType::IROT,
+            "iscl" => PropertyType::ISCL,
             "ispe" => PropertyType::ISPE,
             "jpgC" => PropertyType::JPGC,
+            "lhvC" => PropertyType::LHVC,
+            "linf" => PropertyType::LINF,
+            "mdcv" => PropertyType::MDCV,
+            "oinf" => PropertyType::OINF,
             "pasp" => PropertyType::PASP,
             "pixi" => PropertyType::PIXI,
             "rloc" => PropertyType::RLOC,
             "skip" => PropertyType::FREE,
+            "tols" => PropertyType::TOLS,
+            "uncC" => PropertyType::UNCC,
+            "vvcC" => PropertyType::VVCC,
+            "wbbr" => PropertyType::WBBR,
             _ => PropertyType::RAW,
         }
     }
@@ -169,19 +234,36 @@ pub type PropertyInfos = Vec<PropertyInfo>;
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum PropertyType {
     RAW,
+    AEBR,
+    AFBR,
+    AMVE,
     AUXC,
     AVCC,
+    CCLV,
     CLAP,
+    CLLI,
+    CMPD,
     COLR,
+    FOBR,
     FREE,
     HVCC,
+    HVTC,
     IMIR,
     IROT,
+    ISCL,
     ISPE,
     JPGC,
+    LHVC,
+    LINF,
+    MDCV,
+    OINF,
     PASP,
     PIXI,
     RLOC,
+    TOLS,
+    UNCC,
+    VVCC,
+    WBBR,
 }
 
 pub struct ItemPropertyContainer {
@@ -224,16 +306,108 @@ impl ItemPropertyContainer {
             let sub_box_header = BoxHeader::from_stream(stream)?;
             let mut ex = stream.extract_from(&sub_box_header)?;
             let property: Box<BBox> = match sub_box_header.box_type().to_string().as_str() {
+                "aebr" => Box::new(AutoExposureProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "afbr" => Box::new(FlashExposureProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "amve" => Box::new(AmbientViewingEnvironmentProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "auxC" => Box::new(AuxiliaryTypeProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "cclv" => Box::new(ContentColorVolumeProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "clap" => Box::new(CleanApertureProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "clli" => Box::new(ContentLightLevelProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "cmpd" => Box::new(ComponentDefinitionBox::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "colr" => Box::new(ColorInformationProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "fobr" => Box::new(FocusProperty::from_stream_header(&mut ex, sub_box_header)?),
                 "hvcC" => Box::new(HevcConfigurationBox::from_stream_header(
                     &mut ex,
                     sub_box_header,
                 )?),
+                "hvtC" => Box::new(HevcTileConfigurationBox::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "imir" => Box::new(ImageMirrorProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "irot" => Box::new(ImageRotationProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "iscl" => Box::new(ImageScalingProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
                 "ispe" => Box::new(ImageSpatialExtentsProperty::from_stream_header(
                     &mut ex,
                     sub_box_header,
                 )?),
-                // TODO:
-                _ => unimplemented!("itemprop {}", sub_box_header.box_type().to_string()),
+                "lhvC" => Box::new(LHevcConfigurationBox::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "linf" => Box::new(LayerInformationProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "mdcv" => Box::new(MasteringDisplayColorVolumeProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "oinf" => Box::new(OperatingPointsInformationProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "pasp" => Box::new(PixelAspectRatioProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "rloc" => Box::new(RelativeLocationProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "tols" => Box::new(TargetOlsProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "uncC" => Box::new(UncompressedFrameConfigBox::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "vvcC" => Box::new(VvcConfigurationBox::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                "wbbr" => Box::new(WhiteBalanceProperty::from_stream_header(
+                    &mut ex,
+                    sub_box_header,
+                )?),
+                _ => Box::new(RawPropertyBox::from_stream_header(&mut ex, sub_box_header)?),
             };
             properties.push(property);
         }
@@ -252,6 +426,36 @@ impl ItemPropertyContainer {
     }
 }
 
+// A property this crate has no parser for. The payload is kept as is.
+#[derive(Debug)]
+pub struct RawPropertyBox {
+    box_header: BoxHeader,
+    data: Vec<u8>,
+}
+
+impl BBox for RawPropertyBox {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl RawPropertyBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        Ok(Self {
+            box_header,
+            data: stream.read_bytes(stream.num_bytes_left())?.to_vec(),
+        })
+    }
+
+    pub fn data(&self) -> &Vec<u8> {
+        &self.data
+    }
+}
+
 type AssociationEntries = Vec<AssociationEntry>;
 
 #[derive(Debug)]
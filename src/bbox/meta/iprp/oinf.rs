@@ -0,0 +1,244 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ProfileTierLevelInfo {
+    pub general_profile_space: u8,
+    pub general_tier_flag: u8,
+    pub general_profile_idc: u8,
+    pub general_profile_compatibility_flags: u32,
+    pub general_constraint_indicator_flags: [u8; 6],
+    pub general_level_idc: u8,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OperatingPointLayer {
+    pub ptl_idx: u8,
+    pub layer_id: u8,
+    pub is_output_layer: bool,
+    pub is_alternate_output_layer: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OperatingPoint {
+    pub output_layer_set_idx: u16,
+    pub max_temporal_id: u8,
+    pub layers: Vec<OperatingPointLayer>,
+    pub min_pic_width: u16,
+    pub min_pic_height: u16,
+    pub max_pic_width: u16,
+    pub max_pic_height: u16,
+    pub max_chroma_format: u8,
+    pub max_bit_depth_minus8: u8,
+    pub avg_frame_rate: u16,
+    pub constant_frame_rate: u8,
+    pub max_bit_rate: u32,
+    pub avg_bit_rate: u32,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LayerDependency {
+    pub layer_id: u8,
+    pub direct_ref_layer_ids: Vec<u8>,
+    pub dimension_identifiers: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct OperatingPointsInformationProperty {
+    full_box_header: FullBoxHeader,
+    scalability_mask: u16,
+    profile_tier_levels: Vec<ProfileTierLevelInfo>,
+    operating_points: Vec<OperatingPoint>,
+    layer_dependencies: Vec<LayerDependency>,
+}
+
+impl Default for OperatingPointsInformationProperty {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("oinf".parse().unwrap(), 0, 0),
+            scalability_mask: 0,
+            profile_tier_levels: Vec::new(),
+            operating_points: Vec::new(),
+            layer_dependencies: Vec::new(),
+        }
+    }
+}
+
+impl BBox for OperatingPointsInformationProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl OperatingPointsInformationProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let scalability_mask = stream.read_2bytes()?.to_u16();
+        stream.read_bits(2)?;
+        let num_profile_tier_level = stream.read_bits(6)?;
+        let mut profile_tier_levels = Vec::new();
+        for _ in 0..num_profile_tier_level {
+            let general_profile_space = stream.read_bits(2)? as u8;
+            let general_tier_flag = stream.read_bits(1)? as u8;
+            let general_profile_idc = stream.read_bits(5)? as u8;
+            let general_profile_compatibility_flags = stream.read_4bytes()?.to_u32();
+            let mut general_constraint_indicator_flags = [0u8; 6];
+            for flag in general_constraint_indicator_flags.iter_mut() {
+                *flag = stream.read_byte()?;
+            }
+            let general_level_idc = stream.read_byte()?;
+            profile_tier_levels.push(ProfileTierLevelInfo {
+                general_profile_space,
+                general_tier_flag,
+                general_profile_idc,
+                general_profile_compatibility_flags,
+                general_constraint_indicator_flags,
+                general_level_idc,
+            });
+        }
+
+        let num_operating_points = stream.read_2bytes()?.to_u16();
+        let mut operating_points = Vec::new();
+        for _ in 0..num_operating_points {
+            let output_layer_set_idx = stream.read_2bytes()?.to_u16();
+            let max_temporal_id = stream.read_byte()?;
+            let layer_count = stream.read_byte()?;
+            let mut layers = Vec::new();
+            for _ in 0..layer_count {
+                let ptl_idx = stream.read_byte()?;
+                let layer_id = stream.read_bits(6)? as u8;
+                let is_output_layer = stream.read_bits(1)? != 0;
+                let is_alternate_output_layer = stream.read_bits(1)? != 0;
+                layers.push(OperatingPointLayer {
+                    ptl_idx,
+                    layer_id,
+                    is_output_layer,
+                    is_alternate_output_layer,
+                });
+            }
+            let mut op = OperatingPoint {
+                output_layer_set_idx,
+                max_temporal_id,
+                layers,
+                min_pic_width: stream.read_2bytes()?.to_u16(),
+                min_pic_height: stream.read_2bytes()?.to_u16(),
+                max_pic_width: stream.read_2bytes()?.to_u16(),
+                max_pic_height: stream.read_2bytes()?.to_u16(),
+                max_chroma_format: stream.read_bits(2)? as u8,
+                max_bit_depth_minus8: stream.read_bits(3)? as u8,
+                ..OperatingPoint::default()
+            };
+            stream.read_bits(1)?;
+            let frame_rate_info_flag = stream.read_bits(1)? != 0;
+            let bit_rate_info_flag = stream.read_bits(1)? != 0;
+            if frame_rate_info_flag {
+                op.avg_frame_rate = stream.read_2bytes()?.to_u16();
+                stream.read_bits(6)?;
+                op.constant_frame_rate = stream.read_bits(2)? as u8;
+            }
+            if bit_rate_info_flag {
+                op.max_bit_rate = stream.read_4bytes()?.to_u32();
+                op.avg_bit_rate = stream.read_4bytes()?.to_u32();
+            }
+            operating_points.push(op);
+        }
+
+        let max_layer_count = stream.read_byte()?;
+        let mut layer_dependencies = Vec::new();
+        for _ in 0..max_layer_count {
+            let layer_id = stream.read_byte()?;
+            let num_direct_ref_layers = stream.read_byte()?;
+            let mut direct_ref_layer_ids = Vec::new();
+            for _ in 0..num_direct_ref_layers {
+                direct_ref_layer_ids.push(stream.read_byte()?);
+            }
+            let mut dimension_identifiers = Vec::new();
+            for j in 0..16 {
+                if scalability_mask & (1 << j) != 0 {
+                    dimension_identifiers.push(stream.read_byte()?);
+                }
+            }
+            layer_dependencies.push(LayerDependency {
+                layer_id,
+                direct_ref_layer_ids,
+                dimension_identifiers,
+            });
+        }
+        Ok(Self {
+            full_box_header,
+            scalability_mask,
+            profile_tier_levels,
+            operating_points,
+            layer_dependencies,
+        })
+    }
+
+    pub fn scalability_mask(&self) -> u16 {
+        self.scalability_mask
+    }
+
+    pub fn profile_tier_levels(&self) -> &Vec<ProfileTierLevelInfo> {
+        &self.profile_tier_levels
+    }
+
+    pub fn operating_points(&self) -> &Vec<OperatingPoint> {
+        &self.operating_points
+    }
+
+    pub fn operating_point_by_ols_idx(&self, ols_idx: u16) -> Option<&OperatingPoint> {
+        self.operating_points
+            .iter()
+            .find(|op| op.output_layer_set_idx == ols_idx)
+    }
+
+    pub fn layer_dependencies(&self) -> &Vec<LayerDependency> {
+        &self.layer_dependencies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_oinf_from_stream() {
+        let mut stream = BitStream::new(vec![
+            0x00, 0x00, 0x00, 0x36, 0x6F, 0x69, 0x6E, 0x66, // size, 'oinf'
+            0x00, 0x00, 0x00, 0x00, // version, flags
+            0x00, 0x02, // scalability_mask
+            0x01, // num_profile_tier_level
+            0x01, 0x60, 0x00, 0x00, 0x00, 0x90, 0x00, 0x00, 0x00, 0x00, 0x00, 0x5A, // ptl
+            0x00, 0x01, // num_operating_points
+            0x00, 0x01, 0x00, 0x02, // ols idx, max temporal id, layer count
+            0x00, 0x00, 0x00, 0x06, // layer 0 and layer 1 (output)
+            0x05, 0xA0, 0x03, 0xC0, 0x05, 0xA0, 0x03, 0xC0, // picture sizes
+            0x40, // chroma format 1, bit depth 8, no frame/bit rate info
+            0x02, // max_layer_count
+            0x00, 0x00, 0x00, // layer 0, no refs, dimension id
+            0x01, 0x01, 0x00, 0x01, // layer 1, refs layer 0, dimension id
+        ]);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let oinf =
+            OperatingPointsInformationProperty::from_stream_header(&mut stream, header).unwrap();
+        assert!(stream.is_eof());
+        assert_eq!(oinf.scalability_mask(), 2);
+        assert_eq!(oinf.profile_tier_levels()[0].general_profile_idc, 1);
+        assert_eq!(oinf.profile_tier_levels()[0].general_level_idc, 90);
+        let op = oinf.operating_point_by_ols_idx(1).unwrap();
+        assert_eq!(op.layers.len(), 2);
+        assert!(!op.layers[0].is_output_layer);
+        assert_eq!(op.layers[1].layer_id, 1);
+        assert!(op.layers[1].is_output_layer);
+        assert_eq!(op.max_pic_width, 1440);
+        assert_eq!(op.max_chroma_format, 1);
+        assert_eq!(oinf.layer_dependencies()[1].direct_ref_layer_ids, vec![0]);
+        assert_eq!(oinf.layer_dependencies()[1].dimension_identifiers, vec![1]);
+    }
+}
@@ -0,0 +1,70 @@
+use crate::bbox::header::BoxHeader;
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+// The relative width and height of a pixel.
+#[derive(Debug)]
+pub struct PixelAspectRatioProperty {
+    box_header: BoxHeader,
+    h_spacing: u32,
+    v_spacing: u32,
+}
+
+impl Default for PixelAspectRatioProperty {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("pasp".parse().unwrap()),
+            h_spacing: 1,
+            v_spacing: 1,
+        }
+    }
+}
+
+impl BBox for PixelAspectRatioProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl PixelAspectRatioProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        Ok(Self {
+            box_header,
+            h_spacing: stream.read_4bytes()?.to_u32(),
+            v_spacing: stream.read_4bytes()?.to_u32(),
+        })
+    }
+
+    pub fn h_spacing(&self) -> u32 {
+        self.h_spacing
+    }
+
+    pub fn v_spacing(&self) -> u32 {
+        self.v_spacing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_pasp() {
+        let mut stream = BitStream::new(vec![
+            0x00, 0x00, 0x00, 0x10, 0x70, 0x61, 0x73, 0x70, // size, 'pasp'
+            0x00, 0x00, 0x00, 0x04, // h_spacing
+            0x00, 0x00, 0x00, 0x03, // v_spacing
+        ]);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let pasp = PixelAspectRatioProperty::from_stream_header(&mut stream, header).unwrap();
+        assert!(stream.is_eof());
+        assert_eq!(pasp.h_spacing(), 4);
+        assert_eq!(pasp.v_spacing(), 3);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+#[derive(Debug)]
+pub struct RelativeLocationProperty {
+    full_box_header: FullBoxHeader,
+    horizontal_offset: u32,
+    vertical_offset: u32,
+}
+
+impl Default for RelativeLocationProperty {
+    fn default() -> Self {
+        Self::new(0, 0)
+    }
+}
+
+impl BBox for RelativeLocationProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl RelativeLocationProperty {
+    pub fn new(horizontal_offset: u32, vertical_offset: u32) -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("rloc".parse().unwrap(), 0, 0),
+            horizontal_offset,
+            vertical_offset,
+        }
+    }
+
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let horizontal_offset = stream.read_4bytes()?.to_u32();
+        let vertical_offset = stream.read_4bytes()?.to_u32();
+        Ok(Self {
+            full_box_header,
+            horizontal_offset,
+            vertical_offset,
+        })
+    }
+
+    pub fn horizontal_offset(&self) -> u32 {
+        self.horizontal_offset
+    }
+
+    pub fn set_horizontal_offset(&mut self, offset: u32) {
+        self.horizontal_offset = offset;
+    }
+
+    pub fn vertical_offset(&self) -> u32 {
+        self.vertical_offset
+    }
+
+    pub fn set_vertical_offset(&mut self, offset: u32) {
+        self.vertical_offset = offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_rloc() {
+        let mut stream = BitStream::new(vec![
+            0x00, 0x00, 0x00, 0x14, 0x72, 0x6C, 0x6F, 0x63, // size, 'rloc'
+            0x00, 0x00, 0x00, 0x00, // version, flags
+            0x00, 0x00, 0x02, 0x00, // horizontal_offset
+            0x00, 0x00, 0x01, 0x00, // vertical_offset
+        ]);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let rloc = RelativeLocationProperty::from_stream_header(&mut stream, header).unwrap();
+        assert!(stream.is_eof());
+        assert_eq!(rloc.horizontal_offset(), 512);
+        assert_eq!(rloc.vertical_offset(), 256);
+    }
+}
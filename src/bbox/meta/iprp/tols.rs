@@ -0,0 +1,71 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+#[derive(Debug)]
+pub struct TargetOlsProperty {
+    full_box_header: FullBoxHeader,
+    target_ols_idx: u16,
+}
+
+impl Default for TargetOlsProperty {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl BBox for TargetOlsProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl TargetOlsProperty {
+    pub fn new(target_ols_idx: u16) -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("tols".parse().unwrap(), 0, 0),
+            target_ols_idx,
+        }
+    }
+
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let target_ols_idx = stream.read_2bytes()?.to_u16();
+        Ok(Self {
+            full_box_header,
+            target_ols_idx,
+        })
+    }
+
+    pub fn target_ols_idx(&self) -> u16 {
+        self.target_ols_idx
+    }
+
+    pub fn set_target_ols_idx(&mut self, idx: u16) {
+        self.target_ols_idx = idx;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_tols() {
+        let mut stream = BitStream::new(vec![
+            0x00, 0x00, 0x00, 0x0E, 0x74, 0x6F, 0x6C, 0x73, // size, 'tols'
+            0x00, 0x00, 0x00, 0x00, // version, flags
+            0x00, 0x02, // target_ols_idx
+        ]);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let tols = TargetOlsProperty::from_stream_header(&mut stream, header).unwrap();
+        assert!(stream.is_eof());
+        assert_eq!(tols.target_ols_idx(), 2);
+    }
+}
@@ -0,0 +1,249 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::meta::iprp::cmpd::{
+    COMPONENT_TYPE_ALPHA, COMPONENT_TYPE_BLUE, COMPONENT_TYPE_CB, COMPONENT_TYPE_CR,
+    COMPONENT_TYPE_GREEN, COMPONENT_TYPE_RED, COMPONENT_TYPE_Y,
+};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+pub const SAMPLING_TYPE_NO_SUBSAMPLING: u8 = 0;
+pub const SAMPLING_TYPE_422: u8 = 1;
+pub const SAMPLING_TYPE_420: u8 = 2;
+pub const SAMPLING_TYPE_411: u8 = 3;
+
+pub const INTERLEAVE_TYPE_COMPONENT: u8 = 0;
+pub const INTERLEAVE_TYPE_PIXEL: u8 = 1;
+pub const INTERLEAVE_TYPE_MIXED: u8 = 2;
+pub const INTERLEAVE_TYPE_ROW: u8 = 3;
+pub const INTERLEAVE_TYPE_TILE_COMPONENT: u8 = 4;
+pub const INTERLEAVE_TYPE_MULTI_Y: u8 = 5;
+
+pub const COMPONENT_FORMAT_UNSIGNED: u8 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UncompressedComponent {
+    pub component_index: u16,
+    pub component_bit_depth_minus_one: u8,
+    pub component_format: u8,
+    pub component_align_size: u8,
+}
+
+impl UncompressedComponent {
+    fn new_unsigned(component_index: u16, bit_depth: u8) -> Self {
+        Self {
+            component_index,
+            component_bit_depth_minus_one: bit_depth - 1,
+            component_format: COMPONENT_FORMAT_UNSIGNED,
+            component_align_size: 0,
+        }
+    }
+
+    // Up to 256, so wider than the field it comes from.
+    pub fn bit_depth(&self) -> u16 {
+        u16::from(self.component_bit_depth_minus_one) + 1
+    }
+}
+
+#[derive(Debug)]
+pub struct UncompressedFrameConfigBox {
+    full_box_header: FullBoxHeader,
+    profile: Byte4,
+    components: Vec<UncompressedComponent>,
+    sampling_type: u8,
+    interleave_type: u8,
+    block_size: u8,
+    components_little_endian: bool,
+    block_pad_lsb: bool,
+    block_little_endian: bool,
+    block_reversed: bool,
+    pad_unknown: bool,
+    pixel_size: u32,
+    row_align_size: u32,
+    tile_align_size: u32,
+    num_tile_cols_minus_one: u32,
+    num_tile_rows_minus_one: u32,
+}
+
+impl Default for UncompressedFrameConfigBox {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("uncC".parse().unwrap(), 0, 0),
+            profile: Byte4::default(),
+            components: Vec::new(),
+            sampling_type: SAMPLING_TYPE_NO_SUBSAMPLING,
+            interleave_type: INTERLEAVE_TYPE_COMPONENT,
+            block_size: 0,
+            components_little_endian: false,
+            block_pad_lsb: false,
+            block_little_endian: false,
+            block_reversed: false,
+            pad_unknown: false,
+            pixel_size: 0,
+            row_align_size: 0,
+            tile_align_size: 0,
+            num_tile_cols_minus_one: 0,
+            num_tile_rows_minus_one: 0,
+        }
+    }
+}
+
+impl BBox for UncompressedFrameConfigBox {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl UncompressedFrameConfigBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let mut res = Self {
+            full_box_header,
+            profile: stream.read_4bytes()?,
+            ..Self::default()
+        };
+        if res.full_box_header.version() == 1 {
+            // Version 1 only carries a profile. The frame configuration is
+            // implied by it.
+            res.apply_profile();
+            return Ok(res);
+        }
+        let component_count = stream.read_4bytes()?.to_u32();
+        for _ in 0..component_count {
+            res.components.push(UncompressedComponent {
+                component_index: stream.read_2bytes()?.to_u16(),
+                component_bit_depth_minus_one: stream.read_byte()?,
+                component_format: stream.read_byte()?,
+                component_align_size: stream.read_byte()?,
+            });
+        }
+        res.sampling_type = stream.read_byte()?;
+        res.interleave_type = stream.read_byte()?;
+        res.block_size = stream.read_byte()?;
+        res.components_little_endian = stream.read_flag()?;
+        res.block_pad_lsb = stream.read_flag()?;
+        res.block_little_endian = stream.read_flag()?;
+        res.block_reversed = stream.read_flag()?;
+        res.pad_unknown = stream.read_flag()?;
+        stream.read_bits(3)?;
+        res.pixel_size = stream.read_4bytes()?.to_u32();
+        res.row_align_size = stream.read_4bytes()?.to_u32();
+        res.tile_align_size = stream.read_4bytes()?.to_u32();
+        res.num_tile_cols_minus_one = stream.read_4bytes()?.to_u32();
+        res.num_tile_rows_minus_one = stream.read_4bytes()?.to_u32();
+        Ok(res)
+    }
+
+    fn apply_profile(&mut self) {
+        let (interleave_type, sampling_type) = match self.profile.to_string().as_str() {
+            "rgb3" | "rgba" | "abgr" => (INTERLEAVE_TYPE_PIXEL, SAMPLING_TYPE_NO_SUBSAMPLING),
+            "i420" => (INTERLEAVE_TYPE_COMPONENT, SAMPLING_TYPE_420),
+            "nv12" | "nv21" => (INTERLEAVE_TYPE_MIXED, SAMPLING_TYPE_420),
+            _ => return,
+        };
+        self.interleave_type = interleave_type;
+        self.sampling_type = sampling_type;
+        if let Some(types) = self.profile_component_types() {
+            self.components = (0..types.len())
+                .map(|i| UncompressedComponent::new_unsigned(i as u16, 8))
+                .collect();
+        }
+    }
+
+    // The component types a version 1 profile implies in place of a cmpd box.
+    pub fn profile_component_types(&self) -> Option<Vec<u16>> {
+        if self.full_box_header.version() != 1 {
+            return None;
+        }
+        match self.profile.to_string().as_str() {
+            "rgb3" => Some(vec![
+                COMPONENT_TYPE_RED,
+                COMPONENT_TYPE_GREEN,
+                COMPONENT_TYPE_BLUE,
+            ]),
+            "rgba" => Some(vec![
+                COMPONENT_TYPE_RED,
+                COMPONENT_TYPE_GREEN,
+                COMPONENT_TYPE_BLUE,
+                COMPONENT_TYPE_ALPHA,
+            ]),
+            "abgr" => Some(vec![
+                COMPONENT_TYPE_ALPHA,
+                COMPONENT_TYPE_BLUE,
+                COMPONENT_TYPE_GREEN,
+                COMPONENT_TYPE_RED,
+            ]),
+            "i420" | "nv12" => Some(vec![COMPONENT_TYPE_Y, COMPONENT_TYPE_CB, COMPONENT_TYPE_CR]),
+            "nv21" => Some(vec![COMPONENT_TYPE_Y, COMPONENT_TYPE_CR, COMPONENT_TYPE_CB]),
+            _ => None,
+        }
+    }
+
+    pub fn version(&self) -> u8 {
+        self.full_box_header.version()
+    }
+
+    pub fn profile(&self) -> &Byte4 {
+        &self.profile
+    }
+
+    pub fn components(&self) -> &Vec<UncompressedComponent> {
+        &self.components
+    }
+
+    pub fn sampling_type(&self) -> u8 {
+        self.sampling_type
+    }
+
+    pub fn interleave_type(&self) -> u8 {
+        self.interleave_type
+    }
+
+    pub fn block_size(&self) -> u8 {
+        self.block_size
+    }
+
+    pub fn components_little_endian(&self) -> bool {
+        self.components_little_endian
+    }
+
+    pub fn block_pad_lsb(&self) -> bool {
+        self.block_pad_lsb
+    }
+
+    pub fn block_little_endian(&self) -> bool {
+        self.block_little_endian
+    }
+
+    pub fn block_reversed(&self) -> bool {
+        self.block_reversed
+    }
+
+    pub fn pad_unknown(&self) -> bool {
+        self.pad_unknown
+    }
+
+    pub fn pixel_size(&self) -> u32 {
+        self.pixel_size
+    }
+
+    pub fn row_align_size(&self) -> u32 {
+        self.row_align_size
+    }
+
+    pub fn tile_align_size(&self) -> u32 {
+        self.tile_align_size
+    }
+
+    pub fn num_tile_cols(&self) -> u32 {
+        self.num_tile_cols_minus_one + 1
+    }
+
+    pub fn num_tile_rows(&self) -> u32 {
+        self.num_tile_rows_minus_one + 1
+    }
+}
@@ -0,0 +1,323 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::meta::iprp::{ConfigurationMap, DecoderConfigurationRecord, DecoderParameterType};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+const VVC_OPI_NUT: u8 = 12;
+const VVC_DCI_NUT: u8 = 13;
+const VVC_VPS_NUT: u8 = 14;
+const VVC_SPS_NUT: u8 = 15;
+const VVC_PPS_NUT: u8 = 16;
+const VVC_PREFIX_APS_NUT: u8 = 17;
+
+#[derive(Debug)]
+pub struct VvcConfigurationBox {
+    full_box_header: FullBoxHeader,
+    vvc_config: VvcDecoderConfigurationRecord,
+}
+
+impl Default for VvcConfigurationBox {
+    fn default() -> Self {
+        Self::new(VvcDecoderConfigurationRecord::default())
+    }
+}
+
+impl BBox for VvcConfigurationBox {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl VvcConfigurationBox {
+    pub fn new(vvc_config: VvcDecoderConfigurationRecord) -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("vvcC".parse().unwrap(), 0, 0),
+            vvc_config,
+        }
+    }
+
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        Ok(Self {
+            full_box_header,
+            vvc_config: VvcDecoderConfigurationRecord::from_stream(stream)?,
+        })
+    }
+
+    pub fn config(&self) -> &VvcDecoderConfigurationRecord {
+        &self.vvc_config
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct VvcPtlRecord {
+    general_profile_idc: u8,
+    general_tier_flag: u8,
+    general_level_idc: u8,
+    ptl_frame_only_constraint_flag: bool,
+    ptl_multilayer_enabled_flag: bool,
+    general_constraint_info: Vec<u8>,
+    sublayer_level_idc: Vec<Option<u8>>,
+    general_sub_profile_idc: Vec<u32>,
+}
+
+impl VvcPtlRecord {
+    fn from_stream<T: Stream>(stream: &mut T, num_sublayers: u8) -> Result<Self> {
+        stream.read_bits(2)?;
+        let num_bytes_constraint_info = stream.read_bits(6)?;
+        let general_profile_idc = stream.read_bits(7)? as u8;
+        let general_tier_flag = stream.read_bits(1)? as u8;
+        let general_level_idc = stream.read_byte()?;
+        // The two ptl flags are the leading bits of the constraint info bytes.
+        let general_constraint_info = stream.read_bytes(num_bytes_constraint_info)?.to_vec();
+        let ptl_frame_only_constraint_flag = general_constraint_info
+            .first()
+            .is_some_and(|b| b & 0x80 != 0);
+        let ptl_multilayer_enabled_flag = general_constraint_info
+            .first()
+            .is_some_and(|b| b & 0x40 != 0);
+
+        let mut sublayer_level_present_flags = Vec::new();
+        if num_sublayers > 1 {
+            for _ in 0..num_sublayers - 1 {
+                sublayer_level_present_flags.push(stream.read_flag()?);
+            }
+            stream.read_bits(9 - num_sublayers as usize)?;
+        }
+        let mut sublayer_level_idc = Vec::new();
+        for present in sublayer_level_present_flags {
+            if present {
+                sublayer_level_idc.push(Some(stream.read_byte()?));
+            } else {
+                sublayer_level_idc.push(None);
+            }
+        }
+        let ptl_num_sub_profiles = stream.read_byte()?;
+        let mut general_sub_profile_idc = Vec::new();
+        for _ in 0..ptl_num_sub_profiles {
+            general_sub_profile_idc.push(stream.read_4bytes()?.to_u32());
+        }
+        Ok(Self {
+            general_profile_idc,
+            general_tier_flag,
+            general_level_idc,
+            ptl_frame_only_constraint_flag,
+            ptl_multilayer_enabled_flag,
+            general_constraint_info,
+            sublayer_level_idc,
+            general_sub_profile_idc,
+        })
+    }
+
+    pub fn general_profile_idc(&self) -> u8 {
+        self.general_profile_idc
+    }
+
+    pub fn general_tier_flag(&self) -> u8 {
+        self.general_tier_flag
+    }
+
+    pub fn general_level_idc(&self) -> u8 {
+        self.general_level_idc
+    }
+}
+
+#[derive(Debug)]
+pub struct VvcDecoderConfigurationRecord {
+    length_size_minus_one: u8,
+    ols_idx: u16,
+    num_sublayers: u8,
+    constant_frame_rate: u8,
+    chroma_format_idc: u8,
+    bit_depth_minus8: u8,
+    native_ptl: Option<VvcPtlRecord>,
+    max_picture_width: u16,
+    max_picture_height: u16,
+    avg_frame_rate: u16,
+    nal_array: Vec<VvcNalArray>,
+}
+
+impl Default for VvcDecoderConfigurationRecord {
+    fn default() -> Self {
+        Self {
+            length_size_minus_one: 3,
+            ols_idx: 0,
+            num_sublayers: 0,
+            constant_frame_rate: 0,
+            chroma_format_idc: 0,
+            bit_depth_minus8: 0,
+            native_ptl: None,
+            max_picture_width: 0,
+            max_picture_height: 0,
+            avg_frame_rate: 0,
+            nal_array: Vec::new(),
+        }
+    }
+}
+
+impl DecoderConfigurationRecord for VvcDecoderConfigurationRecord {
+    fn configuration_map(&self) -> ConfigurationMap {
+        let mut m = ConfigurationMap::default();
+        m.insert(
+            DecoderParameterType::VvcVPS,
+            self.get_parameter_sets(VVC_VPS_NUT),
+        );
+        m.insert(
+            DecoderParameterType::VvcSPS,
+            self.get_parameter_sets(VVC_SPS_NUT),
+        );
+        m.insert(
+            DecoderParameterType::VvcPPS,
+            self.get_parameter_sets(VVC_PPS_NUT),
+        );
+        m.insert(
+            DecoderParameterType::VvcAPS,
+            self.get_parameter_sets(VVC_PREFIX_APS_NUT),
+        );
+        m
+    }
+}
+
+impl VvcDecoderConfigurationRecord {
+    fn from_stream<T: Stream>(stream: &mut T) -> Result<Self> {
+        let mut res = Self::default();
+        stream.read_bits(5)?;
+        res.length_size_minus_one = stream.read_bits(2)? as u8;
+        let ptl_present_flag = stream.read_flag()?;
+        if ptl_present_flag {
+            res.ols_idx = stream.read_bits(9)? as u16;
+            res.num_sublayers = stream.read_bits(3)? as u8;
+            res.constant_frame_rate = stream.read_bits(2)? as u8;
+            res.chroma_format_idc = stream.read_bits(2)? as u8;
+            res.bit_depth_minus8 = stream.read_bits(3)? as u8;
+            stream.read_bits(5)?;
+            res.native_ptl = Some(VvcPtlRecord::from_stream(stream, res.num_sublayers)?);
+            res.max_picture_width = stream.read_2bytes()?.to_u16();
+            res.max_picture_height = stream.read_2bytes()?.to_u16();
+            res.avg_frame_rate = stream.read_2bytes()?.to_u16();
+        }
+        let num_of_arrays = stream.read_byte()?;
+        for _ in 0..num_of_arrays {
+            let array_completeness = stream.read_flag()?;
+            stream.read_bits(2)?;
+            let nal_unit_type = stream.read_bits(5)? as u8;
+            let num_nalus = if nal_unit_type != VVC_DCI_NUT && nal_unit_type != VVC_OPI_NUT {
+                stream.read_2bytes()?.to_u16()
+            } else {
+                1
+            };
+            let mut nal_list = Vec::new();
+            for _ in 0..num_nalus {
+                let nal_unit_length = stream.read_2bytes()?.to_usize();
+                nal_list.push(stream.read_bytes(nal_unit_length)?.to_vec());
+            }
+            res.nal_array.push(VvcNalArray {
+                array_completeness,
+                nal_unit_type,
+                nal_list,
+            });
+        }
+        Ok(res)
+    }
+
+    // All NAL units of the given type, each prefixed with a start code.
+    fn get_parameter_sets(&self, nal_unit_type: u8) -> Vec<u8> {
+        let mut res = Vec::new();
+        for array in &self.nal_array {
+            if array.nal_unit_type != nal_unit_type {
+                continue;
+            }
+            for nal_unit in &array.nal_list {
+                res.extend_from_slice(&[0, 0, 0, 1]);
+                res.extend_from_slice(nal_unit);
+            }
+        }
+        res
+    }
+
+    pub fn length_size_minus_one(&self) -> u8 {
+        self.length_size_minus_one
+    }
+
+    pub fn chroma_format_idc(&self) -> u8 {
+        self.chroma_format_idc
+    }
+
+    pub fn bit_depth_minus8(&self) -> u8 {
+        self.bit_depth_minus8
+    }
+
+    pub fn native_ptl(&self) -> Option<&VvcPtlRecord> {
+        self.native_ptl.as_ref()
+    }
+
+    pub fn max_picture_width(&self) -> u16 {
+        self.max_picture_width
+    }
+
+    pub fn max_picture_height(&self) -> u16 {
+        self.max_picture_height
+    }
+}
+
+#[derive(Debug)]
+struct VvcNalArray {
+    array_completeness: bool,
+    nal_unit_type: u8,
+    nal_list: Vec<Vec<u8>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_vvcc_from_stream() {
+        let mut stream = BitStream::new(vec![
+            0x00, 0x00, 0x00, 0x34, 0x76, 0x76, 0x63, 0x43, // size, 'vvcC'
+            0x00, 0x00, 0x00, 0x00, // version, flags
+            0xFF, // reserved, length_size_minus_one 3, ptl present
+            0x00, 0x11, // ols_idx 0, num_sublayers 1, cfr 0, chroma 1
+            0x5F, // bit_depth_minus8 2, reserved
+            0x01, 0x02, 0x33, 0x00, // ptl: 1 byte constraint info, profile 1, level 51
+            0x00, // ptl_num_sub_profiles
+            0x07, 0x80, 0x04, 0x38, 0x00, 0x00, // 1920x1080, avg frame rate
+            0x03, // num_of_arrays
+            0x8F, 0x00, 0x01, 0x00, 0x03, 0x00, 0x79, 0x01, // SPS
+            0x90, 0x00, 0x01, 0x00, 0x02, 0x00, 0x81, // PPS
+            0x91, 0x00, 0x02, 0x00, 0x01, 0xAA, 0x00, 0x01, 0xBB, // two APS
+        ]);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let vvcc = VvcConfigurationBox::from_stream_header(&mut stream, header).unwrap();
+        assert!(stream.is_eof());
+        let config = vvcc.config();
+        assert_eq!(config.length_size_minus_one(), 3);
+        assert_eq!(config.chroma_format_idc(), 1);
+        assert_eq!(config.bit_depth_minus8(), 2);
+        assert_eq!(config.native_ptl().unwrap().general_profile_idc(), 1);
+        assert_eq!(config.native_ptl().unwrap().general_level_idc(), 51);
+        assert_eq!(config.max_picture_width(), 1920);
+        assert_eq!(config.max_picture_height(), 1080);
+
+        let m = config.configuration_map();
+        assert_eq!(
+            m[&DecoderParameterType::VvcSPS],
+            vec![0, 0, 0, 1, 0x00, 0x79, 0x01]
+        );
+        assert_eq!(
+            m[&DecoderParameterType::VvcPPS],
+            vec![0, 0, 0, 1, 0x00, 0x81]
+        );
+        assert_eq!(
+            m[&DecoderParameterType::VvcAPS],
+            vec![0, 0, 0, 1, 0xAA, 0, 0, 0, 1, 0xBB]
+        );
+        assert!(m[&DecoderParameterType::VvcVPS].is_empty());
+    }
+}
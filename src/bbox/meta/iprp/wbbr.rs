@@ -0,0 +1,52 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+// White balance of an image of a white balance bracketing set. blue_amber
+// is a colour temperature in kelvin.
+#[derive(Debug)]
+pub struct WhiteBalanceProperty {
+    full_box_header: FullBoxHeader,
+    blue_amber: u16,
+    green_magenta: i8,
+}
+
+impl Default for WhiteBalanceProperty {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("wbbr".parse().unwrap(), 0, 0),
+            blue_amber: 0,
+            green_magenta: 0,
+        }
+    }
+}
+
+impl BBox for WhiteBalanceProperty {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl WhiteBalanceProperty {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        Ok(Self {
+            full_box_header,
+            blue_amber: stream.read_2bytes()?.to_u16(),
+            green_magenta: stream.read_byte()? as i8,
+        })
+    }
+
+    pub fn blue_amber(&self) -> u16 {
+        self.blue_amber
+    }
+
+    pub fn green_magenta(&self) -> i8 {
+        self.green_magenta
+    }
+}
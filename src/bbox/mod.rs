@@ -5,6 +5,7 @@ use crate::bit::Byte4;
 pub mod ftyp;
 pub mod header;
 pub mod meta;
+pub mod moof;
 pub mod moov;
 
 pub trait BBox {
@@ -0,0 +1,485 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::moov::sample_group::{SampleGroupDescriptionBox, SampleToGroupBox};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::{HeifError, Result};
+
+pub const TFHD_BASE_DATA_OFFSET_PRESENT: u32 = 0x000001;
+pub const TFHD_SAMPLE_DESCRIPTION_INDEX_PRESENT: u32 = 0x000002;
+pub const TFHD_DEFAULT_SAMPLE_DURATION_PRESENT: u32 = 0x000008;
+pub const TFHD_DEFAULT_SAMPLE_SIZE_PRESENT: u32 = 0x000010;
+pub const TFHD_DEFAULT_SAMPLE_FLAGS_PRESENT: u32 = 0x000020;
+pub const TFHD_DEFAULT_BASE_IS_MOOF: u32 = 0x020000;
+
+// sbgp entries of a traf above this index refer to the sgpd of the traf.
+pub const TRAF_GROUP_DESCRIPTION_INDEX_BASE: u32 = 0x10000;
+
+pub const TRUN_DATA_OFFSET_PRESENT: u32 = 0x000001;
+pub const TRUN_FIRST_SAMPLE_FLAGS_PRESENT: u32 = 0x000004;
+pub const TRUN_SAMPLE_DURATION_PRESENT: u32 = 0x000100;
+pub const TRUN_SAMPLE_SIZE_PRESENT: u32 = 0x000200;
+pub const TRUN_SAMPLE_FLAGS_PRESENT: u32 = 0x000400;
+pub const TRUN_SAMPLE_COMPOSITION_TIME_OFFSETS_PRESENT: u32 = 0x000800;
+
+// Fields of the sample flags of trex, tfhd and trun.
+pub fn sample_depends_on(sample_flags: u32) -> u8 {
+    ((sample_flags >> 24) & 0x03) as u8
+}
+
+pub fn sample_is_depended_on(sample_flags: u32) -> u8 {
+    ((sample_flags >> 22) & 0x03) as u8
+}
+
+pub fn sample_is_non_sync_sample(sample_flags: u32) -> bool {
+    (sample_flags >> 16) & 0x01 != 0
+}
+
+#[derive(Debug)]
+pub struct MovieFragmentBox {
+    box_header: BoxHeader,
+    sequence_number: u32,
+    track_fragment_boxes: Vec<TrackFragmentBox>,
+}
+
+impl BBox for MovieFragmentBox {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl MovieFragmentBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let mut sequence_number = 0;
+        let mut track_fragment_boxes = Vec::new();
+        while !stream.is_eof() {
+            let child_box_header = BoxHeader::from_stream(stream)?;
+            let mut ex = stream.extract_from(&child_box_header)?;
+            match child_box_header.box_type().to_string().as_str() {
+                "mfhd" => {
+                    FullBoxHeader::from_stream_header(&mut ex, child_box_header)?;
+                    sequence_number = ex.read_4bytes()?.to_u32();
+                }
+                "traf" => {
+                    track_fragment_boxes.push(TrackFragmentBox::from_stream_header(
+                        &mut ex,
+                        child_box_header,
+                    )?);
+                }
+                _ => {} //skip
+            }
+        }
+        Ok(Self {
+            box_header,
+            sequence_number,
+            track_fragment_boxes,
+        })
+    }
+
+    pub fn sequence_number(&self) -> u32 {
+        self.sequence_number
+    }
+
+    pub fn track_fragment_boxes(&self) -> &Vec<TrackFragmentBox> {
+        &self.track_fragment_boxes
+    }
+}
+
+#[derive(Debug)]
+pub struct TrackFragmentBox {
+    box_header: BoxHeader,
+    track_fragment_header_box: TrackFragmentHeaderBox,
+    base_media_decode_time: Option<u64>,
+    track_run_boxes: Vec<TrackRunBox>,
+    sample_to_group_boxes: Vec<SampleToGroupBox>,
+    sample_group_description_boxes: Vec<SampleGroupDescriptionBox>,
+}
+
+impl TrackFragmentBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let mut track_fragment_header_box = None;
+        let mut base_media_decode_time = None;
+        let mut track_run_boxes = Vec::new();
+        let mut sample_to_group_boxes = Vec::new();
+        let mut sample_group_description_boxes = Vec::new();
+        while !stream.is_eof() {
+            let child_box_header = BoxHeader::from_stream(stream)?;
+            let mut ex = stream.extract_from(&child_box_header)?;
+            match child_box_header.box_type().to_string().as_str() {
+                "tfhd" => {
+                    track_fragment_header_box = Some(TrackFragmentHeaderBox::from_stream_header(
+                        &mut ex,
+                        child_box_header,
+                    )?);
+                }
+                "tfdt" => {
+                    let full_box_header =
+                        FullBoxHeader::from_stream_header(&mut ex, child_box_header)?;
+                    base_media_decode_time = Some(if full_box_header.version() == 1 {
+                        ex.read_8bytes()?.to_u64()
+                    } else {
+                        u64::from(ex.read_4bytes()?.to_u32())
+                    });
+                }
+                "trun" => {
+                    track_run_boxes
+                        .push(TrackRunBox::from_stream_header(&mut ex, child_box_header)?);
+                }
+                "sbgp" => {
+                    sample_to_group_boxes.push(SampleToGroupBox::from_stream_header(
+                        &mut ex,
+                        child_box_header,
+                    )?);
+                }
+                "sgpd" => {
+                    sample_group_description_boxes.push(
+                        SampleGroupDescriptionBox::from_stream_header(&mut ex, child_box_header)?,
+                    );
+                }
+                _ => {} //skip
+            }
+        }
+        let track_fragment_header_box = match track_fragment_header_box {
+            Some(tfhd) => tfhd,
+            None => return Err(HeifError::FileHeader),
+        };
+        Ok(Self {
+            box_header,
+            track_fragment_header_box,
+            base_media_decode_time,
+            track_run_boxes,
+            sample_to_group_boxes,
+            sample_group_description_boxes,
+        })
+    }
+
+    pub fn track_fragment_header_box(&self) -> &TrackFragmentHeaderBox {
+        &self.track_fragment_header_box
+    }
+
+    // From tfdt, when present.
+    pub fn base_media_decode_time(&self) -> Option<u64> {
+        self.base_media_decode_time
+    }
+
+    pub fn track_run_boxes(&self) -> &Vec<TrackRunBox> {
+        &self.track_run_boxes
+    }
+
+    // Samples are numbered from the first sample of the track fragment.
+    pub fn sample_to_group_boxes(&self) -> &Vec<SampleToGroupBox> {
+        &self.sample_to_group_boxes
+    }
+
+    pub fn sample_group_description_boxes(&self) -> &Vec<SampleGroupDescriptionBox> {
+        &self.sample_group_description_boxes
+    }
+}
+
+// Fields missing from tfhd fall back to the trex of the track.
+#[derive(Debug)]
+pub struct TrackFragmentHeaderBox {
+    full_box_header: FullBoxHeader,
+    track_id: u32,
+    base_data_offset: Option<u64>,
+    sample_description_index: Option<u32>,
+    default_sample_duration: Option<u32>,
+    default_sample_size: Option<u32>,
+    default_sample_flags: Option<u32>,
+}
+
+impl TrackFragmentHeaderBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let flags = full_box_header.flags();
+        let track_id = stream.read_4bytes()?.to_u32();
+        let base_data_offset = if flags & TFHD_BASE_DATA_OFFSET_PRESENT != 0 {
+            Some(stream.read_8bytes()?.to_u64())
+        } else {
+            None
+        };
+        let mut read_optional = |flag: u32| -> Result<Option<u32>> {
+            if flags & flag != 0 {
+                Ok(Some(stream.read_4bytes()?.to_u32()))
+            } else {
+                Ok(None)
+            }
+        };
+        let sample_description_index = read_optional(TFHD_SAMPLE_DESCRIPTION_INDEX_PRESENT)?;
+        let default_sample_duration = read_optional(TFHD_DEFAULT_SAMPLE_DURATION_PRESENT)?;
+        let default_sample_size = read_optional(TFHD_DEFAULT_SAMPLE_SIZE_PRESENT)?;
+        let default_sample_flags = read_optional(TFHD_DEFAULT_SAMPLE_FLAGS_PRESENT)?;
+        Ok(Self {
+            full_box_header,
+            track_id,
+            base_data_offset,
+            sample_description_index,
+            default_sample_duration,
+            default_sample_size,
+            default_sample_flags,
+        })
+    }
+
+    pub fn full_box_header(&self) -> &FullBoxHeader {
+        &self.full_box_header
+    }
+
+    pub fn track_id(&self) -> u32 {
+        self.track_id
+    }
+
+    pub fn base_data_offset(&self) -> Option<u64> {
+        self.base_data_offset
+    }
+
+    pub fn sample_description_index(&self) -> Option<u32> {
+        self.sample_description_index
+    }
+
+    pub fn default_sample_duration(&self) -> Option<u32> {
+        self.default_sample_duration
+    }
+
+    pub fn default_sample_size(&self) -> Option<u32> {
+        self.default_sample_size
+    }
+
+    pub fn default_sample_flags(&self) -> Option<u32> {
+        self.default_sample_flags
+    }
+
+    pub fn default_base_is_moof(&self) -> bool {
+        self.full_box_header.flags() & TFHD_DEFAULT_BASE_IS_MOOF != 0
+    }
+}
+
+// Fields missing from a trun sample fall back to tfhd and then trex.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackRunSample {
+    pub duration: Option<u32>,
+    pub size: Option<u32>,
+    pub flags: Option<u32>,
+    pub composition_time_offset: i64,
+}
+
+#[derive(Debug)]
+pub struct TrackRunBox {
+    full_box_header: FullBoxHeader,
+    data_offset: Option<i32>,
+    first_sample_flags: Option<u32>,
+    samples: Vec<TrackRunSample>,
+}
+
+impl TrackRunBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let flags = full_box_header.flags();
+        let sample_count = stream.read_4bytes()?.to_u32();
+        let data_offset = if flags & TRUN_DATA_OFFSET_PRESENT != 0 {
+            Some(stream.read_4bytes()?.to_u32() as i32)
+        } else {
+            None
+        };
+        let first_sample_flags = if flags & TRUN_FIRST_SAMPLE_FLAGS_PRESENT != 0 {
+            Some(stream.read_4bytes()?.to_u32())
+        } else {
+            None
+        };
+        let mut samples = Vec::new();
+        for _ in 0..sample_count {
+            let mut read_optional = |flag: u32| -> Result<Option<u32>> {
+                if flags & flag != 0 {
+                    Ok(Some(stream.read_4bytes()?.to_u32()))
+                } else {
+                    Ok(None)
+                }
+            };
+            let duration = read_optional(TRUN_SAMPLE_DURATION_PRESENT)?;
+            let size = read_optional(TRUN_SAMPLE_SIZE_PRESENT)?;
+            let sample_flags = read_optional(TRUN_SAMPLE_FLAGS_PRESENT)?;
+            let offset = read_optional(TRUN_SAMPLE_COMPOSITION_TIME_OFFSETS_PRESENT)?;
+            // Offsets are signed from version 1.
+            let composition_time_offset = match offset {
+                Some(offset) if full_box_header.version() >= 1 => i64::from(offset as i32),
+                Some(offset) => i64::from(offset),
+                None => 0,
+            };
+            samples.push(TrackRunSample {
+                duration,
+                size,
+                flags: sample_flags,
+                composition_time_offset,
+            });
+        }
+        Ok(Self {
+            full_box_header,
+            data_offset,
+            first_sample_flags,
+            samples,
+        })
+    }
+
+    // Relative to the base data offset of the track fragment.
+    pub fn data_offset(&self) -> Option<i32> {
+        self.data_offset
+    }
+
+    pub fn first_sample_flags(&self) -> Option<u32> {
+        self.first_sample_flags
+    }
+
+    pub fn samples(&self) -> &Vec<TrackRunSample> {
+        &self.samples
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackFragmentRandomAccessEntry {
+    pub time: u64,
+    pub moof_offset: u64,
+    pub traf_number: u32,
+    pub trun_number: u32,
+    pub sample_number: u32,
+}
+
+// The tfra boxes of an mfra box.
+#[derive(Debug)]
+pub struct MovieFragmentRandomAccessBox {
+    box_header: BoxHeader,
+    track_fragment_random_access_boxes: Vec<TrackFragmentRandomAccessBox>,
+}
+
+impl MovieFragmentRandomAccessBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let mut track_fragment_random_access_boxes = Vec::new();
+        while !stream.is_eof() {
+            let child_box_header = BoxHeader::from_stream(stream)?;
+            let mut ex = stream.extract_from(&child_box_header)?;
+            if *child_box_header.box_type() == "tfra" {
+                track_fragment_random_access_boxes.push(
+                    TrackFragmentRandomAccessBox::from_stream_header(&mut ex, child_box_header)?,
+                );
+            }
+        }
+        Ok(Self {
+            box_header,
+            track_fragment_random_access_boxes,
+        })
+    }
+
+    pub fn track_fragment_random_access_boxes(&self) -> &Vec<TrackFragmentRandomAccessBox> {
+        &self.track_fragment_random_access_boxes
+    }
+}
+
+#[derive(Debug)]
+pub struct TrackFragmentRandomAccessBox {
+    full_box_header: FullBoxHeader,
+    track_id: u32,
+    entries: Vec<TrackFragmentRandomAccessEntry>,
+}
+
+impl TrackFragmentRandomAccessBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let is_large = full_box_header.version() == 1;
+        let track_id = stream.read_4bytes()?.to_u32();
+        let sizes = stream.read_4bytes()?.to_u32();
+        // Each number takes one to four bytes.
+        let traf_number_bits = 8 * (((sizes >> 4) & 0x03) as usize + 1);
+        let trun_number_bits = 8 * (((sizes >> 2) & 0x03) as usize + 1);
+        let sample_number_bits = 8 * ((sizes & 0x03) as usize + 1);
+        let entry_count = stream.read_4bytes()?.to_u32();
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            let (time, moof_offset) = if is_large {
+                (
+                    stream.read_8bytes()?.to_u64(),
+                    stream.read_8bytes()?.to_u64(),
+                )
+            } else {
+                (
+                    u64::from(stream.read_4bytes()?.to_u32()),
+                    u64::from(stream.read_4bytes()?.to_u32()),
+                )
+            };
+            entries.push(TrackFragmentRandomAccessEntry {
+                time,
+                moof_offset,
+                traf_number: stream.read_bits(traf_number_bits)? as u32,
+                trun_number: stream.read_bits(trun_number_bits)? as u32,
+                sample_number: stream.read_bits(sample_number_bits)? as u32,
+            });
+        }
+        Ok(Self {
+            full_box_header,
+            track_id,
+            entries,
+        })
+    }
+
+    pub fn track_id(&self) -> u32 {
+        self.track_id
+    }
+
+    // Numbers count from 1.
+    pub fn entries(&self) -> &Vec<TrackFragmentRandomAccessEntry> {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_traf() {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x50, 0x74, 0x72, 0x61, 0x66, // size, 'traf'
+            0x00, 0x00, 0x00, 0x14, 0x74, 0x66, 0x68, 0x64, // size, 'tfhd'
+            0x00, 0x02, 0x00, 0x08, 0x00, 0x00, 0x00, 0x01, // flags, track_id
+            0x00, 0x00, 0x02, 0x00, // default_sample_duration
+            0x00, 0x00, 0x00, 0x14, 0x74, 0x66, 0x64, 0x74, // size, 'tfdt'
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // version, flags
+            0x00, 0x00, 0x04, 0x00, // base_media_decode_time
+            0x00, 0x00, 0x00, 0x28, 0x74, 0x72, 0x75, 0x6E, // size, 'trun'
+            0x01, 0x00, 0x0A, 0x05, 0x00, 0x00, 0x00, 0x02, // flags, sample_count
+            0x00, 0x00, 0x00, 0x58, 0x02, 0x00, 0x00, 0x00, // data_offset, first_sample_flags
+            0x00, 0x00, 0x00, 0x0A, 0xFF, 0xFF, 0xFE, 0x00, // size, composition offset
+            0x00, 0x00, 0x00, 0x0B, 0x00, 0x00, 0x02, 0x00, // size, composition offset
+        ];
+        let mut stream = BitStream::new(data);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let traf = TrackFragmentBox::from_stream_header(&mut stream, header).unwrap();
+        let tfhd = traf.track_fragment_header_box();
+        assert_eq!(tfhd.track_id(), 1);
+        assert!(tfhd.default_base_is_moof());
+        assert_eq!(tfhd.default_sample_duration(), Some(512));
+        assert_eq!(tfhd.default_sample_size(), None);
+        assert_eq!(traf.base_media_decode_time(), Some(1024));
+        let trun = &traf.track_run_boxes()[0];
+        assert_eq!(trun.data_offset(), Some(0x58));
+        assert_eq!(trun.first_sample_flags(), Some(0x02000000));
+        assert_eq!(
+            trun.samples(),
+            &vec![
+                TrackRunSample {
+                    duration: None,
+                    size: Some(10),
+                    flags: None,
+                    composition_time_offset: -512,
+                },
+                TrackRunSample {
+                    duration: None,
+                    size: Some(11),
+                    flags: None,
+                    composition_time_offset: 512,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::meta::iprp::{ConfigurationMap, DecoderConfigurationRecord, DecoderParameterType};
+use crate::bit::Stream;
+use crate::{HeifError, Result};
+
+const ES_DESCRIPTOR_TAG: u8 = 0x03;
+const DECODER_CONFIG_DESCRIPTOR_TAG: u8 = 0x04;
+const DECODER_SPECIFIC_INFO_TAG: u8 = 0x05;
+
+// The ES_Descriptor of an esds box, down to the DecoderSpecificInfo. For
+// mp4a entries the DecoderSpecificInfo is the AudioSpecificConfig.
+#[derive(Debug)]
+pub struct ElementaryStreamDescriptorBox {
+    full_box_header: FullBoxHeader,
+    es_id: u16,
+    object_type_indication: u8,
+    stream_type: u8,
+    buffer_size_db: u32,
+    max_bitrate: u32,
+    avg_bitrate: u32,
+    decoder_specific_info: Vec<u8>,
+}
+
+// Descriptor sizes are coded in up to four bytes of seven bits each.
+fn read_descriptor_header<T: Stream>(stream: &mut T) -> Result<(u8, usize)> {
+    let tag = stream.read_byte()?;
+    let mut size = 0;
+    for _ in 0..4 {
+        let byte = stream.read_byte()?;
+        size = (size << 7) | usize::from(byte & 0x7F);
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    Ok((tag, size))
+}
+
+impl ElementaryStreamDescriptorBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let (tag, _) = read_descriptor_header(stream)?;
+        if tag != ES_DESCRIPTOR_TAG {
+            return Err(HeifError::Unknown(
+                "esds does not start with an ES_Descriptor",
+            ));
+        }
+        let es_id = stream.read_2bytes()?.to_u16();
+        let flags = stream.read_byte()?;
+        if flags & 0x80 != 0 {
+            // depends_on_ES_ID
+            stream.skip_bytes(2)?;
+        }
+        if flags & 0x40 != 0 {
+            let url_length = stream.read_byte()?;
+            stream.skip_bytes(usize::from(url_length))?;
+        }
+        if flags & 0x20 != 0 {
+            // OCR_ES_Id
+            stream.skip_bytes(2)?;
+        }
+        let (tag, _) = read_descriptor_header(stream)?;
+        if tag != DECODER_CONFIG_DESCRIPTOR_TAG {
+            return Err(HeifError::Unknown(
+                "ES_Descriptor has no DecoderConfigDescriptor",
+            ));
+        }
+        let object_type_indication = stream.read_byte()?;
+        let stream_type = stream.read_byte()? >> 2;
+        let buffer_size_db = stream.read_bits(24)? as u32;
+        let max_bitrate = stream.read_4bytes()?.to_u32();
+        let avg_bitrate = stream.read_4bytes()?.to_u32();
+        let mut decoder_specific_info = Vec::new();
+        if !stream.is_eof() {
+            let (tag, size) = read_descriptor_header(stream)?;
+            if tag == DECODER_SPECIFIC_INFO_TAG {
+                decoder_specific_info = stream.read_bytes(size)?.to_vec();
+            }
+        }
+        Ok(Self {
+            full_box_header,
+            es_id,
+            object_type_indication,
+            stream_type,
+            buffer_size_db,
+            max_bitrate,
+            avg_bitrate,
+            decoder_specific_info,
+        })
+    }
+
+    pub fn es_id(&self) -> u16 {
+        self.es_id
+    }
+
+    // 0x40 is MPEG-4 audio.
+    pub fn object_type_indication(&self) -> u8 {
+        self.object_type_indication
+    }
+
+    pub fn stream_type(&self) -> u8 {
+        self.stream_type
+    }
+
+    pub fn buffer_size_db(&self) -> u32 {
+        self.buffer_size_db
+    }
+
+    pub fn max_bitrate(&self) -> u32 {
+        self.max_bitrate
+    }
+
+    pub fn avg_bitrate(&self) -> u32 {
+        self.avg_bitrate
+    }
+
+    pub fn decoder_specific_info(&self) -> &Vec<u8> {
+        &self.decoder_specific_info
+    }
+}
+
+impl DecoderConfigurationRecord for ElementaryStreamDescriptorBox {
+    fn configuration_map(&self) -> ConfigurationMap {
+        let mut m = ConfigurationMap::default();
+        m.insert(
+            DecoderParameterType::AudioSpecificConfig,
+            self.decoder_specific_info.clone(),
+        );
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_esds_audio_specific_config() {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x2A, 0x65, 0x73, 0x64, 0x73, // size, 'esds'
+            0x00, 0x00, 0x00, 0x00, // version, flags
+            0x03, 0x80, 0x80, 0x80, 0x19, 0x00, 0x01, 0x00, // ES_Descriptor, ES_ID 1
+            0x04, 0x11, 0x40, 0x15, 0x00, 0x01, 0x00, 0x00, // DecoderConfigDescriptor
+            0x01, 0xF4, 0x00, 0x00, 0x01, 0xF4, 0x00, // bitrates
+            0x05, 0x02, 0x12, 0x10, // AudioSpecificConfig
+            0x06, 0x01, 0x02, // SLConfigDescriptor
+        ];
+        let mut stream = BitStream::new(data);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let esds = ElementaryStreamDescriptorBox::from_stream_header(&mut stream, header).unwrap();
+        assert_eq!(esds.es_id(), 1);
+        assert_eq!(esds.object_type_indication(), 0x40);
+        assert_eq!(esds.stream_type(), 5);
+        assert_eq!(esds.buffer_size_db(), 256);
+        assert_eq!(esds.max_bitrate(), 128000);
+        assert_eq!(esds.decoder_specific_info(), &vec![0x12, 0x10]);
+        assert_eq!(
+            esds.configuration_map()[&DecoderParameterType::AudioSpecificConfig],
+            vec![0x12, 0x10]
+        );
+    }
+}
@@ -1,5 +1,10 @@
 use crate::bbox::header::{BoxHeader, FullBoxHeader};
-use crate::bbox::meta::dinf::DataEntryBox;
+use crate::bbox::meta::dinf::DataInformationBox;
+use crate::bbox::meta::hdlr::HandlerBox;
+use crate::bbox::moov::sample_table::SampleTableBox;
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
 
 #[derive(Debug)]
 pub struct MediaBox {
@@ -9,6 +14,71 @@ pub struct MediaBox {
     media_information_box: MediaInformationBox,
 }
 
+impl Default for MediaBox {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("mdia".parse().unwrap()),
+            media_header_box: MediaHeaderBox::default(),
+            handler_box: HandlerBox::default(),
+            media_information_box: MediaInformationBox::default(),
+        }
+    }
+}
+
+impl BBox for MediaBox {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl MediaBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let mut s = Self {
+            box_header,
+            ..Self::default()
+        };
+        while !stream.is_eof() {
+            let child_box_header = BoxHeader::from_stream(stream)?;
+            let mut ex = stream.extract_from(&child_box_header)?;
+            match child_box_header.box_type().to_string().as_str() {
+                "mdhd" => {
+                    s.media_header_box =
+                        MediaHeaderBox::from_stream_header(&mut ex, child_box_header)?;
+                }
+                "hdlr" => {
+                    s.handler_box = HandlerBox::from_stream_header(&mut ex, child_box_header)?;
+                }
+                "minf" => {
+                    s.media_information_box =
+                        MediaInformationBox::from_stream_header(&mut ex, child_box_header)?;
+                }
+                _ => {} //skip
+            }
+        }
+        Ok(s)
+    }
+
+    pub fn box_header(&self) -> &BoxHeader {
+        &self.box_header
+    }
+
+    pub fn media_header_box(&self) -> &MediaHeaderBox {
+        &self.media_header_box
+    }
+
+    pub fn handler_box(&self) -> &HandlerBox {
+        &self.handler_box
+    }
+
+    pub fn media_information_box(&self) -> &MediaInformationBox {
+        &self.media_information_box
+    }
+}
+
 #[derive(Debug)]
 pub struct MediaHeaderBox {
     full_box_header: FullBoxHeader,
@@ -19,78 +89,171 @@ pub struct MediaHeaderBox {
     language: u16,
 }
 
-#[derive(Debug)]
-pub struct HandlerBox {
-    full_box_header: FullBoxHeader,
-    handler_type: String,
-    name: String,
+impl Default for MediaHeaderBox {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("mdhd".parse().unwrap(), 0, 0),
+            creation_time: 0,
+            modification_time: 0,
+            time_scale: 0,
+            duration: 0,
+            language: 0,
+        }
+    }
 }
 
-#[derive(Debug)]
-enum MediaType {
+impl MediaHeaderBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let (creation_time, modification_time, time_scale, duration) =
+            if full_box_header.version() == 1 {
+                (
+                    stream.read_8bytes()?.to_u64(),
+                    stream.read_8bytes()?.to_u64(),
+                    stream.read_4bytes()?.to_u32(),
+                    stream.read_8bytes()?.to_u64(),
+                )
+            } else {
+                (
+                    u64::from(stream.read_4bytes()?.to_u32()),
+                    u64::from(stream.read_4bytes()?.to_u32()),
+                    stream.read_4bytes()?.to_u32(),
+                    u64::from(stream.read_4bytes()?.to_u32()),
+                )
+            };
+        let language = stream.read_2bytes()?.to_u16() & 0x7FFF;
+        Ok(Self {
+            full_box_header,
+            creation_time,
+            modification_time,
+            time_scale,
+            duration,
+            language,
+        })
+    }
+
+    pub fn creation_time(&self) -> u64 {
+        self.creation_time
+    }
+
+    pub fn modification_time(&self) -> u64 {
+        self.modification_time
+    }
+
+    pub fn time_scale(&self) -> u32 {
+        self.time_scale
+    }
+
+    pub fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    pub fn language(&self) -> u16 {
+        self.language
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MediaType {
     Null,
     Video,
     Sound,
 }
 
 #[derive(Debug)]
-pub struct MediaInformationBox {
-    box_header: BoxHeader,
-    media_type: MediaType,
-    video_media_header_box: VideoMediaHeaderBox,
-    sound_media_header_box: SoundMediaHeaderBox,
-    null_media_header_box: NullMediaHeaderBox,
-    data_information_box: DataInformationBox,
-    sample_table_box: SampleTableBox,
-}
-
-#[derive(Debug)]
-struct VideoMediaHeaderBox {
+pub struct SoundMediaHeaderBox {
     full_box_header: FullBoxHeader,
+    balance: i16,
 }
 
-#[derive(Debug)]
-struct SoundMediaHeaderBox {
-    full_box_header: FullBoxHeader,
-    balance: u16,
-}
+impl SoundMediaHeaderBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let balance = stream.read_2bytes()?.to_u16() as i16;
+        stream.skip_bytes(2)?;
+        Ok(Self {
+            full_box_header,
+            balance,
+        })
+    }
 
-#[derive(Debug)]
-struct NullMediaHeaderBox {
-    full_box_header: FullBoxHeader,
+    // 8.8 fixed point, from -1.0 for full left to 1.0 for full right.
+    pub fn balance(&self) -> i16 {
+        self.balance
+    }
 }
 
 #[derive(Debug)]
-struct DataInformationBox {
+pub struct MediaInformationBox {
     box_header: BoxHeader,
-    data_reference_box: DataReferenceBox,
+    media_type: MediaType,
+    sound_media_header_box: Option<SoundMediaHeaderBox>,
+    data_information_box: DataInformationBox,
+    sample_table_box: SampleTableBox,
 }
 
-struct DataReferenceBox {
-    full_box_header: FullBoxHeader,
-    data_entries: Vec<DataEntryBox>,
+impl Default for MediaInformationBox {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("minf".parse().unwrap()),
+            media_type: MediaType::Null,
+            sound_media_header_box: None,
+            data_information_box: DataInformationBox::default(),
+            sample_table_box: SampleTableBox::default(),
+        }
+    }
 }
 
-impl std::fmt::Debug for DataReferenceBox {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "DataReferenceBox {:?}", self.full_box_header)
+impl MediaInformationBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let mut s = Self {
+            box_header,
+            ..Self::default()
+        };
+        while !stream.is_eof() {
+            let child_box_header = BoxHeader::from_stream(stream)?;
+            let mut ex = stream.extract_from(&child_box_header)?;
+            match child_box_header.box_type().to_string().as_str() {
+                "vmhd" => s.media_type = MediaType::Video,
+                "smhd" => {
+                    s.media_type = MediaType::Sound;
+                    s.sound_media_header_box = Some(SoundMediaHeaderBox::from_stream_header(
+                        &mut ex,
+                        child_box_header,
+                    )?);
+                }
+                "nmhd" => s.media_type = MediaType::Null,
+                "dinf" => {
+                    s.data_information_box =
+                        DataInformationBox::from_stream_header(&mut ex, child_box_header)?;
+                }
+                "stbl" => {
+                    s.sample_table_box =
+                        SampleTableBox::from_stream_header(&mut ex, child_box_header)?;
+                }
+                _ => {} //skip
+            }
+        }
+        Ok(s)
     }
-}
 
-#[derive(Debug)]
-struct SampleTableBox {
-    box_header: BoxHeader,
-    sample_description_box: SampleDescriptionBox,
-}
+    pub fn box_header(&self) -> &BoxHeader {
+        &self.box_header
+    }
 
-#[derive(Debug)]
-struct SampleDescriptionBox {
-    full_box_header: FullBoxHeader,
-    index: Vec<SampleEntryBox>,
-}
+    pub fn media_type(&self) -> MediaType {
+        self.media_type
+    }
 
-#[derive(Debug)]
-struct SampleEntryBox {
-    box_header: BoxHeader,
-    data_reference_index: u16,
+    pub fn sound_media_header_box(&self) -> Option<&SoundMediaHeaderBox> {
+        self.sound_media_header_box.as_ref()
+    }
+
+    pub fn data_information_box(&self) -> &DataInformationBox {
+        &self.data_information_box
+    }
+
+    pub fn sample_table_box(&self) -> &SampleTableBox {
+        &self.sample_table_box
+    }
 }
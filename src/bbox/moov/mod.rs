@@ -1,21 +1,110 @@
+pub mod esds;
 pub mod media;
+pub mod mvex;
+pub mod sample_group;
+pub mod sample_table;
 pub mod track;
 
 use crate::bbox::header::{BoxHeader, FullBoxHeader};
-use crate::bit::Stream;
+use crate::bbox::meta::MetaBox;
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
 use crate::Result;
+use mvex::MovieExtendsBox;
 use track::TrackBox;
 
 #[derive(Debug)]
 pub struct MovieBox {
+    box_header: BoxHeader,
     movie_header_box: MovieHeaderBox,
     tracks: Vec<TrackBox>,
-    is_ozo_preview_file: bool,
+    movie_extends_box: Option<MovieExtendsBox>,
+    meta_box: Option<MetaBox>,
+}
+
+impl Default for MovieBox {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("moov".parse().unwrap()),
+            movie_header_box: MovieHeaderBox::default(),
+            tracks: Vec::new(),
+            movie_extends_box: None,
+            meta_box: None,
+        }
+    }
+}
+
+impl BBox for MovieBox {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
 }
 
 impl MovieBox {
     pub fn new<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
-        unimplemented!("MovieBox")
+        let mut s = Self {
+            box_header,
+            ..Self::default()
+        };
+        while !stream.is_eof() {
+            let child_box_header = BoxHeader::from_stream(stream)?;
+            let mut ex = stream.extract_from(&child_box_header)?;
+            match child_box_header.box_type().to_string().as_str() {
+                "mvhd" => {
+                    s.movie_header_box =
+                        MovieHeaderBox::from_stream_header(&mut ex, child_box_header)?;
+                }
+                "trak" => {
+                    s.tracks
+                        .push(TrackBox::from_stream_header(&mut ex, child_box_header)?);
+                }
+                "mvex" => {
+                    s.movie_extends_box = Some(MovieExtendsBox::from_stream_header(
+                        &mut ex,
+                        child_box_header,
+                    )?);
+                }
+                "meta" => {
+                    s.meta_box = Some(MetaBox::from_stream_header(&mut ex, child_box_header)?);
+                }
+                _ => {} //skip
+            }
+        }
+        Ok(s)
+    }
+
+    pub fn box_header(&self) -> &BoxHeader {
+        &self.box_header
+    }
+
+    pub fn movie_header_box(&self) -> &MovieHeaderBox {
+        &self.movie_header_box
+    }
+
+    pub fn tracks(&self) -> &Vec<TrackBox> {
+        &self.tracks
+    }
+
+    pub fn tracks_mut(&mut self) -> &mut Vec<TrackBox> {
+        &mut self.tracks
+    }
+
+    // Present when the movie is fragmented.
+    pub fn movie_extends_box(&self) -> Option<&MovieExtendsBox> {
+        self.movie_extends_box.as_ref()
+    }
+
+    pub fn meta_box(&self) -> Option<&MetaBox> {
+        self.meta_box.as_ref()
+    }
+
+    // The reader keeps meta boxes by context id and moves them out.
+    pub fn take_meta_box(&mut self) -> Option<MetaBox> {
+        self.meta_box.take()
     }
 }
 
@@ -25,7 +114,84 @@ pub struct MovieHeaderBox {
     creation_time: u64,
     modification_time: u64,
     time_scale: u32,
-    duration: u32,
+    duration: u64,
     matrix: Vec<i32>,
     next_track_id: u32,
 }
+
+impl Default for MovieHeaderBox {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("mvhd".parse().unwrap(), 0, 0),
+            creation_time: 0,
+            modification_time: 0,
+            time_scale: 0,
+            duration: 0,
+            matrix: Vec::new(),
+            next_track_id: 0,
+        }
+    }
+}
+
+impl MovieHeaderBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let (creation_time, modification_time, time_scale, duration) =
+            if full_box_header.version() == 1 {
+                (
+                    stream.read_8bytes()?.to_u64(),
+                    stream.read_8bytes()?.to_u64(),
+                    stream.read_4bytes()?.to_u32(),
+                    stream.read_8bytes()?.to_u64(),
+                )
+            } else {
+                (
+                    u64::from(stream.read_4bytes()?.to_u32()),
+                    u64::from(stream.read_4bytes()?.to_u32()),
+                    stream.read_4bytes()?.to_u32(),
+                    u64::from(stream.read_4bytes()?.to_u32()),
+                )
+            };
+        // rate, volume and reserved fields
+        stream.skip_bytes(16)?;
+        let mut matrix = Vec::new();
+        for _ in 0..9 {
+            matrix.push(stream.read_4bytes()?.to_u32() as i32);
+        }
+        stream.skip_bytes(24)?;
+        let next_track_id = stream.read_4bytes()?.to_u32();
+        Ok(Self {
+            full_box_header,
+            creation_time,
+            modification_time,
+            time_scale,
+            duration,
+            matrix,
+            next_track_id,
+        })
+    }
+
+    pub fn creation_time(&self) -> u64 {
+        self.creation_time
+    }
+
+    pub fn modification_time(&self) -> u64 {
+        self.modification_time
+    }
+
+    pub fn time_scale(&self) -> u32 {
+        self.time_scale
+    }
+
+    pub fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    pub fn matrix(&self) -> &Vec<i32> {
+        &self.matrix
+    }
+
+    pub fn next_track_id(&self) -> u32 {
+        self.next_track_id
+    }
+}
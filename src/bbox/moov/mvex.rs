@@ -0,0 +1,116 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+// Present when the movie continues in movie fragments.
+#[derive(Debug)]
+pub struct MovieExtendsBox {
+    box_header: BoxHeader,
+    fragment_duration: Option<u64>,
+    track_extends_boxes: Vec<TrackExtendsBox>,
+}
+
+impl BBox for MovieExtendsBox {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl MovieExtendsBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let mut fragment_duration = None;
+        let mut track_extends_boxes = Vec::new();
+        while !stream.is_eof() {
+            let child_box_header = BoxHeader::from_stream(stream)?;
+            let mut ex = stream.extract_from(&child_box_header)?;
+            match child_box_header.box_type().to_string().as_str() {
+                "mehd" => {
+                    let full_box_header =
+                        FullBoxHeader::from_stream_header(&mut ex, child_box_header)?;
+                    fragment_duration = Some(if full_box_header.version() == 1 {
+                        ex.read_8bytes()?.to_u64()
+                    } else {
+                        u64::from(ex.read_4bytes()?.to_u32())
+                    });
+                }
+                "trex" => {
+                    track_extends_boxes.push(TrackExtendsBox::from_stream_header(
+                        &mut ex,
+                        child_box_header,
+                    )?);
+                }
+                _ => {} //skip
+            }
+        }
+        Ok(Self {
+            box_header,
+            fragment_duration,
+            track_extends_boxes,
+        })
+    }
+
+    // The duration of the whole movie with its fragments, from mehd.
+    pub fn fragment_duration(&self) -> Option<u64> {
+        self.fragment_duration
+    }
+
+    pub fn track_extends_boxes(&self) -> &Vec<TrackExtendsBox> {
+        &self.track_extends_boxes
+    }
+
+    pub fn track_extends_box(&self, track_id: u32) -> Option<&TrackExtendsBox> {
+        self.track_extends_boxes
+            .iter()
+            .find(|trex| trex.track_id() == track_id)
+    }
+}
+
+// Defaults for the samples of a track in movie fragments.
+#[derive(Debug)]
+pub struct TrackExtendsBox {
+    full_box_header: FullBoxHeader,
+    track_id: u32,
+    default_sample_description_index: u32,
+    default_sample_duration: u32,
+    default_sample_size: u32,
+    default_sample_flags: u32,
+}
+
+impl TrackExtendsBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        Ok(Self {
+            full_box_header,
+            track_id: stream.read_4bytes()?.to_u32(),
+            default_sample_description_index: stream.read_4bytes()?.to_u32(),
+            default_sample_duration: stream.read_4bytes()?.to_u32(),
+            default_sample_size: stream.read_4bytes()?.to_u32(),
+            default_sample_flags: stream.read_4bytes()?.to_u32(),
+        })
+    }
+
+    pub fn track_id(&self) -> u32 {
+        self.track_id
+    }
+
+    pub fn default_sample_description_index(&self) -> u32 {
+        self.default_sample_description_index
+    }
+
+    pub fn default_sample_duration(&self) -> u32 {
+        self.default_sample_duration
+    }
+
+    pub fn default_sample_size(&self) -> u32 {
+        self.default_sample_size
+    }
+
+    pub fn default_sample_flags(&self) -> u32 {
+        self.default_sample_flags
+    }
+}
@@ -0,0 +1,301 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleToGroupEntry {
+    pub sample_count: u32,
+    pub group_description_index: u32,
+}
+
+#[derive(Debug)]
+pub struct SampleToGroupBox {
+    full_box_header: FullBoxHeader,
+    grouping_type: Byte4,
+    grouping_type_parameter: u32,
+    entries: Vec<SampleToGroupEntry>,
+}
+
+impl Default for SampleToGroupBox {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("sbgp".parse().unwrap(), 0, 0),
+            grouping_type: Byte4::default(),
+            grouping_type_parameter: 0,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl BBox for SampleToGroupBox {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl SampleToGroupBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let grouping_type = stream.read_4bytes()?;
+        let grouping_type_parameter = if full_box_header.version() == 1 {
+            stream.read_4bytes()?.to_u32()
+        } else {
+            0
+        };
+        let entry_count = stream.read_4bytes()?.to_u32();
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            entries.push(SampleToGroupEntry {
+                sample_count: stream.read_4bytes()?.to_u32(),
+                group_description_index: stream.read_4bytes()?.to_u32(),
+            });
+        }
+        Ok(Self {
+            full_box_header,
+            grouping_type,
+            grouping_type_parameter,
+            entries,
+        })
+    }
+
+    pub fn full_box_header(&self) -> &FullBoxHeader {
+        &self.full_box_header
+    }
+
+    pub fn grouping_type(&self) -> &Byte4 {
+        &self.grouping_type
+    }
+
+    pub fn grouping_type_parameter(&self) -> u32 {
+        self.grouping_type_parameter
+    }
+
+    pub fn entries(&self) -> &Vec<SampleToGroupEntry> {
+        &self.entries
+    }
+
+    // The description index of each of the first sample_count samples, with
+    // 0 for samples outside any group.
+    pub fn sample_indices(&self, sample_count: u32) -> Vec<u32> {
+        let mut indices = Vec::with_capacity(sample_count as usize);
+        for entry in &self.entries {
+            for _ in 0..entry.sample_count {
+                if indices.len() == sample_count as usize {
+                    return indices;
+                }
+                indices.push(entry.group_description_index);
+            }
+        }
+        indices.resize(sample_count as usize, 0);
+        indices
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SampleGroupEntry {
+    // eqiv: time of the sample relative to its equivalent items.
+    VisualEquivalence {
+        time_offset: i16,
+        timescale_multiplier: u16,
+    },
+    // refs: the samples the sample is predicted from.
+    DirectReferenceSamples {
+        sample_id: u32,
+        reference_sample_ids: Vec<u32>,
+    },
+    // stmi: metadata items of the meta box with the given handler.
+    SampleToMetadataItem {
+        meta_box_handler_type: Byte4,
+        item_ids: Vec<u32>,
+    },
+    Unknown(Vec<u8>),
+}
+
+fn read_entry<T: Stream>(stream: &mut T, grouping_type: &Byte4) -> Result<SampleGroupEntry> {
+    Ok(match grouping_type.to_string().as_str() {
+        "eqiv" => SampleGroupEntry::VisualEquivalence {
+            time_offset: stream.read_2bytes()?.to_u16() as i16,
+            timescale_multiplier: stream.read_2bytes()?.to_u16(),
+        },
+        "refs" => {
+            let sample_id = stream.read_4bytes()?.to_u32();
+            let count = stream.read_byte()?;
+            let mut reference_sample_ids = Vec::new();
+            for _ in 0..count {
+                reference_sample_ids.push(stream.read_4bytes()?.to_u32());
+            }
+            SampleGroupEntry::DirectReferenceSamples {
+                sample_id,
+                reference_sample_ids,
+            }
+        }
+        "stmi" => {
+            let meta_box_handler_type = stream.read_4bytes()?;
+            let count = stream.read_4bytes()?.to_u32();
+            let mut item_ids = Vec::new();
+            for _ in 0..count {
+                item_ids.push(stream.read_4bytes()?.to_u32());
+            }
+            SampleGroupEntry::SampleToMetadataItem {
+                meta_box_handler_type,
+                item_ids,
+            }
+        }
+        _ => SampleGroupEntry::Unknown(stream.read_bytes(stream.num_bytes_left())?.to_vec()),
+    })
+}
+
+#[derive(Debug)]
+pub struct SampleGroupDescriptionBox {
+    full_box_header: FullBoxHeader,
+    grouping_type: Byte4,
+    default_sample_description_index: u32,
+    entries: Vec<SampleGroupEntry>,
+}
+
+impl Default for SampleGroupDescriptionBox {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("sgpd".parse().unwrap(), 0, 0),
+            grouping_type: Byte4::default(),
+            default_sample_description_index: 0,
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl BBox for SampleGroupDescriptionBox {
+    fn box_type(&self) -> &Byte4 {
+        self.full_box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl SampleGroupDescriptionBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let version = full_box_header.version();
+        let grouping_type = stream.read_4bytes()?;
+        let default_length = if version >= 1 {
+            stream.read_4bytes()?.to_u32()
+        } else {
+            0
+        };
+        let default_sample_description_index = if version >= 2 {
+            stream.read_4bytes()?.to_u32()
+        } else {
+            0
+        };
+        let entry_count = stream.read_4bytes()?.to_u32();
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            if version == 0 {
+                // Version 0 entries carry no length, so entries of other types
+                // cannot be told apart and are left out.
+                if !is_known_grouping_type(&grouping_type) {
+                    break;
+                }
+                entries.push(read_entry(stream, &grouping_type)?);
+                continue;
+            }
+            let length = if default_length == 0 {
+                stream.read_4bytes()?.to_u32()
+            } else {
+                default_length
+            };
+            let mut ex = stream.extract(length as usize)?;
+            entries.push(read_entry(&mut ex, &grouping_type)?);
+        }
+        Ok(Self {
+            full_box_header,
+            grouping_type,
+            default_sample_description_index,
+            entries,
+        })
+    }
+
+    pub fn full_box_header(&self) -> &FullBoxHeader {
+        &self.full_box_header
+    }
+
+    pub fn grouping_type(&self) -> &Byte4 {
+        &self.grouping_type
+    }
+
+    // Description index of samples not mapped by an sbgp, 0 if none.
+    pub fn default_sample_description_index(&self) -> u32 {
+        self.default_sample_description_index
+    }
+
+    pub fn entries(&self) -> &Vec<SampleGroupEntry> {
+        &self.entries
+    }
+
+    // Entries are numbered from 1.
+    pub fn entry(&self, group_description_index: u32) -> Option<&SampleGroupEntry> {
+        if group_description_index == 0 {
+            return None;
+        }
+        self.entries.get(group_description_index as usize - 1)
+    }
+}
+
+fn is_known_grouping_type(grouping_type: &Byte4) -> bool {
+    *grouping_type == "eqiv" || *grouping_type == "refs" || *grouping_type == "stmi"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_sbgp_and_sgpd() {
+        let mut stream = BitStream::new(vec![
+            0x00, 0x00, 0x00, 0x24, 0x73, 0x62, 0x67, 0x70, // size, 'sbgp'
+            0x01, 0x00, 0x00, 0x00, // version 1
+            0x65, 0x71, 0x69, 0x76, // 'eqiv'
+            0x00, 0x00, 0x00, 0x00, // grouping_type_parameter
+            0x00, 0x00, 0x00, 0x01, // entry_count
+            0x00, 0x00, 0x00, 0x02, // sample_count
+            0x00, 0x00, 0x00, 0x01, // group_description_index
+        ]);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let sbgp = SampleToGroupBox::from_stream_header(&mut stream, header).unwrap();
+        assert_eq!(*sbgp.grouping_type(), "eqiv");
+        assert_eq!(sbgp.sample_indices(3), vec![1, 1, 0]);
+
+        let mut stream = BitStream::new(vec![
+            0x00, 0x00, 0x00, 0x2C, 0x73, 0x67, 0x70, 0x64, // size, 'sgpd'
+            0x02, 0x00, 0x00, 0x00, // version 2
+            0x73, 0x74, 0x6D, 0x69, // 'stmi'
+            0x00, 0x00, 0x00, 0x00, // default_length
+            0x00, 0x00, 0x00, 0x01, // default_sample_description_index
+            0x00, 0x00, 0x00, 0x01, // entry_count
+            0x00, 0x00, 0x00, 0x0C, // description_length
+            0x45, 0x78, 0x69, 0x66, // meta_box_handler_type
+            0x00, 0x00, 0x00, 0x01, // num_items
+            0x00, 0x00, 0x00, 0x07, // item_id
+        ]);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let sgpd = SampleGroupDescriptionBox::from_stream_header(&mut stream, header).unwrap();
+        assert!(stream.is_eof());
+        assert_eq!(sgpd.default_sample_description_index(), 1);
+        assert_eq!(
+            sgpd.entry(1),
+            Some(&SampleGroupEntry::SampleToMetadataItem {
+                meta_box_handler_type: "Exif".parse().unwrap(),
+                item_ids: vec![7],
+            })
+        );
+        assert_eq!(sgpd.entry(0), None);
+    }
+}
@@ -0,0 +1,912 @@
+use crate::bbox::header::{BoxHeader, FullBoxHeader, Header};
+use crate::bbox::moov::esds::ElementaryStreamDescriptorBox;
+use crate::bbox::moov::sample_group::{SampleGroupDescriptionBox, SampleToGroupBox};
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Extract, Stream};
+use crate::{HeifError, Result};
+
+#[derive(Debug)]
+pub struct SampleTableBox {
+    box_header: BoxHeader,
+    sample_description_box: SampleDescriptionBox,
+    time_to_sample_box: TimeToSampleBox,
+    composition_offset_box: Option<CompositionOffsetBox>,
+    sample_to_chunk_box: SampleToChunkBox,
+    sample_size_box: SampleSizeBox,
+    chunk_offset_box: ChunkOffsetBox,
+    sync_sample_box: Option<SyncSampleBox>,
+    sample_dependency_type_box: Option<SampleDependencyTypeBox>,
+    sample_to_group_boxes: Vec<SampleToGroupBox>,
+    sample_group_description_boxes: Vec<SampleGroupDescriptionBox>,
+}
+
+impl Default for SampleTableBox {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("stbl".parse().unwrap()),
+            sample_description_box: SampleDescriptionBox::default(),
+            time_to_sample_box: TimeToSampleBox::default(),
+            composition_offset_box: None,
+            sample_to_chunk_box: SampleToChunkBox::default(),
+            sample_size_box: SampleSizeBox::default(),
+            chunk_offset_box: ChunkOffsetBox::default(),
+            sync_sample_box: None,
+            sample_dependency_type_box: None,
+            sample_to_group_boxes: Vec::new(),
+            sample_group_description_boxes: Vec::new(),
+        }
+    }
+}
+
+impl BBox for SampleTableBox {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl SampleTableBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let mut s = Self {
+            box_header,
+            ..Self::default()
+        };
+        while !stream.is_eof() {
+            let child_box_header = BoxHeader::from_stream(stream)?;
+            let mut ex = stream.extract_from(&child_box_header)?;
+            match child_box_header.box_type().to_string().as_str() {
+                "stsd" => {
+                    s.sample_description_box =
+                        SampleDescriptionBox::from_stream_header(&mut ex, child_box_header)?;
+                }
+                "stts" => {
+                    s.time_to_sample_box =
+                        TimeToSampleBox::from_stream_header(&mut ex, child_box_header)?;
+                }
+                "ctts" => {
+                    s.composition_offset_box = Some(CompositionOffsetBox::from_stream_header(
+                        &mut ex,
+                        child_box_header,
+                    )?);
+                }
+                "stsc" => {
+                    s.sample_to_chunk_box =
+                        SampleToChunkBox::from_stream_header(&mut ex, child_box_header)?;
+                }
+                "stsz" | "stz2" => {
+                    s.sample_size_box =
+                        SampleSizeBox::from_stream_header(&mut ex, child_box_header)?;
+                }
+                "stco" | "co64" => {
+                    s.chunk_offset_box =
+                        ChunkOffsetBox::from_stream_header(&mut ex, child_box_header)?;
+                }
+                "stss" => {
+                    s.sync_sample_box = Some(SyncSampleBox::from_stream_header(
+                        &mut ex,
+                        child_box_header,
+                    )?);
+                }
+                "sdtp" => {
+                    s.sample_dependency_type_box = Some(
+                        SampleDependencyTypeBox::from_stream_header(&mut ex, child_box_header)?,
+                    );
+                }
+                "sbgp" => {
+                    s.sample_to_group_boxes
+                        .push(SampleToGroupBox::from_stream_header(
+                            &mut ex,
+                            child_box_header,
+                        )?);
+                }
+                "sgpd" => {
+                    s.sample_group_description_boxes.push(
+                        SampleGroupDescriptionBox::from_stream_header(&mut ex, child_box_header)?,
+                    );
+                }
+                _ => {} //skip
+            }
+        }
+        Ok(s)
+    }
+
+    pub fn box_header(&self) -> &BoxHeader {
+        &self.box_header
+    }
+
+    pub fn sample_description_box(&self) -> &SampleDescriptionBox {
+        &self.sample_description_box
+    }
+
+    pub fn time_to_sample_box(&self) -> &TimeToSampleBox {
+        &self.time_to_sample_box
+    }
+
+    pub fn composition_offset_box(&self) -> Option<&CompositionOffsetBox> {
+        self.composition_offset_box.as_ref()
+    }
+
+    pub fn sample_to_chunk_box(&self) -> &SampleToChunkBox {
+        &self.sample_to_chunk_box
+    }
+
+    pub fn sample_size_box(&self) -> &SampleSizeBox {
+        &self.sample_size_box
+    }
+
+    pub fn chunk_offset_box(&self) -> &ChunkOffsetBox {
+        &self.chunk_offset_box
+    }
+
+    // None when every sample is a sync sample.
+    pub fn sync_sample_box(&self) -> Option<&SyncSampleBox> {
+        self.sync_sample_box.as_ref()
+    }
+
+    pub fn sample_dependency_type_box(&self) -> Option<&SampleDependencyTypeBox> {
+        self.sample_dependency_type_box.as_ref()
+    }
+
+    // Whether a sample, numbered from 0, is a sync sample.
+    pub fn is_sync_sample(&self, sample: u32) -> bool {
+        match &self.sync_sample_box {
+            Some(stss) => stss.sample_numbers().contains(&(sample + 1)),
+            None => true,
+        }
+    }
+
+    pub fn sample_to_group_boxes(&self) -> &Vec<SampleToGroupBox> {
+        &self.sample_to_group_boxes
+    }
+
+    pub fn sample_group_description_boxes(&self) -> &Vec<SampleGroupDescriptionBox> {
+        &self.sample_group_description_boxes
+    }
+
+    pub fn sample_group_description_box(
+        &self,
+        grouping_type: &Byte4,
+    ) -> Option<&SampleGroupDescriptionBox> {
+        self.sample_group_description_boxes
+            .iter()
+            .find(|sgpd| sgpd.grouping_type() == grouping_type)
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_size_box.sample_count()
+    }
+
+    // File offset of each sample, from the chunk offsets and the sizes of
+    // the samples before it in its chunk.
+    pub fn sample_offsets(&self) -> Result<Vec<u64>> {
+        let sample_count = self.sample_count() as usize;
+        let chunk_offsets = self.chunk_offset_box.chunk_offsets();
+        let mut offsets = Vec::with_capacity(sample_count);
+        let mut sample = 0;
+        for (chunk_index, chunk_offset) in chunk_offsets.iter().enumerate() {
+            let entry = match self
+                .sample_to_chunk_box
+                .entry_for_chunk(chunk_index as u32 + 1)
+            {
+                Some(entry) => entry,
+                None => return Err(HeifError::Unknown("stsc does not cover every chunk")),
+            };
+            let mut offset = *chunk_offset;
+            for _ in 0..entry.samples_per_chunk {
+                if sample == sample_count {
+                    break;
+                }
+                offsets.push(offset);
+                offset += u64::from(self.sample_size_box.sample_size(sample as u32));
+                sample += 1;
+            }
+        }
+        if offsets.len() != sample_count {
+            return Err(HeifError::Unknown("chunks do not hold every sample"));
+        }
+        Ok(offsets)
+    }
+
+    // The sample description index of each sample, from 1.
+    pub fn sample_description_indices(&self) -> Vec<u32> {
+        let sample_count = self.sample_count() as usize;
+        let mut indices = Vec::with_capacity(sample_count);
+        for chunk in 1..=self.chunk_offset_box.chunk_offsets().len() as u32 {
+            if let Some(entry) = self.sample_to_chunk_box.entry_for_chunk(chunk) {
+                for _ in 0..entry.samples_per_chunk {
+                    if indices.len() == sample_count {
+                        return indices;
+                    }
+                    indices.push(entry.sample_description_index);
+                }
+            }
+        }
+        indices
+    }
+}
+
+#[derive(Debug)]
+pub struct SampleDescriptionBox {
+    full_box_header: FullBoxHeader,
+    sample_entries: Vec<SampleEntryBox>,
+}
+
+impl Default for SampleDescriptionBox {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("stsd".parse().unwrap(), 0, 0),
+            sample_entries: Vec::new(),
+        }
+    }
+}
+
+impl SampleDescriptionBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let entry_count = stream.read_4bytes()?.to_u32();
+        let mut sample_entries = Vec::new();
+        for _ in 0..entry_count {
+            let child_box_header = BoxHeader::from_stream(stream)?;
+            let mut ex = stream.extract_from(&child_box_header)?;
+            sample_entries.push(SampleEntryBox::from_stream_header(
+                &mut ex,
+                child_box_header,
+            )?);
+        }
+        Ok(Self {
+            full_box_header,
+            sample_entries,
+        })
+    }
+
+    pub fn sample_entries(&self) -> &Vec<SampleEntryBox> {
+        &self.sample_entries
+    }
+
+    // Sample description indices count from 1.
+    pub fn sample_entry(&self, sample_description_index: u32) -> Option<&SampleEntryBox> {
+        if sample_description_index == 0 {
+            return None;
+        }
+        self.sample_entries
+            .get(sample_description_index as usize - 1)
+    }
+}
+
+// Size of the fields of a VisualSampleEntry after data_reference_index.
+pub const VISUAL_SAMPLE_ENTRY_SIZE: usize = 70;
+// Size of the fields of an AudioSampleEntry after data_reference_index.
+pub const AUDIO_SAMPLE_ENTRY_SIZE: usize = 20;
+
+// The fields common to every sample entry. The type specific fields and
+// child boxes are kept undecoded in payload.
+#[derive(Debug)]
+pub struct SampleEntryBox {
+    box_header: BoxHeader,
+    data_reference_index: u16,
+    payload: Vec<u8>,
+}
+
+impl SampleEntryBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        stream.skip_bytes(6)?;
+        let data_reference_index = stream.read_2bytes()?.to_u16();
+        let payload = stream.read_bytes(stream.num_bytes_left())?.to_vec();
+        Ok(Self {
+            box_header,
+            data_reference_index,
+            payload,
+        })
+    }
+
+    pub fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    pub fn data_reference_index(&self) -> u16 {
+        self.data_reference_index
+    }
+
+    pub fn payload(&self) -> &Vec<u8> {
+        &self.payload
+    }
+
+    // Width and height of a visual sample entry.
+    pub fn visual_size(&self) -> Result<(u16, u16)> {
+        if self.payload.len() < VISUAL_SAMPLE_ENTRY_SIZE {
+            return Err(HeifError::Unknown("visual sample entry is too short"));
+        }
+        let width = u16::from_be_bytes([self.payload[16], self.payload[17]]);
+        let height = u16::from_be_bytes([self.payload[18], self.payload[19]]);
+        Ok((width, height))
+    }
+
+    // The namespace of a metx entry or the MIME type of a mett entry. Both
+    // follow the content_encoding string.
+    pub fn metadata_content_type(&self) -> Option<String> {
+        let box_type = self.box_type().to_string();
+        if box_type != "metx" && box_type != "mett" {
+            return None;
+        }
+        let mut stream = Extract::new(&self.payload);
+        stream.read_zero_term_string();
+        Some(stream.read_zero_term_string())
+    }
+
+    // The child boxes which follow the fixed_size bytes of type specific
+    // fields, with their payloads.
+    fn child_boxes(&self, fixed_size: usize) -> Result<Vec<(BoxHeader, &[u8])>> {
+        if self.payload.len() < fixed_size {
+            return Err(HeifError::Unknown("sample entry is too short"));
+        }
+        let mut children = Vec::new();
+        let mut offset = fixed_size;
+        while offset < self.payload.len() {
+            let mut stream = Extract::new(&self.payload[offset..]);
+            let child_box_header = BoxHeader::from_stream(&mut stream)?;
+            let start = offset + stream.get_byte_offset();
+            let end = start + child_box_header.body_size() as usize;
+            if end > self.payload.len() {
+                return Err(HeifError::EOF);
+            }
+            children.push((child_box_header, &self.payload[start..end]));
+            offset = end;
+        }
+        Ok(children)
+    }
+
+    fn parse_child_box<B, F>(
+        &self,
+        fixed_size: usize,
+        box_type: &str,
+        parse: F,
+    ) -> Result<Option<B>>
+    where
+        F: Fn(&mut Extract, BoxHeader) -> Result<B>,
+    {
+        for (child_box_header, payload) in self.child_boxes(fixed_size)? {
+            if *child_box_header.box_type() == box_type {
+                let mut ex = Extract::new(payload);
+                return Ok(Some(parse(&mut ex, child_box_header)?));
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn visual_child_boxes(&self) -> Result<Vec<(BoxHeader, &[u8])>> {
+        self.child_boxes(VISUAL_SAMPLE_ENTRY_SIZE)
+    }
+
+    pub fn audio_child_boxes(&self) -> Result<Vec<(BoxHeader, &[u8])>> {
+        self.child_boxes(AUDIO_SAMPLE_ENTRY_SIZE)
+    }
+
+    // Channel count, sample size and sample rate of an audio sample entry.
+    // The sample rate is 16.16 fixed point.
+    pub fn audio_format(&self) -> Result<(u16, u16, u32)> {
+        if self.payload.len() < AUDIO_SAMPLE_ENTRY_SIZE {
+            return Err(HeifError::Unknown("audio sample entry is too short"));
+        }
+        let mut stream = Extract::new(&self.payload);
+        stream.skip_bytes(8)?;
+        let channel_count = stream.read_2bytes()?.to_u16();
+        let sample_size = stream.read_2bytes()?.to_u16();
+        stream.skip_bytes(4)?;
+        let sample_rate = stream.read_4bytes()?.to_u32();
+        Ok((channel_count, sample_size, sample_rate))
+    }
+
+    pub fn elementary_stream_descriptor_box(
+        &self,
+    ) -> Result<Option<ElementaryStreamDescriptorBox>> {
+        self.parse_child_box(AUDIO_SAMPLE_ENTRY_SIZE, "esds", |ex, header| {
+            ElementaryStreamDescriptorBox::from_stream_header(ex, header)
+        })
+    }
+
+    pub fn has_visual_child_box(&self, box_type: &str) -> bool {
+        self.visual_child_boxes()
+            .map(|children| {
+                children
+                    .iter()
+                    .any(|(header, _)| *header.box_type() == box_type)
+            })
+            .unwrap_or(false)
+    }
+
+    pub fn coding_constraints_box(&self) -> Result<Option<CodingConstraintsBox>> {
+        self.parse_child_box(VISUAL_SAMPLE_ENTRY_SIZE, "ccst", |ex, header| {
+            CodingConstraintsBox::from_stream_header(ex, header)
+        })
+    }
+
+    pub fn auxiliary_type_info_box(&self) -> Result<Option<AuxiliaryTypeInfoBox>> {
+        self.parse_child_box(VISUAL_SAMPLE_ENTRY_SIZE, "auxi", |ex, header| {
+            AuxiliaryTypeInfoBox::from_stream_header(ex, header)
+        })
+    }
+}
+
+// The auxi box of the sample entries of auxiliary image sequences.
+#[derive(Debug)]
+pub struct AuxiliaryTypeInfoBox {
+    full_box_header: FullBoxHeader,
+    aux_track_type: String,
+}
+
+impl AuxiliaryTypeInfoBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let aux_track_type = stream.read_zero_term_string();
+        Ok(Self {
+            full_box_header,
+            aux_track_type,
+        })
+    }
+
+    pub fn aux_track_type(&self) -> &str {
+        &self.aux_track_type
+    }
+}
+
+#[derive(Debug)]
+pub struct CodingConstraintsBox {
+    full_box_header: FullBoxHeader,
+    all_ref_pics_intra: bool,
+    intra_pred_used: bool,
+    max_ref_per_pic: u8,
+}
+
+impl CodingConstraintsBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let all_ref_pics_intra = stream.read_flag()?;
+        let intra_pred_used = stream.read_flag()?;
+        let max_ref_per_pic = stream.read_bits(4)? as u8;
+        // reserved
+        stream.read_bits(26)?;
+        Ok(Self {
+            full_box_header,
+            all_ref_pics_intra,
+            intra_pred_used,
+            max_ref_per_pic,
+        })
+    }
+
+    pub fn all_ref_pics_intra(&self) -> bool {
+        self.all_ref_pics_intra
+    }
+
+    pub fn intra_pred_used(&self) -> bool {
+        self.intra_pred_used
+    }
+
+    // 15 means any number of references.
+    pub fn max_ref_per_pic(&self) -> u8 {
+        self.max_ref_per_pic
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeToSampleEntry {
+    pub sample_count: u32,
+    pub sample_delta: u32,
+}
+
+#[derive(Debug)]
+pub struct TimeToSampleBox {
+    full_box_header: FullBoxHeader,
+    entries: Vec<TimeToSampleEntry>,
+}
+
+impl Default for TimeToSampleBox {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("stts".parse().unwrap(), 0, 0),
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl TimeToSampleBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let entry_count = stream.read_4bytes()?.to_u32();
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            entries.push(TimeToSampleEntry {
+                sample_count: stream.read_4bytes()?.to_u32(),
+                sample_delta: stream.read_4bytes()?.to_u32(),
+            });
+        }
+        Ok(Self {
+            full_box_header,
+            entries,
+        })
+    }
+
+    pub fn entries(&self) -> &Vec<TimeToSampleEntry> {
+        &self.entries
+    }
+
+    // The duration of each of the first sample_count samples.
+    pub fn sample_deltas(&self, sample_count: u32) -> Vec<u32> {
+        let mut deltas = Vec::with_capacity(sample_count as usize);
+        for entry in &self.entries {
+            for _ in 0..entry.sample_count {
+                if deltas.len() == sample_count as usize {
+                    return deltas;
+                }
+                deltas.push(entry.sample_delta);
+            }
+        }
+        deltas.resize(sample_count as usize, 0);
+        deltas
+    }
+}
+
+#[derive(Debug)]
+pub struct CompositionOffsetBox {
+    full_box_header: FullBoxHeader,
+    entries: Vec<(u32, i64)>,
+}
+
+impl CompositionOffsetBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let entry_count = stream.read_4bytes()?.to_u32();
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            let sample_count = stream.read_4bytes()?.to_u32();
+            let offset = stream.read_4bytes()?.to_u32();
+            let offset = if full_box_header.version() == 0 {
+                i64::from(offset)
+            } else {
+                i64::from(offset as i32)
+            };
+            entries.push((sample_count, offset));
+        }
+        Ok(Self {
+            full_box_header,
+            entries,
+        })
+    }
+
+    // The composition offset of each of the first sample_count samples.
+    pub fn sample_offsets(&self, sample_count: u32) -> Vec<i64> {
+        let mut offsets = Vec::with_capacity(sample_count as usize);
+        for (count, offset) in &self.entries {
+            for _ in 0..*count {
+                if offsets.len() == sample_count as usize {
+                    return offsets;
+                }
+                offsets.push(*offset);
+            }
+        }
+        offsets.resize(sample_count as usize, 0);
+        offsets
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleToChunkEntry {
+    pub first_chunk: u32,
+    pub samples_per_chunk: u32,
+    pub sample_description_index: u32,
+}
+
+#[derive(Debug)]
+pub struct SampleToChunkBox {
+    full_box_header: FullBoxHeader,
+    entries: Vec<SampleToChunkEntry>,
+}
+
+impl Default for SampleToChunkBox {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("stsc".parse().unwrap(), 0, 0),
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl SampleToChunkBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let entry_count = stream.read_4bytes()?.to_u32();
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            entries.push(SampleToChunkEntry {
+                first_chunk: stream.read_4bytes()?.to_u32(),
+                samples_per_chunk: stream.read_4bytes()?.to_u32(),
+                sample_description_index: stream.read_4bytes()?.to_u32(),
+            });
+        }
+        Ok(Self {
+            full_box_header,
+            entries,
+        })
+    }
+
+    pub fn entries(&self) -> &Vec<SampleToChunkEntry> {
+        &self.entries
+    }
+
+    // Chunks count from 1. Each entry runs until the first chunk of the next.
+    pub fn entry_for_chunk(&self, chunk: u32) -> Option<&SampleToChunkEntry> {
+        self.entries.iter().rev().find(|e| e.first_chunk <= chunk)
+    }
+}
+
+#[derive(Debug)]
+pub struct SampleSizeBox {
+    full_box_header: FullBoxHeader,
+    sample_size: u32,
+    sample_count: u32,
+    entry_sizes: Vec<u32>,
+}
+
+impl Default for SampleSizeBox {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("stsz".parse().unwrap(), 0, 0),
+            sample_size: 0,
+            sample_count: 0,
+            entry_sizes: Vec::new(),
+        }
+    }
+}
+
+impl SampleSizeBox {
+    // Reads both stsz and the compact stz2.
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let (sample_size, field_size) = if *full_box_header.box_type() == "stz2" {
+            stream.skip_bytes(3)?;
+            (0, stream.read_byte()?)
+        } else {
+            (stream.read_4bytes()?.to_u32(), 32)
+        };
+        let sample_count = stream.read_4bytes()?.to_u32();
+        let mut entry_sizes = Vec::new();
+        if sample_size == 0 {
+            for _ in 0..sample_count {
+                entry_sizes.push(match field_size {
+                    4 | 8 | 16 => stream.read_bits(field_size as usize)? as u32,
+                    32 => stream.read_4bytes()?.to_u32(),
+                    _ => return Err(HeifError::Unknown("invalid stz2 field_size")),
+                });
+            }
+        }
+        Ok(Self {
+            full_box_header,
+            sample_size,
+            sample_count,
+            entry_sizes,
+        })
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    // Samples count from 0.
+    pub fn sample_size(&self, sample: u32) -> u32 {
+        if self.sample_size != 0 {
+            return self.sample_size;
+        }
+        self.entry_sizes.get(sample as usize).copied().unwrap_or(0)
+    }
+}
+
+#[derive(Debug)]
+pub struct ChunkOffsetBox {
+    full_box_header: FullBoxHeader,
+    chunk_offsets: Vec<u64>,
+}
+
+impl Default for ChunkOffsetBox {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("stco".parse().unwrap(), 0, 0),
+            chunk_offsets: Vec::new(),
+        }
+    }
+}
+
+impl ChunkOffsetBox {
+    // Reads both stco and co64.
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let is_large = *full_box_header.box_type() == "co64";
+        let entry_count = stream.read_4bytes()?.to_u32();
+        let mut chunk_offsets = Vec::new();
+        for _ in 0..entry_count {
+            chunk_offsets.push(if is_large {
+                stream.read_8bytes()?.to_u64()
+            } else {
+                u64::from(stream.read_4bytes()?.to_u32())
+            });
+        }
+        Ok(Self {
+            full_box_header,
+            chunk_offsets,
+        })
+    }
+
+    pub fn chunk_offsets(&self) -> &Vec<u64> {
+        &self.chunk_offsets
+    }
+}
+
+#[derive(Debug)]
+pub struct SyncSampleBox {
+    full_box_header: FullBoxHeader,
+    sample_numbers: Vec<u32>,
+}
+
+impl SyncSampleBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let entry_count = stream.read_4bytes()?.to_u32();
+        let mut sample_numbers = Vec::new();
+        for _ in 0..entry_count {
+            sample_numbers.push(stream.read_4bytes()?.to_u32());
+        }
+        Ok(Self {
+            full_box_header,
+            sample_numbers,
+        })
+    }
+
+    // Sample numbers count from 1.
+    pub fn sample_numbers(&self) -> &Vec<u32> {
+        &self.sample_numbers
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleDependency {
+    pub is_leading: u8,
+    pub sample_depends_on: u8,
+    pub sample_is_depended_on: u8,
+    pub sample_has_redundancy: u8,
+}
+
+// One entry per sample. The sample count comes from the box size.
+#[derive(Debug)]
+pub struct SampleDependencyTypeBox {
+    full_box_header: FullBoxHeader,
+    entries: Vec<SampleDependency>,
+}
+
+impl SampleDependencyTypeBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let mut entries = Vec::new();
+        while !stream.is_eof() {
+            let byte = stream.read_byte()?;
+            entries.push(SampleDependency {
+                is_leading: (byte >> 6) & 0x03,
+                sample_depends_on: (byte >> 4) & 0x03,
+                sample_is_depended_on: (byte >> 2) & 0x03,
+                sample_has_redundancy: byte & 0x03,
+            });
+        }
+        Ok(Self {
+            full_box_header,
+            entries,
+        })
+    }
+
+    pub fn entries(&self) -> &Vec<SampleDependency> {
+        &self.entries
+    }
+
+    // The entry of a sample numbered from 0.
+    pub fn entry(&self, sample: u32) -> Option<&SampleDependency> {
+        self.entries.get(sample as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    fn parse(data: Vec<u8>) -> SampleTableBox {
+        let mut stream = BitStream::new(data);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let stbl = SampleTableBox::from_stream_header(&mut stream, header).unwrap();
+        assert!(stream.is_eof());
+        stbl
+    }
+
+    #[test]
+    fn test_sample_offsets_across_chunks() {
+        let stbl = parse(vec![
+            0x00, 0x00, 0x00, 0x74, 0x73, 0x74, 0x62, 0x6C, // size, 'stbl'
+            0x00, 0x00, 0x00, 0x18, 0x73, 0x74, 0x74, 0x73, // size, 'stts'
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, // entry_count
+            0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x02, 0x00, // 3 samples of 512
+            0x00, 0x00, 0x00, 0x1C, 0x73, 0x74, 0x73, 0x63, // size, 'stsc'
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, // entry_count
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, // 2 samples per chunk
+            0x00, 0x00, 0x00, 0x01, // sample_description_index
+            0x00, 0x00, 0x00, 0x20, 0x73, 0x74, 0x73, 0x7A, // size, 'stsz'
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // sample_size
+            0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x0A, // sample_count, size
+            0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x1E, // sizes
+            0x00, 0x00, 0x00, 0x18, 0x73, 0x74, 0x63, 0x6F, // size, 'stco'
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, // entry_count
+            0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x08, 0x00, // chunk offsets
+        ]);
+        assert_eq!(stbl.sample_count(), 3);
+        assert_eq!(stbl.sample_offsets().unwrap(), vec![0x100, 0x10A, 0x800]);
+        assert_eq!(stbl.sample_description_indices(), vec![1, 1, 1]);
+        assert_eq!(stbl.time_to_sample_box().sample_deltas(3), vec![512; 3]);
+        assert!(stbl.sync_sample_box().is_none());
+    }
+
+    #[test]
+    fn test_visual_sample_entry_ccst() {
+        let mut payload = vec![0u8; VISUAL_SAMPLE_ENTRY_SIZE];
+        payload[16..20].copy_from_slice(&[0x00, 0x40, 0x00, 0x30]);
+        payload.extend_from_slice(&[
+            0x00, 0x00, 0x00, 0x10, 0x63, 0x63, 0x73, 0x74, // size, 'ccst'
+            0x00, 0x00, 0x00, 0x00, 0xB0, 0x00, 0x00, 0x00, // intra refs, 12 refs
+            0x00, 0x00, 0x00, 0x08, 0x63, 0x6C, 0x61, 0x70, // size, 'clap'
+        ]);
+        let entry = SampleEntryBox {
+            box_header: BoxHeader::new("hvc1".parse().unwrap()),
+            data_reference_index: 1,
+            payload,
+        };
+        assert_eq!(entry.visual_size().unwrap(), (64, 48));
+        let ccst = entry.coding_constraints_box().unwrap().unwrap();
+        assert!(ccst.all_ref_pics_intra());
+        assert!(!ccst.intra_pred_used());
+        assert_eq!(ccst.max_ref_per_pic(), 12);
+        assert!(entry.has_visual_child_box("clap"));
+        assert!(!entry.has_visual_child_box("auxi"));
+    }
+
+    #[test]
+    fn test_sdtp() {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x0F, 0x73, 0x64, 0x74, 0x70, // size, 'sdtp'
+            0x00, 0x00, 0x00, 0x00, 0x24, 0x18, 0x18, // three samples
+        ];
+        let mut stream = BitStream::new(data);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let sdtp = SampleDependencyTypeBox::from_stream_header(&mut stream, header).unwrap();
+        assert_eq!(sdtp.entries().len(), 3);
+        let first = sdtp.entry(0).unwrap();
+        assert_eq!(first.sample_depends_on, 2);
+        assert_eq!(first.sample_is_depended_on, 1);
+        assert_eq!(sdtp.entry(1).unwrap().sample_depends_on, 1);
+        assert_eq!(sdtp.entry(1).unwrap().sample_is_depended_on, 2);
+        assert!(sdtp.entry(3).is_none());
+    }
+
+    #[test]
+    fn test_metadata_content_type() {
+        let entry = |box_type: &str, payload: &[u8]| SampleEntryBox {
+            box_header: BoxHeader::new(box_type.parse().unwrap()),
+            data_reference_index: 1,
+            payload: payload.to_vec(),
+        };
+        assert_eq!(
+            entry("mett", b"\0text/plain\0").metadata_content_type(),
+            Some("text/plain".to_string())
+        );
+        assert_eq!(
+            entry("metx", b"gzip\0http://ns.adobe.com/xap/1.0/\0\0").metadata_content_type(),
+            Some("http://ns.adobe.com/xap/1.0/".to_string())
+        );
+        assert_eq!(entry("exif", b"").metadata_content_type(), None);
+    }
+}
@@ -1,5 +1,9 @@
 use crate::bbox::header::{BoxHeader, FullBoxHeader};
+use crate::bbox::meta::MetaBox;
 use crate::bbox::moov::media::MediaBox;
+use crate::bbox::BBox;
+use crate::bit::{Byte4, Stream};
+use crate::Result;
 
 #[derive(Debug)]
 pub struct TrackBox {
@@ -8,7 +12,109 @@ pub struct TrackBox {
     media_box: MediaBox,
     track_reference_box: TrackReferenceBox,
     has_track_references: bool,
-    edit_box: EditBox,
+    edit_box: Option<EditBox>,
+    meta_box: Option<MetaBox>,
+}
+
+impl Default for TrackBox {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("trak".parse().unwrap()),
+            track_header_box: TrackHeaderBox::default(),
+            media_box: MediaBox::default(),
+            track_reference_box: TrackReferenceBox::default(),
+            has_track_references: false,
+            edit_box: None,
+            meta_box: None,
+        }
+    }
+}
+
+impl BBox for TrackBox {
+    fn box_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+impl TrackBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let mut s = Self {
+            box_header,
+            ..Self::default()
+        };
+        while !stream.is_eof() {
+            let child_box_header = BoxHeader::from_stream(stream)?;
+            let mut ex = stream.extract_from(&child_box_header)?;
+            match child_box_header.box_type().to_string().as_str() {
+                "tkhd" => {
+                    s.track_header_box =
+                        TrackHeaderBox::from_stream_header(&mut ex, child_box_header)?;
+                }
+                "mdia" => {
+                    s.media_box = MediaBox::from_stream_header(&mut ex, child_box_header)?;
+                }
+                "tref" => {
+                    s.track_reference_box =
+                        TrackReferenceBox::from_stream_header(&mut ex, child_box_header)?;
+                    s.has_track_references = true;
+                }
+                "edts" => {
+                    while !ex.is_eof() {
+                        let edts_child_header = BoxHeader::from_stream(&mut ex)?;
+                        let mut edts_ex = ex.extract_from(&edts_child_header)?;
+                        if *edts_child_header.box_type() == "elst" {
+                            s.edit_box = Some(EditBox::from_stream_header(
+                                &mut edts_ex,
+                                edts_child_header,
+                            )?);
+                        }
+                    }
+                }
+                "meta" => {
+                    s.meta_box = Some(MetaBox::from_stream_header(&mut ex, child_box_header)?);
+                }
+                _ => {} //skip
+            }
+        }
+        Ok(s)
+    }
+
+    pub fn box_header(&self) -> &BoxHeader {
+        &self.box_header
+    }
+
+    pub fn track_header_box(&self) -> &TrackHeaderBox {
+        &self.track_header_box
+    }
+
+    pub fn media_box(&self) -> &MediaBox {
+        &self.media_box
+    }
+
+    pub fn track_reference_box(&self) -> &TrackReferenceBox {
+        &self.track_reference_box
+    }
+
+    pub fn has_track_references(&self) -> bool {
+        self.has_track_references
+    }
+
+    pub fn edit_box(&self) -> Option<&EditBox> {
+        self.edit_box.as_ref()
+    }
+
+    pub fn meta_box(&self) -> Option<&MetaBox> {
+        self.meta_box.as_ref()
+    }
+
+    // The reader keeps meta boxes by context id and moves them out.
+    pub fn take_meta_box(&mut self) -> Option<MetaBox> {
+        self.meta_box.take()
+    }
 }
 
 #[derive(Debug)]
@@ -25,10 +131,130 @@ pub struct TrackHeaderBox {
     matrix: Vec<i32>,
 }
 
+impl Default for TrackHeaderBox {
+    fn default() -> Self {
+        Self {
+            full_box_header: FullBoxHeader::new("tkhd".parse().unwrap(), 0, 0),
+            creation_time: 0,
+            modification_time: 0,
+            track_id: 0,
+            duration: 0,
+            width: 0,
+            height: 0,
+            alternate_group: 0,
+            volume: 0,
+            matrix: Vec::new(),
+        }
+    }
+}
+
+impl TrackHeaderBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let is_large = full_box_header.version() == 1;
+        let read_time = |stream: &mut T| -> Result<u64> {
+            if is_large {
+                Ok(stream.read_8bytes()?.to_u64())
+            } else {
+                Ok(u64::from(stream.read_4bytes()?.to_u32()))
+            }
+        };
+        let creation_time = read_time(stream)?;
+        let modification_time = read_time(stream)?;
+        let track_id = stream.read_4bytes()?.to_u32();
+        stream.skip_bytes(4)?;
+        let duration = read_time(stream)?;
+        stream.skip_bytes(10)?;
+        let alternate_group = stream.read_2bytes()?.to_u16();
+        let volume = stream.read_2bytes()?.to_u16();
+        stream.skip_bytes(2)?;
+        let mut matrix = Vec::new();
+        for _ in 0..9 {
+            matrix.push(stream.read_4bytes()?.to_u32() as i32);
+        }
+        // Width and height are 16.16 fixed point.
+        let width = stream.read_4bytes()?.to_u32() >> 16;
+        let height = stream.read_4bytes()?.to_u32() >> 16;
+        Ok(Self {
+            full_box_header,
+            creation_time,
+            modification_time,
+            track_id,
+            duration,
+            width,
+            height,
+            alternate_group,
+            volume,
+            matrix,
+        })
+    }
+
+    pub fn full_box_header(&self) -> &FullBoxHeader {
+        &self.full_box_header
+    }
+
+    pub fn creation_time(&self) -> u64 {
+        self.creation_time
+    }
+
+    pub fn modification_time(&self) -> u64 {
+        self.modification_time
+    }
+
+    pub fn track_id(&self) -> u32 {
+        self.track_id
+    }
+
+    pub fn duration(&self) -> u64 {
+        self.duration
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn alternate_group(&self) -> u16 {
+        self.alternate_group
+    }
+
+    pub fn volume(&self) -> u16 {
+        self.volume
+    }
+
+    pub fn matrix(&self) -> &Vec<i32> {
+        &self.matrix
+    }
+}
+
 #[derive(Debug)]
-struct TrackReferenceTypeBox {
+pub struct TrackReferenceTypeBox {
     box_header: BoxHeader,
-    track_id: Vec<u32>,
+    track_ids: Vec<u32>,
+}
+
+impl TrackReferenceTypeBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let mut track_ids = Vec::new();
+        while !stream.is_eof() {
+            track_ids.push(stream.read_4bytes()?.to_u32());
+        }
+        Ok(Self {
+            box_header,
+            track_ids,
+        })
+    }
+
+    pub fn reference_type(&self) -> &Byte4 {
+        self.box_header.box_type()
+    }
+
+    pub fn track_ids(&self) -> &Vec<u32> {
+        &self.track_ids
+    }
 }
 
 #[derive(Debug)]
@@ -37,25 +263,138 @@ pub struct TrackReferenceBox {
     track_ref_type_boxes: Vec<TrackReferenceTypeBox>,
 }
 
+impl Default for TrackReferenceBox {
+    fn default() -> Self {
+        Self {
+            box_header: BoxHeader::new("tref".parse().unwrap()),
+            track_ref_type_boxes: Vec::new(),
+        }
+    }
+}
+
+impl TrackReferenceBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let mut track_ref_type_boxes = Vec::new();
+        while !stream.is_eof() {
+            let child_box_header = BoxHeader::from_stream(stream)?;
+            let mut ex = stream.extract_from(&child_box_header)?;
+            track_ref_type_boxes.push(TrackReferenceTypeBox::from_stream_header(
+                &mut ex,
+                child_box_header,
+            )?);
+        }
+        Ok(Self {
+            box_header,
+            track_ref_type_boxes,
+        })
+    }
+
+    pub fn track_ref_type_boxes(&self) -> &Vec<TrackReferenceTypeBox> {
+        &self.track_ref_type_boxes
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EditListEntry {
+    pub segment_duration: u64,
+    pub media_time: i64,
+    pub media_rate_integer: i16,
+    pub media_rate_fraction: i16,
+}
+
+// The elst box of an edts box.
 #[derive(Debug)]
-struct EditBox {
+pub struct EditBox {
     full_box_header: FullBoxHeader,
-    entry_version_0: Vec<EntryVersion0>,
-    entry_version_1: Vec<EntryVersion1>,
+    entries: Vec<EditListEntry>,
 }
 
-#[derive(Debug)]
-struct EntryVersion0 {
-    segment_duration: u32,
-    media_time: i32,
-    media_rate_integer: u16,
-    media_rate_fraction: u16,
+impl EditBox {
+    pub fn from_stream_header<T: Stream>(stream: &mut T, box_header: BoxHeader) -> Result<Self> {
+        let full_box_header = FullBoxHeader::from_stream_header(stream, box_header)?;
+        let is_large = full_box_header.version() == 1;
+        let entry_count = stream.read_4bytes()?.to_u32();
+        let mut entries = Vec::new();
+        for _ in 0..entry_count {
+            let (segment_duration, media_time) = if is_large {
+                (
+                    stream.read_8bytes()?.to_u64(),
+                    stream.read_8bytes()?.to_u64() as i64,
+                )
+            } else {
+                (
+                    u64::from(stream.read_4bytes()?.to_u32()),
+                    i64::from(stream.read_4bytes()?.to_u32() as i32),
+                )
+            };
+            entries.push(EditListEntry {
+                segment_duration,
+                media_time,
+                media_rate_integer: stream.read_2bytes()?.to_u16() as i16,
+                media_rate_fraction: stream.read_2bytes()?.to_u16() as i16,
+            });
+        }
+        Ok(Self {
+            full_box_header,
+            entries,
+        })
+    }
+
+    pub fn full_box_header(&self) -> &FullBoxHeader {
+        &self.full_box_header
+    }
+
+    pub fn entries(&self) -> &Vec<EditListEntry> {
+        &self.entries
+    }
 }
 
-#[derive(Debug)]
-struct EntryVersion1 {
-    segment_duration: u64,
-    media_time: i64,
-    media_rate_integer: u16,
-    media_rate_fraction: u16,
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bit::BitStream;
+
+    #[test]
+    fn test_tref() {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x24, 0x74, 0x72, 0x65, 0x66, // size, 'tref'
+            0x00, 0x00, 0x00, 0x0C, 0x61, 0x75, 0x78, 0x6C, // size, 'auxl'
+            0x00, 0x00, 0x00, 0x01, // track_id
+            0x00, 0x00, 0x00, 0x10, 0x63, 0x64, 0x73, 0x63, // size, 'cdsc'
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, // track_ids
+        ];
+        let mut stream = BitStream::new(data);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let tref = TrackReferenceBox::from_stream_header(&mut stream, header).unwrap();
+        let boxes = tref.track_ref_type_boxes();
+        assert_eq!(boxes.len(), 2);
+        assert_eq!(boxes[0].reference_type().to_string(), "auxl");
+        assert_eq!(boxes[0].track_ids(), &vec![1]);
+        assert_eq!(boxes[1].reference_type().to_string(), "cdsc");
+        assert_eq!(boxes[1].track_ids(), &vec![1, 2]);
+    }
+
+    #[test]
+    fn test_trak_meta() {
+        let data = vec![
+            0x00, 0x00, 0x00, 0x43, 0x74, 0x72, 0x61, 0x6B, // size, 'trak'
+            0x00, 0x00, 0x00, 0x3B, 0x6D, 0x65, 0x74, 0x61, // size, 'meta'
+            0x00, 0x00, 0x00, 0x00, // version, flags
+            0x00, 0x00, 0x00, 0x21, 0x68, 0x64, 0x6C, 0x72, // size, 'hdlr'
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // version, flags, pre_defined
+            0x70, 0x69, 0x63, 0x74, 0x00, 0x00, 0x00, 0x00, // 'pict', reserved
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // reserved
+            0x00, // name
+            0x00, 0x00, 0x00, 0x0E, 0x70, 0x69, 0x74, 0x6D, // size, 'pitm'
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x07, // version, flags, item_id
+        ];
+        let mut stream = BitStream::new(data);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        let mut trak = TrackBox::from_stream_header(&mut stream, header).unwrap();
+        let meta = trak.meta_box().unwrap();
+        assert_eq!(meta.handler_box().handler_type().to_string(), "pict");
+        assert_eq!(meta.primary_item_box().item_id(), 7);
+        assert!(trak.take_meta_box().is_some());
+        assert!(trak.meta_box().is_none());
+    }
 }
@@ -251,6 +251,33 @@ pub trait Stream {
         Ok(return_bits)
     }
 
+    fn read_flag(&mut self) -> Result<bool> {
+        Ok(self.read_bits(1)? != 0)
+    }
+
+    // unsigned Exp-Golomb code ue(v)
+    fn read_uev(&mut self) -> Result<u32> {
+        let mut leading_zero_bits = 0;
+        while self.read_bits(1)? == 0 {
+            leading_zero_bits += 1;
+            if leading_zero_bits > 31 {
+                return Err(HeifError::Unknown("invalid Exp-Golomb code"));
+            }
+        }
+        let suffix = self.read_bits(leading_zero_bits)? as u64;
+        Ok(((1u64 << leading_zero_bits) - 1 + suffix) as u32)
+    }
+
+    // signed Exp-Golomb code se(v)
+    fn read_sev(&mut self) -> Result<i32> {
+        let code_num = i64::from(self.read_uev()?);
+        if code_num % 2 == 1 {
+            Ok(((code_num + 1) / 2) as i32)
+        } else {
+            Ok((-(code_num / 2)) as i32)
+        }
+    }
+
     fn read_zero_term_string(&mut self) -> String {
         let mut string = String::new();
         while !self.is_eof() {
@@ -418,7 +445,10 @@ mod tests {
 
         #[test]
         fn test_from_str() {
-            assert_eq!("abcd".parse().unwrap(), Byte4(0x61, 0x62, 0x63, 0x64));
+            assert_eq!(
+                "abcd".parse::<Byte4>().unwrap(),
+                Byte4(0x61, 0x62, 0x63, 0x64)
+            );
             assert!(Byte4::from_str("a").is_err());
             assert!(Byte4::from_str("abcde").is_err());
         }
@@ -537,6 +567,27 @@ mod tests {
             assert_eq!(stream.byte_offset, 2);
         }
 
+        #[test]
+        fn test_read_uev() {
+            // 1 010 011 00100 0001000
+            let mut stream = BitStream::new(vec![0xA6, 0x41, 0x00]);
+            assert_eq!(stream.read_uev().unwrap(), 0);
+            assert_eq!(stream.read_uev().unwrap(), 1);
+            assert_eq!(stream.read_uev().unwrap(), 2);
+            assert_eq!(stream.read_uev().unwrap(), 3);
+            assert_eq!(stream.read_uev().unwrap(), 7);
+        }
+
+        #[test]
+        fn test_read_sev() {
+            // 010 011 00100 00101
+            let mut stream = BitStream::new(vec![0x4C, 0x85]);
+            assert_eq!(stream.read_sev().unwrap(), 1);
+            assert_eq!(stream.read_sev().unwrap(), -1);
+            assert_eq!(stream.read_sev().unwrap(), 2);
+            assert_eq!(stream.read_sev().unwrap(), -2);
+        }
+
         #[test]
         fn test_read_zero_term_string() {
             let mut stream = BitStream::new(vec![
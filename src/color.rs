@@ -0,0 +1,311 @@
+use crate::bbox::meta::iprp::cmpd::{
+    COMPONENT_TYPE_ALPHA, COMPONENT_TYPE_BLUE, COMPONENT_TYPE_CB, COMPONENT_TYPE_CR,
+    COMPONENT_TYPE_GREEN, COMPONENT_TYPE_MONOCHROME, COMPONENT_TYPE_RED, COMPONENT_TYPE_Y,
+};
+use crate::data::{
+    ChromaUpsampling, ColorInformation, PixelBuffer, PixelComponent, PixelLayout, RgbConversion,
+};
+use crate::decode::{join_planes, split_planes, Plane};
+use crate::{HeifError, Result};
+
+// Matrix coefficients of ITU-T H.273.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Matrix {
+    Identity,
+    YCgCo,
+    YCbCr { kr: f64, kb: f64 },
+}
+
+impl Matrix {
+    fn from_coefficients(matrix_coefficients: u16) -> Result<Self> {
+        Ok(match matrix_coefficients {
+            0 => Matrix::Identity,
+            1 => Matrix::YCbCr {
+                kr: 0.2126,
+                kb: 0.0722,
+            },
+            // Unspecified is treated as BT.601.
+            2 | 5 | 6 => Matrix::YCbCr {
+                kr: 0.299,
+                kb: 0.114,
+            },
+            4 => Matrix::YCbCr { kr: 0.30, kb: 0.11 },
+            7 => Matrix::YCbCr {
+                kr: 0.212,
+                kb: 0.087,
+            },
+            8 => Matrix::YCgCo,
+            9 => Matrix::YCbCr {
+                kr: 0.2627,
+                kb: 0.0593,
+            },
+            _ => return Err(HeifError::Unknown("unsupported matrix_coefficients")),
+        })
+    }
+
+    // Takes normalized luma and chroma, with chroma centred on zero.
+    fn to_rgb(self, y: f64, cb: f64, cr: f64) -> (f64, f64, f64) {
+        match self {
+            Matrix::Identity => (cr, y, cb),
+            Matrix::YCgCo => {
+                let t = y - cb;
+                (t + cr, y + cb, t - cr)
+            }
+            Matrix::YCbCr { kr, kb } => {
+                let r = y + 2.0 * (1.0 - kr) * cr;
+                let b = y + 2.0 * (1.0 - kb) * cb;
+                let g = (y - kr * r - kb * b) / (1.0 - kr - kb);
+                (r, g, b)
+            }
+        }
+    }
+}
+
+fn max_value(bit_depth: u8) -> f64 {
+    f64::from((1u32 << bit_depth) - 1)
+}
+
+fn normalize_luma(value: f64, bit_depth: u8, full_range: bool) -> f64 {
+    if full_range {
+        value / max_value(bit_depth)
+    } else {
+        let scale = 2f64.powi(i32::from(bit_depth) - 8);
+        (value - 16.0 * scale) / (219.0 * scale)
+    }
+}
+
+fn normalize_chroma(value: f64, bit_depth: u8, full_range: bool) -> f64 {
+    let scale = 2f64.powi(i32::from(bit_depth) - 8);
+    if full_range {
+        (value - 128.0 * scale) / max_value(bit_depth)
+    } else {
+        (value - 128.0 * scale) / (224.0 * scale)
+    }
+}
+
+fn quantize(value: f64, bit_depth: u8) -> u16 {
+    (value.clamp(0.0, 1.0) * max_value(bit_depth)).round() as u16
+}
+
+// The value of plane at image position (x, y), interpolating subsampled
+// planes when asked to. Chroma samples are taken to lie at the centre of
+// the luma samples they cover.
+fn sample(plane: &Plane, x: u32, y: u32, upsampling: ChromaUpsampling) -> f64 {
+    if plane.subsampling_x == 1 && plane.subsampling_y == 1 {
+        return f64::from(plane.at(x, y));
+    }
+    if upsampling == ChromaUpsampling::NearestNeighbor {
+        return f64::from(plane.at(
+            (x / plane.subsampling_x).min(plane.width - 1),
+            (y / plane.subsampling_y).min(plane.height - 1),
+        ));
+    }
+    let position = |pos: u32, factor: u32, size: u32| {
+        let p = ((f64::from(pos) + 0.5) / f64::from(factor) - 0.5).clamp(0.0, f64::from(size - 1));
+        let p0 = p.floor() as u32;
+        (p0, (p0 + 1).min(size - 1), p - f64::from(p0))
+    };
+    let (x0, x1, wx) = position(x, plane.subsampling_x, plane.width);
+    let (y0, y1, wy) = position(y, plane.subsampling_y, plane.height);
+    let top = f64::from(plane.at(x0, y0)) * (1.0 - wx) + f64::from(plane.at(x1, y0)) * wx;
+    let bottom = f64::from(plane.at(x0, y1)) * (1.0 - wx) + f64::from(plane.at(x1, y1)) * wx;
+    top * (1.0 - wy) + bottom * wy
+}
+
+fn find_plane(planes: &[Plane], component_type: u16) -> Option<&Plane> {
+    planes.iter().find(|p| p.component_type == component_type)
+}
+
+// Converts a decoded image to RGB, with alpha kept if the image has it.
+// YCbCr input is converted with the matrix and range of the nclx colour
+// information; RGB and monochrome input is only rescaled.
+pub(crate) fn convert_to_rgb(
+    image: &PixelBuffer,
+    color: &ColorInformation,
+    conversion: &RgbConversion,
+) -> Result<PixelBuffer> {
+    if conversion.bit_depth != 8 && conversion.bit_depth != 16 {
+        return Err(HeifError::Unknown("RGB output must be 8 or 16 bits"));
+    }
+    let planes = split_planes(image)?;
+    let full_range = color.full_range_flag;
+    let upsampling = conversion.upsampling;
+    let luma = find_plane(&planes, COMPONENT_TYPE_Y)
+        .or_else(|| find_plane(&planes, COMPONENT_TYPE_MONOCHROME));
+    let cb = find_plane(&planes, COMPONENT_TYPE_CB);
+    let cr = find_plane(&planes, COMPONENT_TYPE_CR);
+    let rgb = (
+        find_plane(&planes, COMPONENT_TYPE_RED),
+        find_plane(&planes, COMPONENT_TYPE_GREEN),
+        find_plane(&planes, COMPONENT_TYPE_BLUE),
+    );
+    let alpha = find_plane(&planes, COMPONENT_TYPE_ALPHA);
+
+    let convert: Box<dyn Fn(u32, u32) -> (f64, f64, f64)> = match (rgb, luma, cb, cr) {
+        ((Some(r), Some(g), Some(b)), _, _, _) => Box::new(move |x, y| {
+            (
+                sample(r, x, y, upsampling) / max_value(r.bit_depth),
+                sample(g, x, y, upsampling) / max_value(g.bit_depth),
+                sample(b, x, y, upsampling) / max_value(b.bit_depth),
+            )
+        }),
+        (_, Some(luma), Some(cb), Some(cr)) => {
+            let matrix = Matrix::from_coefficients(color.matrix_coefficients)?;
+            Box::new(move |x, y| {
+                let y_value = normalize_luma(f64::from(luma.at(x, y)), luma.bit_depth, full_range);
+                let cb_value = sample(cb, x, y, upsampling);
+                let cr_value = sample(cr, x, y, upsampling);
+                if matrix == Matrix::Identity {
+                    matrix.to_rgb(
+                        y_value,
+                        normalize_luma(cb_value, cb.bit_depth, full_range),
+                        normalize_luma(cr_value, cr.bit_depth, full_range),
+                    )
+                } else {
+                    matrix.to_rgb(
+                        y_value,
+                        normalize_chroma(cb_value, cb.bit_depth, full_range),
+                        normalize_chroma(cr_value, cr.bit_depth, full_range),
+                    )
+                }
+            })
+        }
+        (_, Some(luma), None, None) => Box::new(move |x, y| {
+            let value = normalize_luma(f64::from(luma.at(x, y)), luma.bit_depth, full_range);
+            (value, value, value)
+        }),
+        _ => return Err(HeifError::Unknown("image has no RGB or YCbCr components")),
+    };
+
+    let width = image.width;
+    let height = image.height;
+    let bit_depth = conversion.bit_depth;
+    let mut component_types = vec![
+        COMPONENT_TYPE_RED,
+        COMPONENT_TYPE_GREEN,
+        COMPONENT_TYPE_BLUE,
+    ];
+    if alpha.is_some() {
+        component_types.push(COMPONENT_TYPE_ALPHA);
+    }
+    let mut out: Vec<Plane> = component_types
+        .iter()
+        .map(|component_type| Plane {
+            component_type: *component_type,
+            bit_depth,
+            subsampling_x: 1,
+            subsampling_y: 1,
+            width,
+            height,
+            data: Vec::with_capacity(width as usize * height as usize),
+        })
+        .collect();
+    for y in 0..height {
+        for x in 0..width {
+            let (r, g, b) = convert(x, y);
+            out[0].data.push(quantize(r, bit_depth));
+            out[1].data.push(quantize(g, bit_depth));
+            out[2].data.push(quantize(b, bit_depth));
+            if let Some(alpha) = alpha {
+                let value = sample(alpha, x, y, upsampling) / max_value(alpha.bit_depth);
+                out[3].data.push(quantize(value, bit_depth));
+            }
+        }
+    }
+
+    if conversion.layout == PixelLayout::Planar {
+        return Ok(join_planes(width, height, out));
+    }
+    let mut data = Vec::with_capacity(width as usize * height as usize * out.len());
+    for i in 0..width as usize * height as usize {
+        for plane in &out {
+            data.push(plane.data[i]);
+        }
+    }
+    Ok(PixelBuffer {
+        width,
+        height,
+        layout: PixelLayout::Interleaved,
+        components: out
+            .iter()
+            .map(|p| PixelComponent {
+                component_type: p.component_type,
+                bit_depth,
+                width,
+                height,
+            })
+            .collect(),
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ycbcr420(y: Vec<u16>, cb: u16, cr: u16) -> PixelBuffer {
+        let mut data = y;
+        data.push(cb);
+        data.push(cr);
+        let component = |component_type, width, height| PixelComponent {
+            component_type,
+            bit_depth: 8,
+            width,
+            height,
+        };
+        PixelBuffer {
+            width: 2,
+            height: 2,
+            layout: PixelLayout::Planar,
+            components: vec![
+                component(COMPONENT_TYPE_Y, 2, 2),
+                component(COMPONENT_TYPE_CB, 1, 1),
+                component(COMPONENT_TYPE_CR, 1, 1),
+            ],
+            data,
+        }
+    }
+
+    fn nclx(matrix_coefficients: u16, full_range_flag: bool) -> ColorInformation {
+        ColorInformation {
+            matrix_coefficients,
+            full_range_flag,
+            ..ColorInformation::default()
+        }
+    }
+
+    #[test]
+    fn test_bt601_limited_range() {
+        let image = ycbcr420(vec![16, 235, 126, 126], 128, 128);
+        let rgb = convert_to_rgb(&image, &nclx(6, false), &RgbConversion::default()).unwrap();
+        assert_eq!(rgb.layout, PixelLayout::Interleaved);
+        assert_eq!(
+            rgb.data,
+            vec![0, 0, 0, 255, 255, 255, 128, 128, 128, 128, 128, 128]
+        );
+        // Pure red in BT.601 full range.
+        let image = ycbcr420(vec![76; 4], 85, 255);
+        let rgb = convert_to_rgb(&image, &nclx(6, true), &RgbConversion::default()).unwrap();
+        assert!(rgb.data[0] >= 254 && rgb.data[1] <= 1 && rgb.data[2] <= 1);
+    }
+
+    #[test]
+    fn test_matrix_selection_and_bit_depth() {
+        // Pure red in BT.709 limited range.
+        let image = ycbcr420(vec![63; 4], 102, 240);
+        let conversion = RgbConversion {
+            bit_depth: 16,
+            layout: PixelLayout::Planar,
+            ..RgbConversion::default()
+        };
+        let rgb = convert_to_rgb(&image, &nclx(1, false), &conversion).unwrap();
+        assert_eq!(rgb.components.len(), 3);
+        assert!(rgb.data[0] > 65000);
+        assert!(rgb.data[4] < 500 && rgb.data[8] < 500);
+        // Identity puts G in Y, B in Cb and R in Cr.
+        let image = ycbcr420(vec![10; 4], 20, 30);
+        let rgb = convert_to_rgb(&image, &nclx(0, true), &RgbConversion::default()).unwrap();
+        assert_eq!(&rgb.data[..3], &[30, 10, 20]);
+        assert!(convert_to_rgb(&image, &nclx(14, true), &RgbConversion::default()).is_err());
+    }
+}
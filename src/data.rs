@@ -1,9 +1,12 @@
+use std::collections::HashSet;
+
 use crate::bit::Byte4;
-use crate::internal::Groupings;
+use crate::internal::{Groupings, IdVec};
+use crate::{HeifError, Result};
 
 use crate::bbox::meta::iprp::{DecoderParameterType, PropertyType};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TrackSampleType {
     OutRef,
     OutNonRef,
@@ -17,35 +20,69 @@ pub enum ItemPropertyType {
     INVALID,
 
     RAW,
+    AEBR,
+    AFBR,
+    AMVE,
     AUXC,
     AVCC,
+    CCLV,
     CLAP,
+    CLLI,
+    CMPD,
     COLR,
+    FOBR,
     HVCC,
+    HVTC,
     IMIR,
     IROT,
+    ISCL,
     ISPE,
     JPGC,
+    LHVC,
+    LINF,
+    MDCV,
+    OINF,
     PASP,
     PIXI,
     RLOC,
+    TOLS,
+    UNCC,
+    VVCC,
+    WBBR,
 }
 
 impl ItemPropertyType {
     pub fn from(prop_type: PropertyType) -> Self {
         match prop_type {
+            PropertyType::AEBR => ItemPropertyType::AEBR,
+            PropertyType::AFBR => ItemPropertyType::AFBR,
+            PropertyType::AMVE => ItemPropertyType::AMVE,
             PropertyType::AUXC => ItemPropertyType::AUXC,
             PropertyType::AVCC => ItemPropertyType::AVCC,
+            PropertyType::CCLV => ItemPropertyType::CCLV,
             PropertyType::CLAP => ItemPropertyType::CLAP,
+            PropertyType::CLLI => ItemPropertyType::CLLI,
+            PropertyType::CMPD => ItemPropertyType::CMPD,
             PropertyType::COLR => ItemPropertyType::COLR,
+            PropertyType::FOBR => ItemPropertyType::FOBR,
             PropertyType::HVCC => ItemPropertyType::HVCC,
+            PropertyType::HVTC => ItemPropertyType::HVTC,
             PropertyType::IMIR => ItemPropertyType::IMIR,
             PropertyType::IROT => ItemPropertyType::IROT,
+            PropertyType::ISCL => ItemPropertyType::ISCL,
             PropertyType::ISPE => ItemPropertyType::ISPE,
             PropertyType::JPGC => ItemPropertyType::JPGC,
+            PropertyType::LHVC => ItemPropertyType::LHVC,
+            PropertyType::LINF => ItemPropertyType::LINF,
+            PropertyType::MDCV => ItemPropertyType::MDCV,
+            PropertyType::OINF => ItemPropertyType::OINF,
             PropertyType::PASP => ItemPropertyType::PASP,
             PropertyType::PIXI => ItemPropertyType::PIXI,
             PropertyType::RLOC => ItemPropertyType::RLOC,
+            PropertyType::TOLS => ItemPropertyType::TOLS,
+            PropertyType::UNCC => ItemPropertyType::UNCC,
+            PropertyType::VVCC => ItemPropertyType::VVCC,
+            PropertyType::WBBR => ItemPropertyType::WBBR,
             _ => ItemPropertyType::RAW,
         }
     }
@@ -133,6 +170,99 @@ pub struct EntityGrouping {
     pub entity_ids: Vec<u32>,
 }
 
+// Order of the images of a pano group. Grid panoramas give their size in
+// images; the continuous grid order reverses direction on every row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PanoramaDirection {
+    LeftToRight,
+    RightToLeft,
+    BottomToTop,
+    TopToBottom,
+    GridRasterScan { rows: u16, columns: u16 },
+    GridContinuous { rows: u16, columns: u16 },
+    Reserved(u8),
+}
+
+// Timing of a track of an eqiv group against the items of the group, in
+// units of the track timescale times timescale_multiplier / 2^14.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EquivalenceTiming {
+    pub time_offset: i16,
+    pub timescale_multiplier: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntityGroupType {
+    Alternatives,
+    StereoPair,
+    Burst,
+    Panorama(PanoramaDirection),
+    Slideshow,
+    Equivalence(Vec<EquivalenceTiming>),
+    Favourites,
+    WhiteBalanceBracketing,
+    FocusBracketing,
+    FlashExposureBracketing,
+    AutoExposureBracketing,
+    TimeSynchronized,
+    Other(Byte4),
+}
+
+// A typed view of an entity group. Entities keep the order of the group,
+// which is the preference order of altr and the capture order otherwise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityGroup {
+    pub group_id: u32,
+    pub group_type: EntityGroupType,
+    pub entity_ids: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StereoPair {
+    pub group_id: u32,
+    pub left: u32,
+    pub right: u32,
+}
+
+// The bracketing property of one image of a bracketing group.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BracketValue {
+    AutoExposure {
+        exposure_step: i8,
+        exposure_numerator: i8,
+    },
+    WhiteBalance {
+        blue_amber: u16,
+        green_magenta: i8,
+    },
+    Focus {
+        focus_distance_numerator: u16,
+        focus_distance_denominator: u16,
+    },
+    FlashExposure {
+        flash_exposure_numerator: i8,
+        flash_exposure_denominator: i8,
+    },
+}
+
+// What a caller is able to display: the coded and derived item types it
+// can reconstruct, and the properties it applies when they are essential.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Capabilities {
+    pub item_types: HashSet<String>,
+    pub properties: HashSet<String>,
+}
+
+impl Capabilities {
+    pub fn supports_item_type(&self, item_type: &str) -> bool {
+        self.item_types.contains(item_type)
+    }
+
+    pub fn supports_property(&self, property_type: &str) -> bool {
+        self.properties.contains(property_type)
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct MetaBoxInformation {
     pub features: FeatureBitMask,
@@ -164,39 +294,39 @@ pub enum TrackFeatureEnum {
     DisplayAllSamples = 1 << 19,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Byte4ToIds {
     pub box_type: Byte4,
     pub track_ids: Vec<u32>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SampleAndEntryIDs {
     pub sample_id: u32,
     pub sample_group_description_index: u32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SampleGrouping {
     pub grouping_type: Byte4,
     pub type_param: u32,
     pub samples: Vec<SampleAndEntryIDs>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SampleVisualEquivalence {
     pub sample_group_description_index: u32,
-    pub time_offset: u16,
+    pub time_offset: i16,
     pub timescale_multiplier: u16,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SampleToMetadataItem {
     pub sample_group_description_index: u32,
     pub metadata_item_ids: Vec<u32>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct DirectReferenceSamples {
     pub sample_group_description_index: u32,
     pub sample_id: u32,
@@ -239,12 +369,14 @@ pub struct TrackInformation {
     pub max_sample_size: u64,
     pub time_scale: u32,
     pub edit_list: EditList,
+    pub meta_box_information: Option<MetaBoxInformation>,
 }
 
 #[derive(Debug, Default)]
 pub struct FileInformation {
     pub features: FeatureBitMask,
     pub root_meta_box_information: MetaBoxInformation,
+    pub moov_meta_box_information: Option<MetaBoxInformation>,
     pub track_information: Vec<TrackInformation>,
     pub movie_timescale: u32,
 }
@@ -264,6 +396,10 @@ pub enum DecoderSpecInfoType {
     HevcVPS = 32,
     HevcSPS = 33,
     HevcPPS = 34,
+    VvcVPS = 14,
+    VvcSPS = 15,
+    VvcPPS = 16,
+    VvcAPS = 17,
 
     AudioSpecificConfig = 35,
 }
@@ -276,6 +412,10 @@ impl DecoderSpecInfoType {
             DecoderParameterType::HevcVPS => DecoderSpecInfoType::HevcVPS,
             DecoderParameterType::HevcSPS => DecoderSpecInfoType::HevcSPS,
             DecoderParameterType::HevcPPS => DecoderSpecInfoType::HevcPPS,
+            DecoderParameterType::VvcVPS => DecoderSpecInfoType::VvcVPS,
+            DecoderParameterType::VvcSPS => DecoderSpecInfoType::VvcSPS,
+            DecoderParameterType::VvcPPS => DecoderSpecInfoType::VvcPPS,
+            DecoderParameterType::VvcAPS => DecoderSpecInfoType::VvcAPS,
             DecoderParameterType::AudioSpecificConfig => DecoderSpecInfoType::AudioSpecificConfig,
         }
     }
@@ -294,23 +434,68 @@ pub struct ItemDescription {
     pub content_encoding: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Mirror {
     pub horizontal_axis: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Rotate {
     pub angle: u32,
 }
 
-#[derive(Debug)]
+// Target size as fractions of the input width and height.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageScaling {
+    pub width_n: u16,
+    pub width_d: u16,
+    pub height_n: u16,
+    pub height_d: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct RelativeLocation {
     pub horizontal_offset: u32,
     pub vertical_offset: u32,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperatingPoint {
+    pub output_layer_set_idx: u16,
+    pub max_temporal_id: u8,
+    pub layer_ids: Vec<u8>,
+    pub output_layer_ids: Vec<u8>,
+    pub max_pic_width: u16,
+    pub max_pic_height: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PixelLayout {
+    Planar,
+    Interleaved,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PixelComponent {
+    pub component_type: u16,
+    pub bit_depth: u8,
+    pub width: u32,
+    pub height: u32,
+}
+
+// Decoded samples, one u16 per sample. Planar data holds each component
+// plane in turn at its own size; interleaved data holds all components of
+// each pixel together, with subsampled components repeated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PixelBuffer {
+    pub width: u32,
+    pub height: u32,
+    pub layout: PixelLayout,
+    pub components: Vec<PixelComponent>,
+    pub data: Vec<u16>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct PixelAspectRatio {
     pub relative_width: u32,
     pub relative_height: u32,
@@ -321,7 +506,7 @@ pub struct PixelInformation {
     pub bits_per_channel: Vec<u8>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ColorInformation {
     pub color_type: Byte4,
     pub color_primaries: u16,
@@ -331,18 +516,156 @@ pub struct ColorInformation {
     pub icc_profile: Vec<u8>,
 }
 
-#[derive(Debug)]
+// Images without an nclx colr are taken to be sRGB with BT.601 full range
+// YCbCr.
+impl Default for ColorInformation {
+    fn default() -> Self {
+        Self {
+            color_type: "nclx".parse().unwrap(),
+            color_primaries: 1,
+            transfer_characteristics: 13,
+            matrix_coefficients: 6,
+            full_range_flag: true,
+            icc_profile: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChromaUpsampling {
+    NearestNeighbor,
+    Bilinear,
+}
+
+// What to do with colour samples which were premultiplied by alpha. Keep
+// returns them as stored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlphaHandling {
+    Keep,
+    Unpremultiply,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RgbConversion {
+    pub upsampling: ChromaUpsampling,
+    pub bit_depth: u8,
+    pub layout: PixelLayout,
+    pub alpha: AlphaHandling,
+}
+
+impl Default for RgbConversion {
+    fn default() -> Self {
+        Self {
+            upsampling: ChromaUpsampling::Bilinear,
+            bit_depth: 8,
+            layout: PixelLayout::Interleaved,
+            alpha: AlphaHandling::Keep,
+        }
+    }
+}
+
+// A CIE 1931 xy chromaticity.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Chromaticity {
+    pub x: f64,
+    pub y: f64,
+}
+
+// Light levels in cd/m².
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContentLightLevel {
+    pub max_content_light_level: u16,
+    pub max_pic_average_light_level: u16,
+}
+
+// Primaries are in red, green, blue order and luminance is in cd/m².
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MasteringDisplayColorVolume {
+    pub display_primaries: [Chromaticity; 3],
+    pub white_point: Chromaticity,
+    pub max_luminance: f64,
+    pub min_luminance: f64,
+}
+
+// Primaries are in red, green, blue order and luminance is in cd/m².
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContentColorVolume {
+    pub primaries: Option<[Chromaticity; 3]>,
+    pub min_luminance: Option<f64>,
+    pub max_luminance: Option<f64>,
+    pub avg_luminance: Option<f64>,
+}
+
+// ambient_illuminance is in lux.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmbientViewingEnvironment {
+    pub ambient_illuminance: f64,
+    pub ambient_light: Chromaticity,
+}
+
+// Values of one channel of ISO 21496-1 gain map metadata. gain_map_min
+// and gain_map_max are log2 values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GainMapChannel {
+    pub gain_map_min: f64,
+    pub gain_map_max: f64,
+    pub gamma: f64,
+    pub base_offset: f64,
+    pub alternate_offset: f64,
+}
+
+// channels holds one entry, or three for red, green and blue. Headrooms are
+// log2 values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GainMapMetadata {
+    pub version: u8,
+    pub minimum_version: u16,
+    pub writer_version: u16,
+    pub use_base_color_space: bool,
+    pub base_hdr_headroom: f64,
+    pub alternate_hdr_headroom: f64,
+    pub channels: Vec<GainMapChannel>,
+}
+
+// A gain map is signalled either by a tmap derived item, which carries the
+// metadata, or by an Apple auxiliary image, whose metadata is kept elsewhere.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GainMap {
+    pub base_item_id: u32,
+    pub gain_map_item_id: u32,
+    pub tone_map_item_id: Option<u32>,
+    pub metadata: Option<GainMapMetadata>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct CleanAperture {
     pub width_n: u32,
     pub width_d: u32,
     pub height_n: u32,
     pub height_d: u32,
-    pub horizontal_offset_n: u32,
+    pub horizontal_offset_n: i32,
     pub horizontal_offset_d: u32,
-    pub vertical_offset_n: u32,
+    pub vertical_offset_n: i32,
     pub vertical_offset_d: u32,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageTransform {
+    CleanAperture(CleanAperture),
+    Mirror(Mirror),
+    Rotate(Rotate),
+    Scale(ImageScaling),
+}
+
+// The coded item an identity derivation chain ends at, and the transforms
+// to apply to its decoded image in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedImage {
+    pub source_item_id: u32,
+    pub derivation_chain: IdVec,
+    pub transforms: Vec<ImageTransform>,
+}
+
 #[derive(Debug)]
 pub struct AuxiliaryType {
     pub aux_type: String,
@@ -378,7 +701,96 @@ pub struct Grid {
     pub image_ids: Vec<u32>,
 }
 
-#[derive(Debug)]
+impl Grid {
+    // Places the tiles in row-major order. Tiles in the last column and row
+    // are cropped so the canvas matches output_width and output_height.
+    pub fn layout(&self, tile_width: u32, tile_height: u32) -> Result<GridLayout> {
+        if self.image_ids.len() as u64 != self.rows as u64 * self.columns as u64 {
+            return Err(HeifError::Unknown(
+                "grid tile count does not match rows and columns",
+            ));
+        }
+        if self.output_width == 0 || self.output_height == 0 || tile_width == 0 || tile_height == 0
+        {
+            return Err(HeifError::Unknown("grid has an empty canvas or tile"));
+        }
+        let covered_width = self.columns as u64 * tile_width as u64;
+        let covered_height = self.rows as u64 * tile_height as u64;
+        if covered_width < self.output_width as u64 || covered_height < self.output_height as u64 {
+            return Err(HeifError::Unknown(
+                "grid tiles do not cover the output size",
+            ));
+        }
+        if covered_width - tile_width as u64 >= self.output_width as u64
+            || covered_height - tile_height as u64 >= self.output_height as u64
+        {
+            return Err(HeifError::Unknown(
+                "grid has a tile column or row outside the output size",
+            ));
+        }
+        let mut tiles = Vec::with_capacity(self.image_ids.len());
+        for (index, item_id) in self.image_ids.iter().enumerate() {
+            let row = index as u32 / self.columns;
+            let column = index as u32 % self.columns;
+            let x = column * tile_width;
+            let y = row * tile_height;
+            let width = tile_width.min(self.output_width - x);
+            let height = tile_height.min(self.output_height - y);
+            tiles.push(GridTile {
+                item_id: *item_id,
+                row,
+                column,
+                destination: Rectangle {
+                    x,
+                    y,
+                    width,
+                    height,
+                },
+                crop_right: tile_width - width,
+                crop_bottom: tile_height - height,
+            });
+        }
+        Ok(GridLayout {
+            output_width: self.output_width,
+            output_height: self.output_height,
+            tile_width,
+            tile_height,
+            tiles,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rectangle {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// destination is the area of the canvas the tile is copied to. The top left
+// width x height pixels of the tile are used, crop_right and crop_bottom are
+// the pixels dropped past the canvas edge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridTile {
+    pub item_id: u32,
+    pub row: u32,
+    pub column: u32,
+    pub destination: Rectangle,
+    pub crop_right: u32,
+    pub crop_bottom: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridLayout {
+    pub output_width: u32,
+    pub output_height: u32,
+    pub tile_width: u32,
+    pub tile_height: u32,
+    pub tiles: Vec<GridTile>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
 pub struct CodingConstraints {
     pub all_ref_pics_intra: bool,
     pub intra_pred_used: bool,
@@ -392,20 +804,54 @@ pub enum EditType {
     Shift,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct EditUnit {
     pub edit_type: EditType,
     pub media_time_in_track_ts: i64,
     pub duration_in_movie_ts: u64,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct EditList {
     pub looping: bool,
     pub repetitions: f64,
     pub edit_units: Vec<EditUnit>,
 }
 
+// A sample of a timed metadata track. content_type is the namespace of an
+// XML metx entry or the MIME type of a mett entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataSample {
+    pub track_id: u32,
+    pub sample_id: u32,
+    pub sample_entry_type: Byte4,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+// The sample entry of an audio track. sample_rate is in Hz and
+// audio_specific_config comes from the esds of mp4a entries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioFormat {
+    pub sample_entry_type: Byte4,
+    pub channel_count: u16,
+    pub sample_size: u16,
+    pub sample_rate: u32,
+    pub object_type_indication: u8,
+    pub audio_specific_config: Vec<u8>,
+    pub time_scale: u32,
+}
+
+// An audio sample with its times in the media timescale of its track, after
+// the edit list is applied. Priming samples have negative times.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioSample {
+    pub sample_id: u32,
+    pub presentation_time_ts: i64,
+    pub duration_ts: u64,
+    pub data: Vec<u8>,
+}
+
 #[derive(Debug, Default)]
 pub struct ImageGrid {
     pub rows_minus_one: u8,
@@ -413,3 +859,46 @@ pub struct ImageGrid {
     pub output_width: u32,
     pub output_height: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(output_width: u32, output_height: u32, columns: u32, rows: u32) -> Grid {
+        Grid {
+            output_width,
+            output_height,
+            columns,
+            rows,
+            image_ids: (1..=columns * rows).collect(),
+        }
+    }
+
+    #[test]
+    fn test_grid_layout_crops_edge_tiles() {
+        let layout = grid(1000, 700, 2, 2).layout(512, 512).unwrap();
+        assert_eq!(layout.tiles.len(), 4);
+        let last = &layout.tiles[3];
+        assert_eq!((last.item_id, last.row, last.column), (4, 1, 1));
+        assert_eq!(
+            last.destination,
+            Rectangle {
+                x: 512,
+                y: 512,
+                width: 488,
+                height: 188
+            }
+        );
+        assert_eq!((last.crop_right, last.crop_bottom), (24, 324));
+        assert_eq!(layout.tiles[0].crop_right, 0);
+    }
+
+    #[test]
+    fn test_grid_layout_errors() {
+        let mut g = grid(1000, 700, 2, 2);
+        g.image_ids.pop();
+        assert!(g.layout(512, 512).is_err());
+        assert!(grid(1100, 700, 2, 2).layout(512, 512).is_err());
+        assert!(grid(500, 700, 2, 2).layout(512, 512).is_err());
+    }
+}
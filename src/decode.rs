@@ -0,0 +1,747 @@
+use std::collections::HashMap;
+
+use crate::bbox::meta::iprp::cmpd::{
+    COMPONENT_TYPE_ALPHA, COMPONENT_TYPE_BLUE, COMPONENT_TYPE_CB, COMPONENT_TYPE_CR,
+    COMPONENT_TYPE_GREEN, COMPONENT_TYPE_RED,
+};
+use crate::data::{
+    CleanAperture, GridLayout, ImageScaling, ImageTransform, Overlay, PixelBuffer, PixelComponent,
+    PixelLayout,
+};
+use crate::{HeifError, Result};
+
+// Decodes the coded images of one codec. decoder_config holds the parameter
+// sets of the item's decoder configuration in decoding order, and data the
+// coded image with byte stream start codes. The result must be planar.
+pub trait ImageDecoder {
+    fn decode(
+        &self,
+        code_type: &str,
+        decoder_config: &[Vec<u8>],
+        data: &[u8],
+    ) -> Result<PixelBuffer>;
+}
+
+#[derive(Default)]
+pub struct DecoderRegistry {
+    decoders: HashMap<String, Box<dyn ImageDecoder>>,
+}
+
+impl std::fmt::Debug for DecoderRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut code_types: Vec<&String> = self.decoders.keys().collect();
+        code_types.sort();
+        write!(f, "DecoderRegistry {:?}", code_types)
+    }
+}
+
+impl DecoderRegistry {
+    pub fn register(&mut self, code_type: &str, decoder: Box<dyn ImageDecoder>) {
+        self.decoders.insert(code_type.to_string(), decoder);
+    }
+
+    pub fn get(&self, code_type: &str) -> Option<&dyn ImageDecoder> {
+        self.decoders.get(code_type).map(|d| d.as_ref())
+    }
+
+    pub fn code_types(&self) -> Vec<String> {
+        let mut code_types: Vec<String> = self.decoders.keys().cloned().collect();
+        code_types.sort();
+        code_types
+    }
+}
+
+// One component plane. subsampling_x and subsampling_y relate its size to
+// the size of the image it belongs to.
+#[derive(Debug, Clone)]
+pub(crate) struct Plane {
+    pub component_type: u16,
+    pub bit_depth: u8,
+    pub subsampling_x: u32,
+    pub subsampling_y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u16>,
+}
+
+impl Plane {
+    fn new(template: &Plane, image_width: u32, image_height: u32, fill: u16) -> Self {
+        let width = image_width.div_ceil(template.subsampling_x);
+        let height = image_height.div_ceil(template.subsampling_y);
+        Self {
+            width,
+            height,
+            data: vec![fill; width as usize * height as usize],
+            ..template.clone()
+        }
+    }
+
+    pub fn at(&self, x: u32, y: u32) -> u16 {
+        self.data[y as usize * self.width as usize + x as usize]
+    }
+
+    fn has_format_of(&self, other: &Plane) -> bool {
+        self.component_type == other.component_type
+            && self.bit_depth == other.bit_depth
+            && self.subsampling_x == other.subsampling_x
+            && self.subsampling_y == other.subsampling_y
+    }
+
+    // Copies src with its top left corner at (x, y), dropping whatever falls
+    // outside of this plane.
+    fn blit(&mut self, src: &Plane, x: i64, y: i64, width: u32, height: u32) {
+        for src_y in 0..height.min(src.height) {
+            let dst_y = y + i64::from(src_y);
+            if dst_y < 0 || dst_y >= i64::from(self.height) {
+                continue;
+            }
+            for src_x in 0..width.min(src.width) {
+                let dst_x = x + i64::from(src_x);
+                if dst_x < 0 || dst_x >= i64::from(self.width) {
+                    continue;
+                }
+                self.data[dst_y as usize * self.width as usize + dst_x as usize] =
+                    src.at(src_x, src_y);
+            }
+        }
+    }
+
+    fn map<F: Fn(u32, u32) -> (u32, u32)>(&self, width: u32, height: u32, source: F) -> Self {
+        let mut data = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let (src_x, src_y) = source(x, y);
+                data.push(self.at(src_x, src_y));
+            }
+        }
+        Self {
+            width,
+            height,
+            data,
+            ..self.clone()
+        }
+    }
+}
+
+// Interleaved buffers give one full size plane per component.
+pub(crate) fn split_planes(image: &PixelBuffer) -> Result<Vec<Plane>> {
+    if image.layout == PixelLayout::Interleaved {
+        let count = image.components.len();
+        let size = image.width as usize * image.height as usize;
+        if image.data.len() < size * count {
+            return Err(HeifError::Unknown(
+                "pixel buffer is smaller than its components",
+            ));
+        }
+        return Ok(image
+            .components
+            .iter()
+            .enumerate()
+            .map(|(i, component)| Plane {
+                component_type: component.component_type,
+                bit_depth: component.bit_depth,
+                subsampling_x: 1,
+                subsampling_y: 1,
+                width: image.width,
+                height: image.height,
+                data: image.data[..size * count]
+                    .iter()
+                    .skip(i)
+                    .step_by(count)
+                    .copied()
+                    .collect(),
+            })
+            .collect());
+    }
+    let mut planes = Vec::new();
+    let mut offset = 0;
+    for component in &image.components {
+        if component.width == 0 || component.height == 0 {
+            return Err(HeifError::Unknown("pixel buffer has an empty component"));
+        }
+        let size = component.width as usize * component.height as usize;
+        if offset + size > image.data.len() {
+            return Err(HeifError::Unknown(
+                "pixel buffer is smaller than its components",
+            ));
+        }
+        planes.push(Plane {
+            component_type: component.component_type,
+            bit_depth: component.bit_depth,
+            subsampling_x: image.width.div_ceil(component.width),
+            subsampling_y: image.height.div_ceil(component.height),
+            width: component.width,
+            height: component.height,
+            data: image.data[offset..offset + size].to_vec(),
+        });
+        offset += size;
+    }
+    Ok(planes)
+}
+
+pub(crate) fn join_planes(width: u32, height: u32, planes: Vec<Plane>) -> PixelBuffer {
+    let mut components = Vec::new();
+    let mut data = Vec::new();
+    for mut plane in planes {
+        components.push(PixelComponent {
+            component_type: plane.component_type,
+            bit_depth: plane.bit_depth,
+            width: plane.width,
+            height: plane.height,
+        });
+        data.append(&mut plane.data);
+    }
+    PixelBuffer {
+        width,
+        height,
+        layout: PixelLayout::Planar,
+        components,
+        data,
+    }
+}
+
+fn check_same_format(planes: &[Plane], reference: &[Plane]) -> Result<()> {
+    if planes.len() != reference.len()
+        || planes
+            .iter()
+            .zip(reference.iter())
+            .any(|(a, b)| !a.has_format_of(b))
+    {
+        return Err(HeifError::Unknown(
+            "input images have different component formats",
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn assemble_grid(layout: &GridLayout, tiles: &[PixelBuffer]) -> Result<PixelBuffer> {
+    if tiles.len() != layout.tiles.len() || tiles.is_empty() {
+        return Err(HeifError::Unknown(
+            "grid tile count does not match its layout",
+        ));
+    }
+    let reference = split_planes(&tiles[0])?;
+    let mut canvas: Vec<Plane> = reference
+        .iter()
+        .map(|p| Plane::new(p, layout.output_width, layout.output_height, 0))
+        .collect();
+    for (placement, tile) in layout.tiles.iter().zip(tiles.iter()) {
+        if tile.width != layout.tile_width || tile.height != layout.tile_height {
+            return Err(HeifError::Unknown(
+                "decoded grid tile size does not match its ispe",
+            ));
+        }
+        let tile_planes = split_planes(tile)?;
+        check_same_format(&tile_planes, &reference)?;
+        let rect = &placement.destination;
+        for (dst, src) in canvas.iter_mut().zip(tile_planes.iter()) {
+            dst.blit(
+                src,
+                i64::from(rect.x / src.subsampling_x),
+                i64::from(rect.y / src.subsampling_y),
+                rect.width.div_ceil(src.subsampling_x),
+                rect.height.div_ceil(src.subsampling_y),
+            );
+        }
+    }
+    Ok(join_planes(
+        layout.output_width,
+        layout.output_height,
+        canvas,
+    ))
+}
+
+fn scale_fill_value(value: f64, bit_depth: u8) -> u16 {
+    let max = f64::from((1u32 << bit_depth) - 1);
+    (value.clamp(0.0, 1.0) * max).round() as u16
+}
+
+// The canvas fill value of an overlay is given as 16 bit RGBA. Components
+// of other colour spaces get the BT.601 full range equivalent.
+fn overlay_fill_value(rgba: (u16, u16, u16, u16), component_type: u16, bit_depth: u8) -> u16 {
+    let r = f64::from(rgba.0) / 65535.0;
+    let g = f64::from(rgba.1) / 65535.0;
+    let b = f64::from(rgba.2) / 65535.0;
+    let a = f64::from(rgba.3) / 65535.0;
+    let y = 0.299 * r + 0.587 * g + 0.114 * b;
+    let value = match component_type {
+        COMPONENT_TYPE_RED => r,
+        COMPONENT_TYPE_GREEN => g,
+        COMPONENT_TYPE_BLUE => b,
+        COMPONENT_TYPE_ALPHA => a,
+        COMPONENT_TYPE_CB => (b - y) / 1.772 + 0.5,
+        COMPONENT_TYPE_CR => (r - y) / 1.402 + 0.5,
+        _ => y,
+    };
+    scale_fill_value(value, bit_depth)
+}
+
+// Draws the images in order over a canvas filled with the overlay colour.
+// Later images cover earlier ones and anything outside the canvas is cut off.
+pub(crate) fn composite_overlay(overlay: &Overlay, images: &[PixelBuffer]) -> Result<PixelBuffer> {
+    if images.len() != overlay.offsets.len() || images.is_empty() {
+        return Err(HeifError::Unknown(
+            "overlay image count does not match its offsets",
+        ));
+    }
+    if overlay.output_width == 0 || overlay.output_height == 0 {
+        return Err(HeifError::Unknown("overlay has an empty canvas"));
+    }
+    let reference = split_planes(&images[0])?;
+    let mut canvas: Vec<Plane> = reference
+        .iter()
+        .map(|p| {
+            let fill = overlay_fill_value(overlay.rgba, p.component_type, p.bit_depth);
+            Plane::new(p, overlay.output_width, overlay.output_height, fill)
+        })
+        .collect();
+    for (offset, image) in overlay.offsets.iter().zip(images.iter()) {
+        let planes = split_planes(image)?;
+        check_same_format(&planes, &reference)?;
+        for (dst, src) in canvas.iter_mut().zip(planes.iter()) {
+            dst.blit(
+                src,
+                i64::from(offset.horizontal).div_euclid(i64::from(src.subsampling_x)),
+                i64::from(offset.vertical).div_euclid(i64::from(src.subsampling_y)),
+                src.width,
+                src.height,
+            );
+        }
+    }
+    Ok(join_planes(
+        overlay.output_width,
+        overlay.output_height,
+        canvas,
+    ))
+}
+
+// The integer crop rectangle of a clean aperture as (x, y, width, height).
+fn clean_aperture_rect(
+    clap: &CleanAperture,
+    width: u32,
+    height: u32,
+) -> Result<(u32, u32, u32, u32)> {
+    if width == 0 || height == 0 {
+        return Err(HeifError::Unknown("clean aperture of an empty image"));
+    }
+    if clap.width_d == 0
+        || clap.height_d == 0
+        || clap.horizontal_offset_d == 0
+        || clap.vertical_offset_d == 0
+    {
+        return Err(HeifError::Unknown("clean aperture has a zero denominator"));
+    }
+    let clap_width = (f64::from(clap.width_n) / f64::from(clap.width_d)).round();
+    let clap_height = (f64::from(clap.height_n) / f64::from(clap.height_d)).round();
+    let center_x = f64::from(clap.horizontal_offset_n) / f64::from(clap.horizontal_offset_d)
+        + f64::from(width - 1) / 2.0;
+    let center_y = f64::from(clap.vertical_offset_n) / f64::from(clap.vertical_offset_d)
+        + f64::from(height - 1) / 2.0;
+    let left = (center_x - (clap_width - 1.0) / 2.0).round();
+    let top = (center_y - (clap_height - 1.0) / 2.0).round();
+    if clap_width < 1.0
+        || clap_height < 1.0
+        || left < 0.0
+        || top < 0.0
+        || left + clap_width > f64::from(width)
+        || top + clap_height > f64::from(height)
+    {
+        return Err(HeifError::Unknown("clean aperture lies outside the image"));
+    }
+    Ok((
+        left as u32,
+        top as u32,
+        clap_width as u32,
+        clap_height as u32,
+    ))
+}
+
+fn scaled_size(scaling: &ImageScaling, width: u32, height: u32) -> Result<(u32, u32)> {
+    if scaling.width_d == 0 || scaling.height_d == 0 {
+        return Err(HeifError::Unknown("image scaling has a zero denominator"));
+    }
+    let scaled = |size: u32, n: u16, d: u16| {
+        ((u64::from(size) * u64::from(n) + u64::from(d) / 2) / u64::from(d)) as u32
+    };
+    let width = scaled(width, scaling.width_n, scaling.width_d);
+    let height = scaled(height, scaling.height_n, scaling.height_d);
+    if width == 0 || height == 0 {
+        return Err(HeifError::Unknown("image scaling gives an empty image"));
+    }
+    Ok((width, height))
+}
+
+// The size of an image of width x height after the transforms are applied.
+pub(crate) fn transformed_size(
+    width: u32,
+    height: u32,
+    transforms: &[ImageTransform],
+) -> Result<(u32, u32)> {
+    let mut size = (width, height);
+    for transform in transforms {
+        size = match transform {
+            ImageTransform::CleanAperture(clap) => {
+                let (_, _, width, height) = clean_aperture_rect(clap, size.0, size.1)?;
+                (width, height)
+            }
+            ImageTransform::Mirror(_) => size,
+            ImageTransform::Rotate(rotate) if rotate.angle % 180 == 90 => (size.1, size.0),
+            ImageTransform::Rotate(_) => size,
+            ImageTransform::Scale(scaling) => scaled_size(scaling, size.0, size.1)?,
+        };
+    }
+    Ok(size)
+}
+
+pub(crate) fn apply_transform(
+    image: &PixelBuffer,
+    transform: &ImageTransform,
+) -> Result<PixelBuffer> {
+    let planes = split_planes(image)?;
+    let (width, height, planes) = match transform {
+        // Nearest neighbour resampling.
+        ImageTransform::Scale(scaling) => {
+            let (width, height) = scaled_size(scaling, image.width, image.height)?;
+            let planes = planes
+                .iter()
+                .map(|p| {
+                    let w = width.div_ceil(p.subsampling_x);
+                    let h = height.div_ceil(p.subsampling_y);
+                    p.map(w, h, |x, y| {
+                        (
+                            (u64::from(x) * u64::from(p.width) / u64::from(w)) as u32,
+                            (u64::from(y) * u64::from(p.height) / u64::from(h)) as u32,
+                        )
+                    })
+                })
+                .collect();
+            (width, height, planes)
+        }
+        ImageTransform::CleanAperture(clap) => {
+            let (left, top, width, height) = clean_aperture_rect(clap, image.width, image.height)?;
+            let planes = planes
+                .iter()
+                .map(|p| {
+                    let x0 = left / p.subsampling_x;
+                    let y0 = top / p.subsampling_y;
+                    let w = width.div_ceil(p.subsampling_x).min(p.width - x0);
+                    let h = height.div_ceil(p.subsampling_y).min(p.height - y0);
+                    p.map(w, h, |x, y| (x0 + x, y0 + y))
+                })
+                .collect();
+            (width, height, planes)
+        }
+        ImageTransform::Mirror(mirror) => {
+            let planes = planes
+                .iter()
+                .map(|p| {
+                    if mirror.horizontal_axis {
+                        p.map(p.width, p.height, |x, y| (x, p.height - 1 - y))
+                    } else {
+                        p.map(p.width, p.height, |x, y| (p.width - 1 - x, y))
+                    }
+                })
+                .collect();
+            (image.width, image.height, planes)
+        }
+        // Rotation is anti-clockwise.
+        ImageTransform::Rotate(rotate) => match rotate.angle % 360 {
+            0 => (image.width, image.height, planes),
+            90 => (
+                image.height,
+                image.width,
+                planes
+                    .iter()
+                    .map(|p| Plane {
+                        subsampling_x: p.subsampling_y,
+                        subsampling_y: p.subsampling_x,
+                        ..p.map(p.height, p.width, |x, y| (p.width - 1 - y, x))
+                    })
+                    .collect(),
+            ),
+            180 => (
+                image.width,
+                image.height,
+                planes
+                    .iter()
+                    .map(|p| {
+                        p.map(p.width, p.height, |x, y| {
+                            (p.width - 1 - x, p.height - 1 - y)
+                        })
+                    })
+                    .collect(),
+            ),
+            270 => (
+                image.height,
+                image.width,
+                planes
+                    .iter()
+                    .map(|p| Plane {
+                        subsampling_x: p.subsampling_y,
+                        subsampling_y: p.subsampling_x,
+                        ..p.map(p.height, p.width, |x, y| (y, p.height - 1 - x))
+                    })
+                    .collect(),
+            ),
+            _ => {
+                return Err(HeifError::Unknown(
+                    "rotation is not a multiple of 90 degrees",
+                ))
+            }
+        },
+    };
+    Ok(join_planes(width, height, planes))
+}
+
+// Appends the first component of alpha as the alpha plane of image. An image
+// which already has an alpha component is left as it is.
+pub(crate) fn attach_alpha(image: PixelBuffer, alpha: &PixelBuffer) -> Result<PixelBuffer> {
+    if image
+        .components
+        .iter()
+        .any(|c| c.component_type == COMPONENT_TYPE_ALPHA)
+    {
+        return Ok(image);
+    }
+    let alpha_plane = match split_planes(alpha)?.into_iter().next() {
+        Some(plane) => plane,
+        None => return Err(HeifError::Unknown("alpha image has no components")),
+    };
+    if alpha_plane.width != image.width || alpha_plane.height != image.height {
+        return Err(HeifError::Unknown(
+            "alpha image size does not match the image",
+        ));
+    }
+    let mut planes = split_planes(&image)?;
+    planes.push(Plane {
+        component_type: COMPONENT_TYPE_ALPHA,
+        subsampling_x: 1,
+        subsampling_y: 1,
+        ..alpha_plane
+    });
+    Ok(join_planes(image.width, image.height, planes))
+}
+
+// Divides the colour components of a premultiplied image by its alpha.
+// Chroma is scaled around its midpoint, and limited range luma around its
+// black level. Pixels with zero alpha are left as they are.
+pub(crate) fn unpremultiply_alpha(image: &PixelBuffer, full_range: bool) -> Result<PixelBuffer> {
+    let mut planes = split_planes(image)?;
+    let alpha = match planes
+        .iter()
+        .position(|p| p.component_type == COMPONENT_TYPE_ALPHA)
+    {
+        Some(index) => planes.remove(index),
+        None => return Ok(image.clone()),
+    };
+    let alpha_max = f64::from((1u32 << alpha.bit_depth) - 1);
+    for plane in planes.iter_mut() {
+        let max = f64::from((1u32 << plane.bit_depth) - 1);
+        let base = if plane.component_type == COMPONENT_TYPE_CB
+            || plane.component_type == COMPONENT_TYPE_CR
+        {
+            f64::from(1u32 << (plane.bit_depth - 1))
+        } else if !full_range && plane.bit_depth >= 8 {
+            f64::from(16u32 << (plane.bit_depth - 8))
+        } else {
+            0.0
+        };
+        for y in 0..plane.height {
+            for x in 0..plane.width {
+                let a = alpha.at(
+                    (x * plane.subsampling_x).min(alpha.width - 1),
+                    (y * plane.subsampling_y).min(alpha.height - 1),
+                );
+                if a == 0 {
+                    continue;
+                }
+                let index = y as usize * plane.width as usize + x as usize;
+                let value = f64::from(plane.data[index]);
+                let unpremultiplied = base + (value - base) * alpha_max / f64::from(a);
+                plane.data[index] = unpremultiplied.round().clamp(0.0, max) as u16;
+            }
+        }
+    }
+    planes.push(alpha);
+    Ok(join_planes(image.width, image.height, planes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bbox::meta::iprp::cmpd::COMPONENT_TYPE_MONOCHROME;
+    use crate::data::{Grid, Mirror, Offset, Rotate};
+
+    fn mono(width: u32, height: u32, data: Vec<u16>) -> PixelBuffer {
+        PixelBuffer {
+            width,
+            height,
+            layout: PixelLayout::Planar,
+            components: vec![PixelComponent {
+                component_type: COMPONENT_TYPE_MONOCHROME,
+                bit_depth: 8,
+                width,
+                height,
+            }],
+            data,
+        }
+    }
+
+    struct MockDecoder;
+
+    impl ImageDecoder for MockDecoder {
+        fn decode(&self, _: &str, decoder_config: &[Vec<u8>], data: &[u8]) -> Result<PixelBuffer> {
+            let value = u16::from(data[0]) + decoder_config.len() as u16;
+            Ok(mono(2, 2, vec![value; 4]))
+        }
+    }
+
+    #[test]
+    fn test_assemble_grid_from_mock_decoder() {
+        let mut registry = DecoderRegistry::default();
+        registry.register("hvc1", Box::new(MockDecoder));
+        assert!(registry.get("av01").is_none());
+        let decoder = registry.get("hvc1").unwrap();
+        let tiles: Vec<PixelBuffer> = (0..4u8)
+            .map(|i| decoder.decode("hvc1", &[vec![0]], &[i]).unwrap())
+            .collect();
+        let grid = Grid {
+            output_width: 3,
+            output_height: 3,
+            columns: 2,
+            rows: 2,
+            image_ids: vec![1, 2, 3, 4],
+        };
+        let image = assemble_grid(&grid.layout(2, 2).unwrap(), &tiles).unwrap();
+        assert_eq!((image.width, image.height), (3, 3));
+        assert_eq!(image.data, vec![1, 1, 2, 1, 1, 2, 3, 3, 4]);
+    }
+
+    #[test]
+    fn test_composite_overlay_clips_and_fills() {
+        let overlay = Overlay {
+            rgba: (65535, 65535, 65535, 65535),
+            output_width: 3,
+            output_height: 2,
+            offsets: vec![
+                Offset {
+                    horizontal: -1,
+                    vertical: 0,
+                },
+                Offset {
+                    horizontal: 2,
+                    vertical: 1,
+                },
+            ],
+            image_ids: vec![1, 2],
+        };
+        let images = vec![mono(2, 1, vec![1, 2]), mono(2, 2, vec![3, 4, 5, 6])];
+        let image = composite_overlay(&overlay, &images).unwrap();
+        assert_eq!(image.data, vec![2, 255, 255, 255, 255, 3]);
+    }
+
+    #[test]
+    fn test_transforms() {
+        let image = mono(3, 2, vec![1, 2, 3, 4, 5, 6]);
+        let rotated =
+            apply_transform(&image, &ImageTransform::Rotate(Rotate { angle: 90 })).unwrap();
+        assert_eq!((rotated.width, rotated.height), (2, 3));
+        assert_eq!(rotated.data, vec![3, 6, 2, 5, 1, 4]);
+        let rotated =
+            apply_transform(&image, &ImageTransform::Rotate(Rotate { angle: 270 })).unwrap();
+        assert_eq!(rotated.data, vec![4, 1, 5, 2, 6, 3]);
+        let mirrored = apply_transform(
+            &image,
+            &ImageTransform::Mirror(Mirror {
+                horizontal_axis: true,
+            }),
+        )
+        .unwrap();
+        assert_eq!(mirrored.data, vec![4, 5, 6, 1, 2, 3]);
+        let clap = CleanAperture {
+            width_n: 1,
+            width_d: 1,
+            height_n: 2,
+            height_d: 1,
+            horizontal_offset_n: 1,
+            horizontal_offset_d: 1,
+            vertical_offset_n: 0,
+            vertical_offset_d: 1,
+        };
+        let scaling = ImageScaling {
+            width_n: 2,
+            width_d: 3,
+            height_n: 1,
+            height_d: 1,
+        };
+        let scaled = apply_transform(&image, &ImageTransform::Scale(scaling)).unwrap();
+        assert_eq!((scaled.width, scaled.height), (2, 2));
+        assert_eq!(scaled.data, vec![1, 2, 4, 5]);
+        let transforms = [
+            ImageTransform::CleanAperture(clap),
+            ImageTransform::Rotate(Rotate { angle: 90 }),
+        ];
+        assert_eq!(transformed_size(3, 2, &transforms).unwrap(), (2, 1));
+        let cropped = apply_transform(&image, &ImageTransform::CleanAperture(clap)).unwrap();
+        assert_eq!((cropped.width, cropped.height), (1, 2));
+        assert_eq!(cropped.data, vec![3, 6]);
+    }
+
+    #[test]
+    fn test_attach_alpha() {
+        let image = attach_alpha(mono(2, 1, vec![1, 2]), &mono(2, 1, vec![9, 8])).unwrap();
+        assert_eq!(image.components.len(), 2);
+        assert_eq!(image.components[1].component_type, COMPONENT_TYPE_ALPHA);
+        assert_eq!(image.data, vec![1, 2, 9, 8]);
+        assert!(attach_alpha(mono(2, 1, vec![1, 2]), &mono(1, 1, vec![9])).is_err());
+    }
+
+    #[test]
+    fn test_unpremultiply_alpha() {
+        let image = attach_alpha(
+            mono(3, 1, vec![100, 60, 255]),
+            &mono(3, 1, vec![255, 0, 51]),
+        )
+        .unwrap();
+        let image = unpremultiply_alpha(&image, true).unwrap();
+        assert_eq!(image.data, vec![100, 60, 255, 255, 0, 51]);
+        let image = attach_alpha(mono(1, 1, vec![40]), &mono(1, 1, vec![51])).unwrap();
+        assert_eq!(
+            unpremultiply_alpha(&image, true).unwrap().data,
+            vec![200, 51]
+        );
+    }
+
+    #[test]
+    fn test_unpremultiply_alpha_limited_range() {
+        // Black premultiplied by any alpha stays at the black level 16, and
+        // 16 + 20 at 20% alpha is 16 + 100.
+        let image = attach_alpha(mono(2, 1, vec![16, 36]), &mono(2, 1, vec![51, 51])).unwrap();
+        assert_eq!(
+            unpremultiply_alpha(&image, false).unwrap().data,
+            vec![16, 116, 51, 51]
+        );
+        assert_eq!(
+            unpremultiply_alpha(&image, true).unwrap().data,
+            vec![80, 180, 51, 51]
+        );
+
+        let image = attach_alpha(mono(1, 1, vec![64 + 80]), &mono(1, 1, vec![511])).unwrap();
+        let image = PixelBuffer {
+            components: image
+                .components
+                .iter()
+                .map(|c| PixelComponent {
+                    bit_depth: 10,
+                    ..c.clone()
+                })
+                .collect(),
+            ..image
+        };
+        // 10-bit black level is 64, alpha 511 of 1023 is about one half.
+        assert_eq!(
+            unpremultiply_alpha(&image, false).unwrap().data,
+            vec![64 + 160, 511]
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::bbox::meta::MetaBox;
+use crate::bit::Byte4;
+use crate::internal::{IdVec, ItemFeaturesMap};
+use crate::{HeifError, Result};
+
+pub(crate) const MAX_DERIVATION_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceType {
+    Dimg,
+    Thmb,
+    Auxl,
+    Cdsc,
+    Base,
+    Tbas,
+    Prem,
+    Exbl,
+}
+
+const REFERENCE_TYPES: [ReferenceType; 8] = [
+    ReferenceType::Dimg,
+    ReferenceType::Thmb,
+    ReferenceType::Auxl,
+    ReferenceType::Cdsc,
+    ReferenceType::Base,
+    ReferenceType::Tbas,
+    ReferenceType::Prem,
+    ReferenceType::Exbl,
+];
+
+impl ReferenceType {
+    pub fn fourcc(self) -> &'static str {
+        match self {
+            ReferenceType::Dimg => "dimg",
+            ReferenceType::Thmb => "thmb",
+            ReferenceType::Auxl => "auxl",
+            ReferenceType::Cdsc => "cdsc",
+            ReferenceType::Base => "base",
+            ReferenceType::Tbas => "tbas",
+            ReferenceType::Prem => "prem",
+            ReferenceType::Exbl => "exbl",
+        }
+    }
+
+    // Reference types whose target is needed to reconstruct the source.
+    fn is_decode_dependency(self) -> bool {
+        self == ReferenceType::Dimg || self == ReferenceType::Exbl
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphNode {
+    pub item_id: u32,
+    pub item_type: Byte4,
+    pub feature_mask: u32,
+}
+
+// One reference from an iref box. index is the position of to_item_id in
+// the reference, which orders the inputs of a derived image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphEdge {
+    pub reference_type: ReferenceType,
+    pub from_item_id: u32,
+    pub to_item_id: u32,
+    pub index: usize,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct DerivationGraph {
+    nodes: BTreeMap<u32, GraphNode>,
+    edges: Vec<GraphEdge>,
+}
+
+impl DerivationGraph {
+    pub fn from_meta_box(metabox: &MetaBox) -> Self {
+        let mut graph = Self::default();
+        for item in metabox.item_info_box().item_info_list() {
+            graph.nodes.insert(
+                item.item_id(),
+                GraphNode {
+                    item_id: item.item_id(),
+                    item_type: item.item_type().clone(),
+                    feature_mask: 0,
+                },
+            );
+        }
+        let iref = metabox.item_reference_box();
+        for reference_type in REFERENCE_TYPES.iter() {
+            for reference in iref.references_of_type(reference_type.fourcc().parse().unwrap()) {
+                for (index, to_item_id) in reference.to_item_ids().iter().enumerate() {
+                    graph.edges.push(GraphEdge {
+                        reference_type: *reference_type,
+                        from_item_id: reference.get_from_item_id(),
+                        to_item_id: *to_item_id,
+                        index,
+                    });
+                }
+            }
+        }
+        graph
+    }
+
+    pub(crate) fn set_feature_masks(&mut self, item_features_map: &ItemFeaturesMap) {
+        for (item_id, features) in item_features_map {
+            if let Some(node) = self.nodes.get_mut(item_id) {
+                node.feature_mask = features.feature_mask();
+            }
+        }
+    }
+
+    pub fn node(&self, item_id: u32) -> Option<&GraphNode> {
+        self.nodes.get(&item_id)
+    }
+
+    pub fn nodes(&self) -> Vec<&GraphNode> {
+        self.nodes.values().collect()
+    }
+
+    pub fn edges(&self) -> &Vec<GraphEdge> {
+        &self.edges
+    }
+
+    // Targets of the references of the given type from item_id, in order.
+    pub fn references_from(&self, item_id: u32, reference_type: ReferenceType) -> IdVec {
+        self.edges
+            .iter()
+            .filter(|e| e.reference_type == reference_type && e.from_item_id == item_id)
+            .map(|e| e.to_item_id)
+            .collect()
+    }
+
+    // Items which reference item_id with the given type.
+    pub fn references_to(&self, item_id: u32, reference_type: ReferenceType) -> IdVec {
+        self.edges
+            .iter()
+            .filter(|e| e.reference_type == reference_type && e.to_item_id == item_id)
+            .map(|e| e.from_item_id)
+            .collect()
+    }
+
+    pub fn has_references_from(&self, item_id: u32, reference_type: ReferenceType) -> bool {
+        self.edges
+            .iter()
+            .any(|e| e.reference_type == reference_type && e.from_item_id == item_id)
+    }
+
+    pub fn has_references_to(&self, item_id: u32, reference_type: ReferenceType) -> bool {
+        self.edges
+            .iter()
+            .any(|e| e.reference_type == reference_type && e.to_item_id == item_id)
+    }
+
+    // References whose source or target is not an item of the meta box.
+    pub fn dangling_references(&self) -> Vec<GraphEdge> {
+        self.edges
+            .iter()
+            .filter(|e| {
+                !self.nodes.contains_key(&e.from_item_id) || !self.nodes.contains_key(&e.to_item_id)
+            })
+            .copied()
+            .collect()
+    }
+
+    fn dependencies(&self, item_id: u32) -> impl Iterator<Item = u32> + '_ {
+        self.edges
+            .iter()
+            .filter(move |e| e.reference_type.is_decode_dependency() && e.from_item_id == item_id)
+            .map(|e| e.to_item_id)
+    }
+
+    // Returns the items of a dependency cycle over dimg and exbl references,
+    // if there is one.
+    pub fn find_cycle(&self) -> Option<IdVec> {
+        let mut finished = HashSet::new();
+        for item_id in self.nodes.keys() {
+            let mut path = IdVec::new();
+            if let Some(cycle) = self.find_cycle_from(*item_id, &mut path, &mut finished) {
+                return Some(cycle);
+            }
+        }
+        None
+    }
+
+    fn find_cycle_from(
+        &self,
+        item_id: u32,
+        path: &mut IdVec,
+        finished: &mut HashSet<u32>,
+    ) -> Option<IdVec> {
+        if let Some(pos) = path.iter().position(|id| *id == item_id) {
+            return Some(path[pos..].to_vec());
+        }
+        if finished.contains(&item_id) {
+            return None;
+        }
+        path.push(item_id);
+        for dependency in self.dependencies(item_id) {
+            if let Some(cycle) = self.find_cycle_from(dependency, path, finished) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        finished.insert(item_id);
+        None
+    }
+
+    // Every item which item_id depends on through dimg and exbl references,
+    // followed by item_id itself, each placed after all of its inputs.
+    pub fn decode_order(&self, item_id: u32) -> Result<IdVec> {
+        let mut order = IdVec::new();
+        let mut path = IdVec::new();
+        self.visit(item_id, &mut path, &mut order)?;
+        Ok(order)
+    }
+
+    fn visit(&self, item_id: u32, path: &mut IdVec, order: &mut IdVec) -> Result<()> {
+        if !self.nodes.contains_key(&item_id) {
+            return Err(HeifError::InvalidItemID);
+        }
+        if path.contains(&item_id) {
+            return Err(HeifError::Unknown("derivation graph contains a cycle"));
+        }
+        if path.len() >= MAX_DERIVATION_DEPTH {
+            return Err(HeifError::Unknown("derivation graph is too deep"));
+        }
+        if order.contains(&item_id) {
+            return Ok(());
+        }
+        path.push(item_id);
+        let dependencies: IdVec = self.dependencies(item_id).collect();
+        for dependency in dependencies {
+            self.visit(dependency, path, order)?;
+        }
+        path.pop();
+        order.push(item_id);
+        Ok(())
+    }
+
+    // The coded items needed to reconstruct item_id, in decode order.
+    // Derived items (those with dimg inputs) are left out.
+    pub fn coded_items_for(&self, item_id: u32) -> Result<IdVec> {
+        Ok(self
+            .decode_order(item_id)?
+            .into_iter()
+            .filter(|id| !self.has_references_from(*id, ReferenceType::Dimg))
+            .collect())
+    }
+
+    // All items in an order where every item follows its inputs.
+    pub fn topological_order(&self) -> Result<IdVec> {
+        let mut order = IdVec::new();
+        for item_id in self.nodes.keys() {
+            let mut path = IdVec::new();
+            self.visit(*item_id, &mut path, &mut order)?;
+        }
+        Ok(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(items: &[(u32, &str)], edges: &[(ReferenceType, u32, u32)]) -> DerivationGraph {
+        let mut graph = DerivationGraph::default();
+        for (item_id, item_type) in items {
+            graph.nodes.insert(
+                *item_id,
+                GraphNode {
+                    item_id: *item_id,
+                    item_type: item_type.parse().unwrap(),
+                    feature_mask: 0,
+                },
+            );
+        }
+        for (index, (reference_type, from_item_id, to_item_id)) in edges.iter().enumerate() {
+            graph.edges.push(GraphEdge {
+                reference_type: *reference_type,
+                from_item_id: *from_item_id,
+                to_item_id: *to_item_id,
+                index,
+            });
+        }
+        graph
+    }
+
+    #[test]
+    fn test_coded_items_in_decode_order() {
+        let g = graph(
+            &[
+                (1, "iden"),
+                (2, "grid"),
+                (3, "hvc1"),
+                (4, "hvc1"),
+                (5, "hvc1"),
+            ],
+            &[
+                (ReferenceType::Dimg, 1, 2),
+                (ReferenceType::Dimg, 2, 3),
+                (ReferenceType::Dimg, 2, 4),
+                (ReferenceType::Thmb, 5, 1),
+            ],
+        );
+        assert_eq!(g.decode_order(1).unwrap(), vec![3, 4, 2, 1]);
+        assert_eq!(g.coded_items_for(1).unwrap(), vec![3, 4]);
+        assert_eq!(g.coded_items_for(5).unwrap(), vec![5]);
+        assert_eq!(g.references_to(1, ReferenceType::Thmb), vec![5]);
+        assert!(g.find_cycle().is_none());
+        assert!(g.dangling_references().is_empty());
+    }
+
+    #[test]
+    fn test_cycle_and_dangling_reference() {
+        let g = graph(
+            &[(1, "iden"), (2, "iden"), (3, "hvc1")],
+            &[
+                (ReferenceType::Dimg, 1, 2),
+                (ReferenceType::Dimg, 2, 1),
+                (ReferenceType::Cdsc, 3, 9),
+            ],
+        );
+        assert_eq!(g.find_cycle(), Some(vec![1, 2]));
+        assert!(g.decode_order(1).is_err());
+        assert!(g.topological_order().is_err());
+        assert_eq!(g.dangling_references().len(), 1);
+        assert_eq!(g.dangling_references()[0].to_item_id, 9);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::bit::{BitStream, Stream};
+use crate::data::{GainMapChannel, GainMapMetadata, PixelBuffer};
+use crate::decode::split_planes;
+use crate::{HeifError, Result};
+
+pub(crate) const AUX_TYPE_APPLE_GAIN_MAP: &str = "urn:com:apple:photo:2020:aux:hdrgainmap";
+
+fn read_fraction(stream: &mut BitStream, signed: bool) -> Result<f64> {
+    let n = stream.read_4bytes()?.to_u32();
+    let d = stream.read_4bytes()?.to_u32();
+    if d == 0 {
+        return Err(HeifError::Unknown("tmap fraction has a zero denominator"));
+    }
+    let n = if signed {
+        f64::from(n as i32)
+    } else {
+        f64::from(n)
+    };
+    Ok(n / f64::from(d))
+}
+
+// The ISO 21496-1 gain map metadata carried as the data of a tmap item.
+pub(crate) fn parse_tone_map(data: Vec<u8>) -> Result<GainMapMetadata> {
+    let mut stream = BitStream::new(data);
+    let version = stream.read_byte()?;
+    if version != 0 {
+        return Err(HeifError::Unknown("unsupported tmap version"));
+    }
+    let minimum_version = stream.read_2bytes()?.to_u16();
+    if minimum_version != 0 {
+        return Err(HeifError::Unknown("unsupported tmap minimum_version"));
+    }
+    let writer_version = stream.read_2bytes()?.to_u16();
+    let is_multichannel = stream.read_flag()?;
+    let use_base_color_space = stream.read_flag()?;
+    stream.read_bits(6)?;
+    let base_hdr_headroom = read_fraction(&mut stream, false)?;
+    let alternate_hdr_headroom = read_fraction(&mut stream, false)?;
+    let channel_count = if is_multichannel { 3 } else { 1 };
+    let mut channels = Vec::with_capacity(channel_count);
+    for _ in 0..channel_count {
+        channels.push(GainMapChannel {
+            gain_map_min: read_fraction(&mut stream, true)?,
+            gain_map_max: read_fraction(&mut stream, true)?,
+            gamma: read_fraction(&mut stream, false)?,
+            base_offset: read_fraction(&mut stream, true)?,
+            alternate_offset: read_fraction(&mut stream, true)?,
+        });
+    }
+    Ok(GainMapMetadata {
+        version,
+        minimum_version,
+        writer_version,
+        use_base_color_space,
+        base_hdr_headroom,
+        alternate_hdr_headroom,
+        channels,
+    })
+}
+
+// Applies a decoded gain map to linear RGB base pixels, three values per
+// pixel, for a display with the given headroom (log2 of the peak over SDR
+// white). The gain map is scaled to the base size by nearest neighbour.
+pub fn apply_gain_map(
+    metadata: &GainMapMetadata,
+    base: &[f32],
+    width: u32,
+    height: u32,
+    gain_map: &PixelBuffer,
+    display_hdr_headroom: f64,
+) -> Result<Vec<f32>> {
+    let pixel_count = width as usize * height as usize;
+    if base.len() != pixel_count * 3 {
+        return Err(HeifError::Unknown(
+            "base pixels do not match the image size",
+        ));
+    }
+    if metadata.channels.is_empty() {
+        return Err(HeifError::Unknown("gain map metadata has no channels"));
+    }
+    let planes = split_planes(gain_map)?;
+    if planes.is_empty() || (metadata.channels.len() == 3 && planes.len() < 3) {
+        return Err(HeifError::Unknown("gain map image has too few components"));
+    }
+    let headroom_range = metadata.alternate_hdr_headroom - metadata.base_hdr_headroom;
+    let weight = if headroom_range == 0.0 {
+        0.0
+    } else {
+        ((display_hdr_headroom - metadata.base_hdr_headroom) / headroom_range).clamp(0.0, 1.0)
+    };
+    let mut out = Vec::with_capacity(base.len());
+    for y in 0..height {
+        for x in 0..width {
+            for c in 0..3 {
+                let index = if metadata.channels.len() == 3 { c } else { 0 };
+                let channel = &metadata.channels[index];
+                let plane = &planes[index];
+                let gx = (u64::from(x) * u64::from(gain_map.width) / u64::from(width)) as u32;
+                let gy = (u64::from(y) * u64::from(gain_map.height) / u64::from(height)) as u32;
+                let value = f64::from(plane.at(
+                    (gx / plane.subsampling_x).min(plane.width - 1),
+                    (gy / plane.subsampling_y).min(plane.height - 1),
+                )) / f64::from((1u32 << plane.bit_depth) - 1);
+                let recovery = if channel.gamma == 1.0 {
+                    value
+                } else {
+                    value.powf(1.0 / channel.gamma)
+                };
+                let log_boost =
+                    channel.gain_map_min * (1.0 - recovery) + channel.gain_map_max * recovery;
+                let base_value =
+                    f64::from(base[(y as usize * width as usize + x as usize) * 3 + c]);
+                let result = (base_value + channel.base_offset) * (log_boost * weight).exp2()
+                    - channel.alternate_offset;
+                out.push(result as f32);
+            }
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bbox::meta::iprp::cmpd::COMPONENT_TYPE_MONOCHROME;
+    use crate::data::{PixelComponent, PixelLayout};
+
+    fn tmap_payload() -> Vec<u8> {
+        let mut data = vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x40];
+        let fractions: [(u32, u32); 7] = [
+            (0, 1),             // base_hdr_headroom
+            (2, 1),             // alternate_hdr_headroom
+            (0, 1),             // gain_map_min
+            (2, 1),             // gain_map_max
+            (1, 1),             // gamma
+            (1, 64),            // base_offset
+            (-1i32 as u32, 64), // alternate_offset
+        ];
+        for (n, d) in fractions.iter() {
+            data.extend_from_slice(&n.to_be_bytes());
+            data.extend_from_slice(&d.to_be_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_tone_map() {
+        let metadata = parse_tone_map(tmap_payload()).unwrap();
+        assert!(metadata.use_base_color_space);
+        assert_eq!(metadata.channels.len(), 1);
+        assert_eq!(metadata.alternate_hdr_headroom, 2.0);
+        assert_eq!(metadata.channels[0].gain_map_max, 2.0);
+        assert_eq!(metadata.channels[0].alternate_offset, -1.0 / 64.0);
+        assert!(parse_tone_map(vec![1]).is_err());
+    }
+
+    #[test]
+    fn test_apply_gain_map() {
+        let mut metadata = parse_tone_map(tmap_payload()).unwrap();
+        metadata.channels[0].base_offset = 0.0;
+        metadata.channels[0].alternate_offset = 0.0;
+        let gain_map = PixelBuffer {
+            width: 1,
+            height: 1,
+            layout: PixelLayout::Planar,
+            components: vec![PixelComponent {
+                component_type: COMPONENT_TYPE_MONOCHROME,
+                bit_depth: 8,
+                width: 1,
+                height: 1,
+            }],
+            data: vec![255],
+        };
+        let base = [0.25f32; 6];
+        let full = apply_gain_map(&metadata, &base, 2, 1, &gain_map, 2.0).unwrap();
+        assert_eq!(full, vec![1.0; 6]);
+        let half = apply_gain_map(&metadata, &base, 2, 1, &gain_map, 1.0).unwrap();
+        assert_eq!(half, vec![0.5; 6]);
+        let sdr = apply_gain_map(&metadata, &base, 2, 1, &gain_map, 0.0).unwrap();
+        assert_eq!(sdr, vec![0.25; 6]);
+    }
+}
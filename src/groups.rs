@@ -0,0 +1,109 @@
+use crate::bbox::meta::grpl::EntityToGroupBox;
+use crate::bit::{BitStream, Byte4, Stream};
+use crate::data::{EntityGroup, EntityGroupType, EquivalenceTiming, PanoramaDirection};
+use crate::Result;
+
+fn parse_panorama_direction(payload: &[u8]) -> Result<PanoramaDirection> {
+    let mut stream = BitStream::new(payload.to_vec());
+    let direction = stream.read_byte()?;
+    Ok(match direction {
+        0 => PanoramaDirection::LeftToRight,
+        1 => PanoramaDirection::RightToLeft,
+        2 => PanoramaDirection::BottomToTop,
+        3 => PanoramaDirection::TopToBottom,
+        4 | 5 => {
+            let rows = u16::from(stream.read_byte()?) + 1;
+            let columns = u16::from(stream.read_byte()?) + 1;
+            if direction == 4 {
+                PanoramaDirection::GridRasterScan { rows, columns }
+            } else {
+                PanoramaDirection::GridContinuous { rows, columns }
+            }
+        }
+        _ => PanoramaDirection::Reserved(direction),
+    })
+}
+
+// One timing per track of the group, in the order of the entity ids.
+fn parse_equivalence_timings(payload: &[u8]) -> Result<Vec<EquivalenceTiming>> {
+    let mut stream = BitStream::new(payload.to_vec());
+    let mut timings = Vec::new();
+    while stream.has_bytes(4) {
+        timings.push(EquivalenceTiming {
+            time_offset: stream.read_2bytes()?.to_u16() as i16,
+            timescale_multiplier: stream.read_2bytes()?.to_u16(),
+        });
+    }
+    Ok(timings)
+}
+
+pub(crate) fn entity_group_type(group_type: &Byte4, payload: &[u8]) -> Result<EntityGroupType> {
+    Ok(match group_type.to_string().as_str() {
+        "altr" => EntityGroupType::Alternatives,
+        "ster" => EntityGroupType::StereoPair,
+        "brst" => EntityGroupType::Burst,
+        "pano" => EntityGroupType::Panorama(parse_panorama_direction(payload)?),
+        "slid" => EntityGroupType::Slideshow,
+        "eqiv" => EntityGroupType::Equivalence(parse_equivalence_timings(payload)?),
+        "favc" => EntityGroupType::Favourites,
+        "wbbr" => EntityGroupType::WhiteBalanceBracketing,
+        "fobr" => EntityGroupType::FocusBracketing,
+        "afbr" => EntityGroupType::FlashExposureBracketing,
+        "aebr" => EntityGroupType::AutoExposureBracketing,
+        "tsyn" => EntityGroupType::TimeSynchronized,
+        _ => EntityGroupType::Other(group_type.clone()),
+    })
+}
+
+pub(crate) fn entity_group(group_box: &EntityToGroupBox) -> Result<EntityGroup> {
+    Ok(EntityGroup {
+        group_id: group_box.group_id(),
+        group_type: entity_group_type(group_box.full_box_header().box_type(), group_box.payload())?,
+        entity_ids: group_box.entity_ids().to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group_type(fourcc: &str, payload: &[u8]) -> Result<EntityGroupType> {
+        entity_group_type(&fourcc.parse().unwrap(), payload)
+    }
+
+    #[test]
+    fn test_panorama_direction() {
+        assert_eq!(
+            group_type("pano", &[3]).unwrap(),
+            EntityGroupType::Panorama(PanoramaDirection::TopToBottom)
+        );
+        assert_eq!(
+            group_type("pano", &[5, 1, 3]).unwrap(),
+            EntityGroupType::Panorama(PanoramaDirection::GridContinuous {
+                rows: 2,
+                columns: 4
+            })
+        );
+        assert!(group_type("pano", &[4, 1]).is_err());
+        assert!(group_type("pano", &[]).is_err());
+    }
+
+    #[test]
+    fn test_group_types_with_payload() {
+        assert_eq!(
+            group_type("eqiv", &[0xFF, 0xFE, 0x40, 0x00]).unwrap(),
+            EntityGroupType::Equivalence(vec![EquivalenceTiming {
+                time_offset: -2,
+                timescale_multiplier: 0x4000,
+            }])
+        );
+        assert_eq!(
+            group_type("altr", &[]).unwrap(),
+            EntityGroupType::Alternatives
+        );
+        assert_eq!(
+            group_type("iaug", &[]).unwrap(),
+            EntityGroupType::Other("iaug".parse().unwrap())
+        );
+    }
+}
@@ -0,0 +1,857 @@
+use crate::bit::{BitStream, Stream};
+use crate::{HeifError, Result};
+
+const NAL_UNIT_TYPE_VPS: u8 = 32;
+const NAL_UNIT_TYPE_SPS: u8 = 33;
+const NAL_UNIT_TYPE_PPS: u8 = 34;
+
+const EXTENDED_SAR: u8 = 255;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct NalUnitHeader {
+    pub nal_unit_type: u8,
+    pub nuh_layer_id: u8,
+    pub nuh_temporal_id_plus1: u8,
+}
+
+impl NalUnitHeader {
+    fn from_stream<T: Stream>(stream: &mut T) -> Result<Self> {
+        if stream.read_flag()? {
+            return Err(HeifError::Unknown("forbidden_zero_bit is set"));
+        }
+        let nal_unit_type = stream.read_bits(6)? as u8;
+        let nuh_layer_id = stream.read_bits(6)? as u8;
+        let nuh_temporal_id_plus1 = stream.read_bits(3)? as u8;
+        Ok(Self {
+            nal_unit_type,
+            nuh_layer_id,
+            nuh_temporal_id_plus1,
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ProfileTierLevel {
+    pub general_profile_space: u8,
+    pub general_tier_flag: bool,
+    pub general_profile_idc: u8,
+    pub general_profile_compatibility_flags: u32,
+    pub general_progressive_source_flag: bool,
+    pub general_interlaced_source_flag: bool,
+    pub general_non_packed_constraint_flag: bool,
+    pub general_frame_only_constraint_flag: bool,
+    pub general_level_idc: u8,
+    pub sub_layers: Vec<SubLayerProfileTierLevel>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SubLayerProfileTierLevel {
+    pub profile_present_flag: bool,
+    pub level_present_flag: bool,
+    pub profile_space: u8,
+    pub tier_flag: bool,
+    pub profile_idc: u8,
+    pub profile_compatibility_flags: u32,
+    pub level_idc: u8,
+}
+
+impl ProfileTierLevel {
+    fn from_stream<T: Stream>(
+        stream: &mut T,
+        profile_present_flag: bool,
+        max_num_sub_layers_minus1: u8,
+    ) -> Result<Self> {
+        let mut ptl = Self::default();
+        if profile_present_flag {
+            ptl.general_profile_space = stream.read_bits(2)? as u8;
+            ptl.general_tier_flag = stream.read_flag()?;
+            ptl.general_profile_idc = stream.read_bits(5)? as u8;
+            ptl.general_profile_compatibility_flags = stream.read_bits(32)? as u32;
+            ptl.general_progressive_source_flag = stream.read_flag()?;
+            ptl.general_interlaced_source_flag = stream.read_flag()?;
+            ptl.general_non_packed_constraint_flag = stream.read_flag()?;
+            ptl.general_frame_only_constraint_flag = stream.read_flag()?;
+            // general_reserved_zero_43bits, general_inbld_flag
+            stream.read_bits(44)?;
+        }
+        ptl.general_level_idc = stream.read_bits(8)? as u8;
+
+        for _ in 0..max_num_sub_layers_minus1 {
+            ptl.sub_layers.push(SubLayerProfileTierLevel {
+                profile_present_flag: stream.read_flag()?,
+                level_present_flag: stream.read_flag()?,
+                ..SubLayerProfileTierLevel::default()
+            });
+        }
+        if max_num_sub_layers_minus1 > 0 {
+            for _ in max_num_sub_layers_minus1..8 {
+                // reserved_zero_2bits
+                stream.read_bits(2)?;
+            }
+        }
+        for sub_layer in &mut ptl.sub_layers {
+            if sub_layer.profile_present_flag {
+                sub_layer.profile_space = stream.read_bits(2)? as u8;
+                sub_layer.tier_flag = stream.read_flag()?;
+                sub_layer.profile_idc = stream.read_bits(5)? as u8;
+                sub_layer.profile_compatibility_flags = stream.read_bits(32)? as u32;
+                // source and constraint flags, reserved bits, inbld_flag
+                stream.read_bits(48)?;
+            }
+            if sub_layer.level_present_flag {
+                sub_layer.level_idc = stream.read_bits(8)? as u8;
+            }
+        }
+        Ok(ptl)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct SubLayerOrderingInfo {
+    pub max_dec_pic_buffering_minus1: u32,
+    pub max_num_reorder_pics: u32,
+    pub max_latency_increase_plus1: u32,
+}
+
+fn read_sub_layer_ordering_infos<T: Stream>(
+    stream: &mut T,
+    max_sub_layers_minus1: u8,
+) -> Result<Vec<SubLayerOrderingInfo>> {
+    let sub_layer_ordering_info_present_flag = stream.read_flag()?;
+    let first = if sub_layer_ordering_info_present_flag {
+        0
+    } else {
+        max_sub_layers_minus1
+    };
+    let mut infos = Vec::new();
+    for _ in first..=max_sub_layers_minus1 {
+        infos.push(SubLayerOrderingInfo {
+            max_dec_pic_buffering_minus1: stream.read_uev()?,
+            max_num_reorder_pics: stream.read_uev()?,
+            max_latency_increase_plus1: stream.read_uev()?,
+        });
+    }
+    Ok(infos)
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct TimingInfo {
+    pub num_units_in_tick: u32,
+    pub time_scale: u32,
+    pub poc_proportional_to_timing_flag: bool,
+    pub num_ticks_poc_diff_one_minus1: u32,
+}
+
+impl TimingInfo {
+    fn from_stream<T: Stream>(stream: &mut T) -> Result<Self> {
+        let num_units_in_tick = stream.read_bits(32)? as u32;
+        let time_scale = stream.read_bits(32)? as u32;
+        let poc_proportional_to_timing_flag = stream.read_flag()?;
+        let num_ticks_poc_diff_one_minus1 = if poc_proportional_to_timing_flag {
+            stream.read_uev()?
+        } else {
+            0
+        };
+        Ok(Self {
+            num_units_in_tick,
+            time_scale,
+            poc_proportional_to_timing_flag,
+            num_ticks_poc_diff_one_minus1,
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VideoParameterSet {
+    pub header: NalUnitHeader,
+    pub vps_video_parameter_set_id: u8,
+    pub vps_base_layer_internal_flag: bool,
+    pub vps_base_layer_available_flag: bool,
+    pub vps_max_layers_minus1: u8,
+    pub vps_max_sub_layers_minus1: u8,
+    pub vps_temporal_id_nesting_flag: bool,
+    pub profile_tier_level: ProfileTierLevel,
+    pub sub_layer_ordering_infos: Vec<SubLayerOrderingInfo>,
+    pub vps_max_layer_id: u8,
+    pub vps_num_layer_sets_minus1: u32,
+    pub layer_id_included_flags: Vec<Vec<bool>>,
+    pub timing_info: Option<TimingInfo>,
+}
+
+impl VideoParameterSet {
+    pub fn from_nal_unit(nal_unit: &[u8]) -> Result<Self> {
+        let mut stream = rbsp_stream(nal_unit);
+        let header = NalUnitHeader::from_stream(&mut stream)?;
+        if header.nal_unit_type != NAL_UNIT_TYPE_VPS {
+            return Err(HeifError::Unknown("NAL unit is not a VPS"));
+        }
+        let vps_video_parameter_set_id = stream.read_bits(4)? as u8;
+        let vps_base_layer_internal_flag = stream.read_flag()?;
+        let vps_base_layer_available_flag = stream.read_flag()?;
+        let vps_max_layers_minus1 = stream.read_bits(6)? as u8;
+        let vps_max_sub_layers_minus1 = stream.read_bits(3)? as u8;
+        let vps_temporal_id_nesting_flag = stream.read_flag()?;
+        // vps_reserved_0xffff_16bits
+        stream.read_bits(16)?;
+        let profile_tier_level =
+            ProfileTierLevel::from_stream(&mut stream, true, vps_max_sub_layers_minus1)?;
+        let sub_layer_ordering_infos =
+            read_sub_layer_ordering_infos(&mut stream, vps_max_sub_layers_minus1)?;
+        let vps_max_layer_id = stream.read_bits(6)? as u8;
+        let vps_num_layer_sets_minus1 = stream.read_uev()?;
+        let mut layer_id_included_flags = Vec::new();
+        for _ in 1..=vps_num_layer_sets_minus1 {
+            let mut flags = Vec::new();
+            for _ in 0..=vps_max_layer_id {
+                flags.push(stream.read_flag()?);
+            }
+            layer_id_included_flags.push(flags);
+        }
+        let timing_info = if stream.read_flag()? {
+            Some(TimingInfo::from_stream(&mut stream)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            header,
+            vps_video_parameter_set_id,
+            vps_base_layer_internal_flag,
+            vps_base_layer_available_flag,
+            vps_max_layers_minus1,
+            vps_max_sub_layers_minus1,
+            vps_temporal_id_nesting_flag,
+            profile_tier_level,
+            sub_layer_ordering_infos,
+            vps_max_layer_id,
+            vps_num_layer_sets_minus1,
+            layer_id_included_flags,
+            timing_info,
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ConformanceWindow {
+    pub left_offset: u32,
+    pub right_offset: u32,
+    pub top_offset: u32,
+    pub bottom_offset: u32,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ColourDescription {
+    pub colour_primaries: u8,
+    pub transfer_characteristics: u8,
+    pub matrix_coeffs: u8,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VuiParameters {
+    pub aspect_ratio_idc: u8,
+    pub sar_width: u16,
+    pub sar_height: u16,
+    pub overscan_info_present_flag: bool,
+    pub overscan_appropriate_flag: bool,
+    pub video_signal_type_present_flag: bool,
+    pub video_format: u8,
+    pub video_full_range_flag: bool,
+    pub colour_description: Option<ColourDescription>,
+    pub chroma_sample_loc_type_top_field: u32,
+    pub chroma_sample_loc_type_bottom_field: u32,
+    pub neutral_chroma_indication_flag: bool,
+    pub field_seq_flag: bool,
+    pub frame_field_info_present_flag: bool,
+    pub default_display_window: Option<ConformanceWindow>,
+    pub timing_info: Option<TimingInfo>,
+}
+
+impl VuiParameters {
+    // HRD parameters and bitstream restrictions are not parsed.
+    fn from_stream<T: Stream>(stream: &mut T) -> Result<Self> {
+        let mut vui = Self::default();
+        if stream.read_flag()? {
+            vui.aspect_ratio_idc = stream.read_bits(8)? as u8;
+            if vui.aspect_ratio_idc == EXTENDED_SAR {
+                vui.sar_width = stream.read_bits(16)? as u16;
+                vui.sar_height = stream.read_bits(16)? as u16;
+            }
+        }
+        vui.overscan_info_present_flag = stream.read_flag()?;
+        if vui.overscan_info_present_flag {
+            vui.overscan_appropriate_flag = stream.read_flag()?;
+        }
+        vui.video_signal_type_present_flag = stream.read_flag()?;
+        if vui.video_signal_type_present_flag {
+            vui.video_format = stream.read_bits(3)? as u8;
+            vui.video_full_range_flag = stream.read_flag()?;
+            if stream.read_flag()? {
+                vui.colour_description = Some(ColourDescription {
+                    colour_primaries: stream.read_bits(8)? as u8,
+                    transfer_characteristics: stream.read_bits(8)? as u8,
+                    matrix_coeffs: stream.read_bits(8)? as u8,
+                });
+            }
+        }
+        if stream.read_flag()? {
+            vui.chroma_sample_loc_type_top_field = stream.read_uev()?;
+            vui.chroma_sample_loc_type_bottom_field = stream.read_uev()?;
+        }
+        vui.neutral_chroma_indication_flag = stream.read_flag()?;
+        vui.field_seq_flag = stream.read_flag()?;
+        vui.frame_field_info_present_flag = stream.read_flag()?;
+        if stream.read_flag()? {
+            vui.default_display_window = Some(read_window(stream)?);
+        }
+        if stream.read_flag()? {
+            vui.timing_info = Some(TimingInfo::from_stream(stream)?);
+        }
+        Ok(vui)
+    }
+}
+
+fn read_window<T: Stream>(stream: &mut T) -> Result<ConformanceWindow> {
+    Ok(ConformanceWindow {
+        left_offset: stream.read_uev()?,
+        right_offset: stream.read_uev()?,
+        top_offset: stream.read_uev()?,
+        bottom_offset: stream.read_uev()?,
+    })
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShortTermRefPicSet {
+    pub delta_poc_s0: Vec<i32>,
+    pub used_by_curr_pic_s0: Vec<bool>,
+    pub delta_poc_s1: Vec<i32>,
+    pub used_by_curr_pic_s1: Vec<bool>,
+}
+
+impl ShortTermRefPicSet {
+    fn num_negative_pics(&self) -> usize {
+        self.delta_poc_s0.len()
+    }
+
+    fn num_delta_pocs(&self) -> usize {
+        self.delta_poc_s0.len() + self.delta_poc_s1.len()
+    }
+
+    fn from_stream<T: Stream>(
+        stream: &mut T,
+        st_rps_idx: usize,
+        sets: &[ShortTermRefPicSet],
+    ) -> Result<Self> {
+        let inter_ref_pic_set_prediction_flag = st_rps_idx != 0 && stream.read_flag()?;
+        let mut rps = Self::default();
+        if inter_ref_pic_set_prediction_flag {
+            // delta_idx_minus1 is only present in slice headers
+            let reference = &sets[st_rps_idx - 1];
+            let delta_rps_sign = stream.read_flag()?;
+            let abs_delta_rps_minus1 = stream.read_uev()?;
+            let delta_rps = if delta_rps_sign { -1 } else { 1 } * (abs_delta_rps_minus1 as i32 + 1);
+
+            let mut used_by_curr_pic_flag = Vec::new();
+            let mut use_delta_flag = Vec::new();
+            for _ in 0..=reference.num_delta_pocs() {
+                let used = stream.read_flag()?;
+                used_by_curr_pic_flag.push(used);
+                use_delta_flag.push(used || stream.read_flag()?);
+            }
+
+            let num_negative = reference.num_negative_pics();
+            let num_delta_pocs = reference.num_delta_pocs();
+            // (7-61)
+            for (j, delta_poc) in reference.delta_poc_s1.iter().enumerate().rev() {
+                let d_poc = delta_poc + delta_rps;
+                if d_poc < 0 && use_delta_flag[num_negative + j] {
+                    rps.delta_poc_s0.push(d_poc);
+                    rps.used_by_curr_pic_s0
+                        .push(used_by_curr_pic_flag[num_negative + j]);
+                }
+            }
+            if delta_rps < 0 && use_delta_flag[num_delta_pocs] {
+                rps.delta_poc_s0.push(delta_rps);
+                rps.used_by_curr_pic_s0
+                    .push(used_by_curr_pic_flag[num_delta_pocs]);
+            }
+            for (j, delta_poc) in reference.delta_poc_s0.iter().enumerate() {
+                let d_poc = delta_poc + delta_rps;
+                if d_poc < 0 && use_delta_flag[j] {
+                    rps.delta_poc_s0.push(d_poc);
+                    rps.used_by_curr_pic_s0.push(used_by_curr_pic_flag[j]);
+                }
+            }
+            // (7-62)
+            for (j, delta_poc) in reference.delta_poc_s0.iter().enumerate().rev() {
+                let d_poc = delta_poc + delta_rps;
+                if d_poc > 0 && use_delta_flag[j] {
+                    rps.delta_poc_s1.push(d_poc);
+                    rps.used_by_curr_pic_s1.push(used_by_curr_pic_flag[j]);
+                }
+            }
+            if delta_rps > 0 && use_delta_flag[num_delta_pocs] {
+                rps.delta_poc_s1.push(delta_rps);
+                rps.used_by_curr_pic_s1
+                    .push(used_by_curr_pic_flag[num_delta_pocs]);
+            }
+            for (j, delta_poc) in reference.delta_poc_s1.iter().enumerate() {
+                let d_poc = delta_poc + delta_rps;
+                if d_poc > 0 && use_delta_flag[num_negative + j] {
+                    rps.delta_poc_s1.push(d_poc);
+                    rps.used_by_curr_pic_s1
+                        .push(used_by_curr_pic_flag[num_negative + j]);
+                }
+            }
+        } else {
+            let num_negative_pics = stream.read_uev()?;
+            let num_positive_pics = stream.read_uev()?;
+            let mut poc = 0i32;
+            for _ in 0..num_negative_pics {
+                poc -= stream.read_uev()? as i32 + 1;
+                rps.delta_poc_s0.push(poc);
+                rps.used_by_curr_pic_s0.push(stream.read_flag()?);
+            }
+            poc = 0;
+            for _ in 0..num_positive_pics {
+                poc += stream.read_uev()? as i32 + 1;
+                rps.delta_poc_s1.push(poc);
+                rps.used_by_curr_pic_s1.push(stream.read_flag()?);
+            }
+        }
+        Ok(rps)
+    }
+}
+
+fn skip_scaling_list_data<T: Stream>(stream: &mut T) -> Result<()> {
+    for size_id in 0..4 {
+        let step = if size_id == 3 { 3 } else { 1 };
+        for _ in (0..6).step_by(step) {
+            let scaling_list_pred_mode_flag = stream.read_flag()?;
+            if !scaling_list_pred_mode_flag {
+                // scaling_list_pred_matrix_id_delta
+                stream.read_uev()?;
+            } else {
+                let coef_num = std::cmp::min(64, 1 << (4 + (size_id << 1)));
+                if size_id > 1 {
+                    // scaling_list_dc_coef_minus8
+                    stream.read_sev()?;
+                }
+                for _ in 0..coef_num {
+                    // scaling_list_delta_coef
+                    stream.read_sev()?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SequenceParameterSet {
+    pub header: NalUnitHeader,
+    pub sps_video_parameter_set_id: u8,
+    pub sps_max_sub_layers_minus1: u8,
+    pub sps_temporal_id_nesting_flag: bool,
+    pub profile_tier_level: ProfileTierLevel,
+    pub sps_seq_parameter_set_id: u32,
+    pub chroma_format_idc: u32,
+    pub separate_colour_plane_flag: bool,
+    pub pic_width_in_luma_samples: u32,
+    pub pic_height_in_luma_samples: u32,
+    pub conformance_window: Option<ConformanceWindow>,
+    pub bit_depth_luma_minus8: u32,
+    pub bit_depth_chroma_minus8: u32,
+    pub log2_max_pic_order_cnt_lsb_minus4: u32,
+    pub sub_layer_ordering_infos: Vec<SubLayerOrderingInfo>,
+    pub log2_min_luma_coding_block_size_minus3: u32,
+    pub log2_diff_max_min_luma_coding_block_size: u32,
+    pub log2_min_luma_transform_block_size_minus2: u32,
+    pub log2_diff_max_min_luma_transform_block_size: u32,
+    pub max_transform_hierarchy_depth_inter: u32,
+    pub max_transform_hierarchy_depth_intra: u32,
+    pub scaling_list_enabled_flag: bool,
+    pub amp_enabled_flag: bool,
+    pub sample_adaptive_offset_enabled_flag: bool,
+    pub pcm_enabled_flag: bool,
+    pub short_term_ref_pic_sets: Vec<ShortTermRefPicSet>,
+    pub long_term_ref_pics_present_flag: bool,
+    pub sps_temporal_mvp_enabled_flag: bool,
+    pub strong_intra_smoothing_enabled_flag: bool,
+    pub vui_parameters: Option<VuiParameters>,
+}
+
+impl SequenceParameterSet {
+    // Only the single-layer syntax (nuh_layer_id == 0) is supported.
+    pub fn from_nal_unit(nal_unit: &[u8]) -> Result<Self> {
+        let mut stream = rbsp_stream(nal_unit);
+        let header = NalUnitHeader::from_stream(&mut stream)?;
+        if header.nal_unit_type != NAL_UNIT_TYPE_SPS {
+            return Err(HeifError::Unknown("NAL unit is not a SPS"));
+        }
+        if header.nuh_layer_id != 0 {
+            return Err(HeifError::Unknown("multi-layer SPS is not supported"));
+        }
+        let mut sps = Self {
+            header,
+            ..Self::default()
+        };
+        sps.sps_video_parameter_set_id = stream.read_bits(4)? as u8;
+        sps.sps_max_sub_layers_minus1 = stream.read_bits(3)? as u8;
+        sps.sps_temporal_id_nesting_flag = stream.read_flag()?;
+        sps.profile_tier_level =
+            ProfileTierLevel::from_stream(&mut stream, true, sps.sps_max_sub_layers_minus1)?;
+        sps.sps_seq_parameter_set_id = stream.read_uev()?;
+        sps.chroma_format_idc = stream.read_uev()?;
+        if sps.chroma_format_idc > 3 {
+            return Err(HeifError::Unknown("invalid chroma_format_idc"));
+        }
+        if sps.chroma_format_idc == 3 {
+            sps.separate_colour_plane_flag = stream.read_flag()?;
+        }
+        sps.pic_width_in_luma_samples = stream.read_uev()?;
+        sps.pic_height_in_luma_samples = stream.read_uev()?;
+        if stream.read_flag()? {
+            sps.conformance_window = Some(read_window(&mut stream)?);
+        }
+        sps.bit_depth_luma_minus8 = stream.read_uev()?;
+        sps.bit_depth_chroma_minus8 = stream.read_uev()?;
+        sps.log2_max_pic_order_cnt_lsb_minus4 = stream.read_uev()?;
+        if sps.log2_max_pic_order_cnt_lsb_minus4 > 12 {
+            return Err(HeifError::Unknown(
+                "invalid log2_max_pic_order_cnt_lsb_minus4",
+            ));
+        }
+        sps.sub_layer_ordering_infos =
+            read_sub_layer_ordering_infos(&mut stream, sps.sps_max_sub_layers_minus1)?;
+        sps.log2_min_luma_coding_block_size_minus3 = stream.read_uev()?;
+        sps.log2_diff_max_min_luma_coding_block_size = stream.read_uev()?;
+        sps.log2_min_luma_transform_block_size_minus2 = stream.read_uev()?;
+        sps.log2_diff_max_min_luma_transform_block_size = stream.read_uev()?;
+        sps.max_transform_hierarchy_depth_inter = stream.read_uev()?;
+        sps.max_transform_hierarchy_depth_intra = stream.read_uev()?;
+        sps.scaling_list_enabled_flag = stream.read_flag()?;
+        if sps.scaling_list_enabled_flag {
+            let sps_scaling_list_data_present_flag = stream.read_flag()?;
+            if sps_scaling_list_data_present_flag {
+                skip_scaling_list_data(&mut stream)?;
+            }
+        }
+        sps.amp_enabled_flag = stream.read_flag()?;
+        sps.sample_adaptive_offset_enabled_flag = stream.read_flag()?;
+        sps.pcm_enabled_flag = stream.read_flag()?;
+        if sps.pcm_enabled_flag {
+            // pcm_sample_bit_depth_luma_minus1, pcm_sample_bit_depth_chroma_minus1
+            stream.read_bits(8)?;
+            // log2_min_pcm_luma_coding_block_size_minus3
+            stream.read_uev()?;
+            // log2_diff_max_min_pcm_luma_coding_block_size
+            stream.read_uev()?;
+            // pcm_loop_filter_disabled_flag
+            stream.read_flag()?;
+        }
+        let num_short_term_ref_pic_sets = stream.read_uev()?;
+        if num_short_term_ref_pic_sets > 64 {
+            return Err(HeifError::Unknown("invalid num_short_term_ref_pic_sets"));
+        }
+        for i in 0..num_short_term_ref_pic_sets as usize {
+            let rps =
+                ShortTermRefPicSet::from_stream(&mut stream, i, &sps.short_term_ref_pic_sets)?;
+            sps.short_term_ref_pic_sets.push(rps);
+        }
+        sps.long_term_ref_pics_present_flag = stream.read_flag()?;
+        if sps.long_term_ref_pics_present_flag {
+            let num_long_term_ref_pics_sps = stream.read_uev()?;
+            let lsb_bits = sps.log2_max_pic_order_cnt_lsb_minus4 as usize + 4;
+            for _ in 0..num_long_term_ref_pics_sps {
+                // lt_ref_pic_poc_lsb_sps, used_by_curr_pic_lt_sps_flag
+                stream.read_bits(lsb_bits)?;
+                stream.read_flag()?;
+            }
+        }
+        sps.sps_temporal_mvp_enabled_flag = stream.read_flag()?;
+        sps.strong_intra_smoothing_enabled_flag = stream.read_flag()?;
+        if stream.read_flag()? {
+            sps.vui_parameters = Some(VuiParameters::from_stream(&mut stream)?);
+        }
+        Ok(sps)
+    }
+
+    pub fn bit_depth_luma(&self) -> u32 {
+        self.bit_depth_luma_minus8 + 8
+    }
+
+    pub fn bit_depth_chroma(&self) -> u32 {
+        self.bit_depth_chroma_minus8 + 8
+    }
+
+    // SubWidthC and SubHeightC of Table 6-1
+    pub fn chroma_subsampling(&self) -> (u32, u32) {
+        match (self.chroma_format_idc, self.separate_colour_plane_flag) {
+            (1, _) => (2, 2),
+            (2, _) => (2, 1),
+            _ => (1, 1),
+        }
+    }
+
+    // Picture size after applying the conformance cropping window.
+    pub fn output_size(&self) -> (u32, u32) {
+        let mut width = self.pic_width_in_luma_samples;
+        let mut height = self.pic_height_in_luma_samples;
+        if let Some(window) = &self.conformance_window {
+            let (sub_width, sub_height) = self.chroma_subsampling();
+            width = width.saturating_sub(sub_width * (window.left_offset + window.right_offset));
+            height = height.saturating_sub(sub_height * (window.top_offset + window.bottom_offset));
+        }
+        (width, height)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TileInfo {
+    pub num_tile_columns_minus1: u32,
+    pub num_tile_rows_minus1: u32,
+    pub uniform_spacing_flag: bool,
+    pub column_width_minus1: Vec<u32>,
+    pub row_height_minus1: Vec<u32>,
+    pub loop_filter_across_tiles_enabled_flag: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PictureParameterSet {
+    pub header: NalUnitHeader,
+    pub pps_pic_parameter_set_id: u32,
+    pub pps_seq_parameter_set_id: u32,
+    pub dependent_slice_segments_enabled_flag: bool,
+    pub output_flag_present_flag: bool,
+    pub num_extra_slice_header_bits: u8,
+    pub sign_data_hiding_enabled_flag: bool,
+    pub cabac_init_present_flag: bool,
+    pub num_ref_idx_l0_default_active_minus1: u32,
+    pub num_ref_idx_l1_default_active_minus1: u32,
+    pub init_qp_minus26: i32,
+    pub constrained_intra_pred_flag: bool,
+    pub transform_skip_enabled_flag: bool,
+    pub cu_qp_delta_enabled_flag: bool,
+    pub diff_cu_qp_delta_depth: u32,
+    pub pps_cb_qp_offset: i32,
+    pub pps_cr_qp_offset: i32,
+    pub pps_slice_chroma_qp_offsets_present_flag: bool,
+    pub weighted_pred_flag: bool,
+    pub weighted_bipred_flag: bool,
+    pub transquant_bypass_enabled_flag: bool,
+    pub entropy_coding_sync_enabled_flag: bool,
+    pub tile_info: Option<TileInfo>,
+}
+
+impl PictureParameterSet {
+    // Parsing stops after the tiling syntax.
+    pub fn from_nal_unit(nal_unit: &[u8]) -> Result<Self> {
+        let mut stream = rbsp_stream(nal_unit);
+        let header = NalUnitHeader::from_stream(&mut stream)?;
+        if header.nal_unit_type != NAL_UNIT_TYPE_PPS {
+            return Err(HeifError::Unknown("NAL unit is not a PPS"));
+        }
+        let mut pps = Self {
+            header,
+            ..Self::default()
+        };
+        pps.pps_pic_parameter_set_id = stream.read_uev()?;
+        pps.pps_seq_parameter_set_id = stream.read_uev()?;
+        pps.dependent_slice_segments_enabled_flag = stream.read_flag()?;
+        pps.output_flag_present_flag = stream.read_flag()?;
+        pps.num_extra_slice_header_bits = stream.read_bits(3)? as u8;
+        pps.sign_data_hiding_enabled_flag = stream.read_flag()?;
+        pps.cabac_init_present_flag = stream.read_flag()?;
+        pps.num_ref_idx_l0_default_active_minus1 = stream.read_uev()?;
+        pps.num_ref_idx_l1_default_active_minus1 = stream.read_uev()?;
+        pps.init_qp_minus26 = stream.read_sev()?;
+        pps.constrained_intra_pred_flag = stream.read_flag()?;
+        pps.transform_skip_enabled_flag = stream.read_flag()?;
+        pps.cu_qp_delta_enabled_flag = stream.read_flag()?;
+        if pps.cu_qp_delta_enabled_flag {
+            pps.diff_cu_qp_delta_depth = stream.read_uev()?;
+        }
+        pps.pps_cb_qp_offset = stream.read_sev()?;
+        pps.pps_cr_qp_offset = stream.read_sev()?;
+        pps.pps_slice_chroma_qp_offsets_present_flag = stream.read_flag()?;
+        pps.weighted_pred_flag = stream.read_flag()?;
+        pps.weighted_bipred_flag = stream.read_flag()?;
+        pps.transquant_bypass_enabled_flag = stream.read_flag()?;
+        let tiles_enabled_flag = stream.read_flag()?;
+        pps.entropy_coding_sync_enabled_flag = stream.read_flag()?;
+        if tiles_enabled_flag {
+            let mut tile_info = TileInfo {
+                num_tile_columns_minus1: stream.read_uev()?,
+                num_tile_rows_minus1: stream.read_uev()?,
+                uniform_spacing_flag: stream.read_flag()?,
+                ..TileInfo::default()
+            };
+            if !tile_info.uniform_spacing_flag {
+                for _ in 0..tile_info.num_tile_columns_minus1 {
+                    tile_info.column_width_minus1.push(stream.read_uev()?);
+                }
+                for _ in 0..tile_info.num_tile_rows_minus1 {
+                    tile_info.row_height_minus1.push(stream.read_uev()?);
+                }
+            }
+            tile_info.loop_filter_across_tiles_enabled_flag = stream.read_flag()?;
+            pps.tile_info = Some(tile_info);
+        }
+        Ok(pps)
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HevcParameterSets {
+    pub vps: Option<VideoParameterSet>,
+    pub sps: SequenceParameterSet,
+    pub pps: Option<PictureParameterSet>,
+}
+
+// Strips a leading start code, if any, and the emulation prevention bytes.
+fn rbsp_stream(nal_unit: &[u8]) -> BitStream {
+    let start_code_len = if nal_unit.starts_with(&[0, 0, 0, 1]) {
+        4
+    } else if nal_unit.starts_with(&[0, 0, 1]) {
+        3
+    } else {
+        0
+    };
+    BitStream::new(remove_emulation_prevention_bytes(
+        &nal_unit[start_code_len..],
+    ))
+}
+
+pub fn remove_emulation_prevention_bytes(data: &[u8]) -> Vec<u8> {
+    let mut rbsp = Vec::with_capacity(data.len());
+    let mut zero_count = 0;
+    for &byte in data {
+        if zero_count >= 2 && byte == 3 {
+            zero_count = 0;
+            continue;
+        }
+        if byte == 0 {
+            zero_count += 1;
+        } else {
+            zero_count = 0;
+        }
+        rbsp.push(byte);
+    }
+    rbsp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // parameter sets of examples/images/images/autumn_1440x960.heic
+    const VPS: [u8; 28] = [
+        0x00, 0x00, 0x00, 0x01, 0x40, 0x01, 0x0c, 0x01, 0xff, 0xff, 0x01, 0x60, 0x00, 0x00, 0x03,
+        0x00, 0x00, 0x03, 0x00, 0x00, 0x03, 0x00, 0x00, 0x03, 0x00, 0xba, 0xf0, 0x24,
+    ];
+    const SPS: [u8; 35] = [
+        0x00, 0x00, 0x00, 0x01, 0x42, 0x01, 0x01, 0x01, 0x60, 0x00, 0x00, 0x03, 0x00, 0x00, 0x03,
+        0x00, 0x00, 0x03, 0x00, 0x00, 0x03, 0x00, 0xba, 0xa0, 0x02, 0xd0, 0x80, 0x3c, 0x1f, 0xe5,
+        0xf9, 0x24, 0x6d, 0x9e, 0xd9,
+    ];
+    const PPS: [u8; 11] = [
+        0x00, 0x00, 0x00, 0x01, 0x44, 0x01, 0xc1, 0x90, 0x95, 0x81, 0x12,
+    ];
+
+    #[test]
+    fn test_remove_emulation_prevention_bytes() {
+        assert_eq!(
+            remove_emulation_prevention_bytes(&[0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x03, 0x03]),
+            vec![0x00, 0x00, 0x01, 0x00, 0x00, 0x03]
+        );
+        assert_eq!(
+            remove_emulation_prevention_bytes(&[0x00, 0x03, 0x00]),
+            vec![0x00, 0x03, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_vps() {
+        let vps = VideoParameterSet::from_nal_unit(&VPS).unwrap();
+        assert_eq!(vps.header.nal_unit_type, NAL_UNIT_TYPE_VPS);
+        assert_eq!(vps.vps_max_layers_minus1, 0);
+        assert_eq!(vps.vps_max_sub_layers_minus1, 0);
+        assert_eq!(vps.profile_tier_level.general_profile_idc, 1);
+        assert_eq!(vps.profile_tier_level.general_level_idc, 186);
+        assert_eq!(vps.timing_info, None);
+    }
+
+    #[test]
+    fn test_sps() {
+        let sps = SequenceParameterSet::from_nal_unit(&SPS).unwrap();
+        assert_eq!(sps.profile_tier_level.general_profile_idc, 1);
+        assert!(!sps.profile_tier_level.general_tier_flag);
+        assert_eq!(sps.profile_tier_level.general_level_idc, 186);
+        assert_eq!(sps.chroma_format_idc, 1);
+        assert_eq!(sps.pic_width_in_luma_samples, 1440);
+        assert_eq!(sps.pic_height_in_luma_samples, 960);
+        assert_eq!(sps.conformance_window, Some(ConformanceWindow::default()));
+        assert_eq!(sps.output_size(), (1440, 960));
+        assert_eq!(sps.bit_depth_luma(), 8);
+        assert_eq!(sps.bit_depth_chroma(), 8);
+        assert_eq!(sps.short_term_ref_pic_sets.len(), 2);
+        assert_eq!(sps.vui_parameters, None);
+    }
+
+    #[test]
+    fn test_vui() {
+        // video_format 5, full range, colour description (1, 13, 6)
+        let mut stream = BitStream::new(vec![0x37, 0x01, 0x0D, 0x06, 0x00]);
+        let vui = VuiParameters::from_stream(&mut stream).unwrap();
+        assert_eq!(vui.video_format, 5);
+        assert!(vui.video_full_range_flag);
+        assert_eq!(
+            vui.colour_description,
+            Some(ColourDescription {
+                colour_primaries: 1,
+                transfer_characteristics: 13,
+                matrix_coeffs: 6,
+            })
+        );
+        assert_eq!(vui.default_display_window, None);
+        assert_eq!(vui.timing_info, None);
+    }
+
+    #[test]
+    fn test_output_size() {
+        let sps = SequenceParameterSet {
+            chroma_format_idc: 1,
+            pic_width_in_luma_samples: 1456,
+            pic_height_in_luma_samples: 976,
+            conformance_window: Some(ConformanceWindow {
+                left_offset: 0,
+                right_offset: 8,
+                top_offset: 0,
+                bottom_offset: 8,
+            }),
+            ..SequenceParameterSet::default()
+        };
+        assert_eq!(sps.output_size(), (1440, 960));
+    }
+
+    #[test]
+    fn test_pps() {
+        let pps = PictureParameterSet::from_nal_unit(&PPS).unwrap();
+        assert_eq!(pps.pps_pic_parameter_set_id, 0);
+        assert_eq!(pps.pps_seq_parameter_set_id, 0);
+        assert_eq!(pps.tile_info, None);
+        assert!(SequenceParameterSet::from_nal_unit(&PPS).is_err());
+    }
+
+    #[test]
+    fn test_inter_ref_pic_set() {
+        // set 0: one negative picture at -1 (010 1 1 1)
+        // set 1: predicted from set 0 with deltaRps -1, all used (1 1 1 1 1)
+        let mut stream = BitStream::new(vec![0x5F, 0xE0]);
+        let set0 = ShortTermRefPicSet::from_stream(&mut stream, 0, &[]).unwrap();
+        assert_eq!(set0.delta_poc_s0, vec![-1]);
+        assert_eq!(set0.delta_poc_s1, Vec::<i32>::new());
+        let set1 = ShortTermRefPicSet::from_stream(&mut stream, 1, &[set0]).unwrap();
+        assert_eq!(set1.delta_poc_s0, vec![-1, -2]);
+        assert_eq!(set1.used_by_curr_pic_s0, vec![true, true]);
+    }
+}
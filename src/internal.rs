@@ -2,6 +2,7 @@ use std::collections::{HashMap, HashSet};
 
 use crate::bit::Byte4;
 use crate::data::*;
+use crate::derivation::DerivationGraph;
 
 #[derive(Debug, Default)]
 pub struct FileFeature {
@@ -112,15 +113,17 @@ pub struct MetaBoxProperties {
     pub meta_box_feature: MetaBoxFeature,
     pub item_features_map: ItemFeaturesMap,
     pub entity_groupings: Groupings,
+    pub derivation_graph: DerivationGraph,
 }
 
+// The movie-level meta box, with moov_id being its context id.
 #[derive(Debug)]
 pub struct MoovProperties {
     pub moov_id: u32,
     pub meta_box_properties: MetaBoxProperties,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SampleProperties {
     pub sample_id: u32,
     pub sample_entry_type: Byte4,
@@ -143,12 +146,16 @@ pub struct TrackProperties {
     pub alternate_track_ids: IdVec,
     pub reference_track_ids: HashMap<String, IdVec>,
     pub grouped_samples: Vec<SampleGrouping>,
+    // Entries of the traf sgpd boxes read so far, per grouping type. They are
+    // numbered on from TRAF_GROUP_DESCRIPTION_INDEX_BASE.
+    pub fragment_group_entry_counts: HashMap<String, u32>,
     pub equivalences: Vec<SampleVisualEquivalence>,
     pub metadatas: Vec<SampleToMetadataItem>,
     pub reference_samples: Vec<DirectReferenceSamples>,
     pub max_sample_size: u64,
     pub time_scale: u32,
     pub edit_list: EditList,
+    pub meta_box_properties: Option<MetaBoxProperties>,
 }
 
 #[derive(Debug, Default)]
@@ -156,6 +163,7 @@ pub struct FileInformationInternal {
     pub file_feature: FileFeature,
     pub track_properties: HashMap<u32, TrackProperties>,
     pub root_meta_box_properties: MetaBoxProperties,
+    pub moov_properties: Option<MoovProperties>,
     pub movie_timescale: u32,
 }
 
@@ -1,8 +1,18 @@
 mod bbox;
 mod bit;
+mod color;
 mod data;
+mod decode;
+mod derivation;
+mod gainmap;
+mod groups;
+mod hevc;
 mod internal;
 pub mod reader;
+mod resolver;
+#[cfg(test)]
+mod testutil;
+mod unci;
 
 pub type Result<T> = std::result::Result<T, HeifError>;
 
@@ -17,6 +27,7 @@ pub enum HeifError {
     InvalidSequenceID,
     ProtectedItem,
     UnsupportedCodeType,
+    NoDataResolver,
     EOF,
     Unknown(&'static str),
 }
@@ -33,6 +44,7 @@ impl HeifError {
             HeifError::InvalidSequenceID => "InvalidSequenceID",
             HeifError::ProtectedItem => "ProtectedItem",
             HeifError::UnsupportedCodeType => "UnsupportedCodeType",
+            HeifError::NoDataResolver => "NoDataResolver",
             HeifError::EOF => "EOF",
             HeifError::Unknown(s) => s,
         }
@@ -1,20 +1,80 @@
-use std::collections::{HashMap, LinkedList};
+use std::collections::{HashMap, HashSet, LinkedList};
 use std::fs::File;
 
 use crate::bbox::ftyp::FileTypeBox;
 use crate::bbox::header::{BoxHeader, Header};
 use crate::bbox::meta::iinf::ItemInfoEntry;
 use crate::bbox::meta::iloc::ConstructionMethod;
-use crate::bbox::meta::iprp::hevc::HevcConfigurationBox;
+use crate::bbox::meta::iprp::aebr::AutoExposureProperty;
+use crate::bbox::meta::iprp::afbr::FlashExposureProperty;
+use crate::bbox::meta::iprp::amve::AmbientViewingEnvironmentProperty;
+use crate::bbox::meta::iprp::auxc::AuxiliaryTypeProperty;
+use crate::bbox::meta::iprp::cclv::ContentColorVolumeProperty;
+use crate::bbox::meta::iprp::clap::CleanApertureProperty;
+use crate::bbox::meta::iprp::clli::ContentLightLevelProperty;
+use crate::bbox::meta::iprp::cmpd::ComponentDefinitionBox;
+use crate::bbox::meta::iprp::colr::ColorInformationProperty;
+use crate::bbox::meta::iprp::fobr::FocusProperty;
+use crate::bbox::meta::iprp::hevc::{HevcConfigurationBox, LHevcConfigurationBox};
+use crate::bbox::meta::iprp::imir::ImageMirrorProperty;
+use crate::bbox::meta::iprp::irot::ImageRotationProperty;
+use crate::bbox::meta::iprp::iscl::ImageScalingProperty;
 use crate::bbox::meta::iprp::ispe::ImageSpatialExtentsProperty;
-use crate::bbox::meta::iprp::{DecoderConfigurationRecord, PropertyType};
+use crate::bbox::meta::iprp::linf::LayerInformationProperty;
+use crate::bbox::meta::iprp::mdcv::MasteringDisplayColorVolumeProperty;
+use crate::bbox::meta::iprp::oinf::OperatingPointsInformationProperty;
+use crate::bbox::meta::iprp::pasp::PixelAspectRatioProperty;
+use crate::bbox::meta::iprp::rloc::RelativeLocationProperty;
+use crate::bbox::meta::iprp::tols::TargetOlsProperty;
+use crate::bbox::meta::iprp::uncc::UncompressedFrameConfigBox;
+use crate::bbox::meta::iprp::vvc::VvcConfigurationBox;
+use crate::bbox::meta::iprp::wbbr::WhiteBalanceProperty;
+use crate::bbox::meta::iprp::{DecoderConfigurationRecord, DecoderParameterType, PropertyType};
 use crate::bbox::meta::MetaBox;
+use crate::bbox::moof::{
+    sample_depends_on, sample_is_depended_on, sample_is_non_sync_sample, MovieFragmentBox,
+    MovieFragmentRandomAccessBox, TrackFragmentBox, TRAF_GROUP_DESCRIPTION_INDEX_BASE,
+};
+use crate::bbox::moov::mvex::TrackExtendsBox;
+use crate::bbox::moov::sample_group::{
+    SampleGroupDescriptionBox, SampleGroupEntry, SampleToGroupBox,
+};
+use crate::bbox::moov::sample_table::{SampleEntryBox, SampleTableBox};
+use crate::bbox::moov::track::TrackBox;
 use crate::bbox::moov::MovieBox;
 use crate::bit::{BitStream, Byte4, Stream};
+use crate::color::convert_to_rgb;
 use crate::data::*;
+use crate::decode::{
+    apply_transform, assemble_grid, attach_alpha, composite_overlay, transformed_size,
+    unpremultiply_alpha, DecoderRegistry,
+};
+use crate::derivation::MAX_DERIVATION_DEPTH;
+use crate::gainmap::{parse_tone_map, AUX_TYPE_APPLE_GAIN_MAP};
+use crate::groups::entity_group;
 use crate::internal::*;
+use crate::resolver::DataReferences;
+use crate::unci::decode_uncompressed_image;
 use crate::{HeifError, Result};
 
+pub use crate::data::{
+    AlphaHandling, AmbientViewingEnvironment, AudioFormat, AudioSample, AuxiliaryType,
+    BracketValue, Capabilities, ChromaUpsampling, Chromaticity, CleanAperture, CodingConstraints,
+    ColorInformation, ContentColorVolume, ContentLightLevel, EntityGroup, EntityGroupType,
+    EquivalenceTiming, GridLayout, GridTile, ImageTransform, MasteringDisplayColorVolume,
+    MetadataSample, Mirror, PanoramaDirection, PixelBuffer, PixelComponent, PixelLayout, Rectangle,
+    ResolvedImage, RgbConversion, Rotate, SampleType, StereoPair, TrackSampleType,
+};
+pub use crate::decode::ImageDecoder;
+pub use crate::derivation::{DerivationGraph, GraphEdge, GraphNode, ReferenceType};
+pub use crate::gainmap::apply_gain_map;
+pub use crate::hevc::{
+    ColourDescription, ConformanceWindow, HevcParameterSets, NalUnitHeader, PictureParameterSet,
+    ProfileTierLevel, SequenceParameterSet, ShortTermRefPicSet, SubLayerOrderingInfo,
+    SubLayerProfileTierLevel, TileInfo, TimingInfo, VideoParameterSet, VuiParameters,
+};
+pub use crate::resolver::{DataResolver, DirectoryResolver};
+
 #[derive(Debug)]
 struct ItemInfo {
     pub item_type: Byte4,
@@ -41,12 +101,14 @@ struct MetaBoxInfo {
 #[derive(Default, Debug)]
 struct SampleInfo {
     decoding_order: u32,
+    decode_time: i64,
     composition_times: Vec<i64>,
     data_offset: u64,
     data_length: u64,
     width: u32,
     height: u32,
     decode_dependencies: IdVec,
+    is_sync: bool,
 }
 
 type SampleInfoVector = Vec<SampleInfo>;
@@ -62,6 +124,9 @@ struct TrackInfo {
     pub clap_properties: HashMap<u32, CleanAperture>,
     pub auxi_properties: HashMap<u32, AuxiliaryType>,
     pub repetitions: f64,
+    // Fragment samples by moof offset and the traf, trun and sample numbers
+    // of tfra entries, which count from 1.
+    pub fragment_sample_ids: HashMap<(u64, u32, u32, u32), u32>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -92,6 +157,9 @@ pub struct HeifReader {
     metabox_info: HashMap<u32, MetaBoxInfo>,
     matrix: Vec<i32>,
     track_info: HashMap<u32, TrackInfo>,
+    movie_box: MovieBox,
+    decoders: DecoderRegistry,
+    data_references: DataReferences,
 }
 
 // accessors
@@ -124,6 +192,12 @@ impl HeifReader {
         self.check_initialized(&self.ftyp.compatible_brands())
     }
 
+    // True if the file declares the VVC image brand, either as major brand or
+    // in the compatible brands.
+    pub fn has_vvc_brand(&self) -> Result<bool> {
+        self.check_initialized(self.ftyp.has_brand("vvic"))
+    }
+
     pub fn display_width(&self, sequence_id: u32) -> Result<u32> {
         Ok(self.get_track_by_sequence_id(sequence_id)?.width)
     }
@@ -133,7 +207,11 @@ impl HeifReader {
     }
 
     pub fn width(&self, item_id: u32) -> Result<u32> {
-        if let Some(info) = self.root_meta_box_info()?.item_info_map.get(&item_id) {
+        self.width_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn width_in_context(&self, context_id: u32, item_id: u32) -> Result<u32> {
+        if let Some(info) = self.meta_box_info(context_id)?.item_info_map.get(&item_id) {
             Ok(info.width)
         } else {
             Err(HeifError::InvalidItemID)
@@ -150,8 +228,11 @@ impl HeifReader {
     }
 
     pub fn height(&self, item_id: u32) -> Result<u32> {
-        let root = self.root_meta_box_info()?;
-        if let Some(info) = root.item_info_map.get(&item_id) {
+        self.height_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn height_in_context(&self, context_id: u32, item_id: u32) -> Result<u32> {
+        if let Some(info) = self.meta_box_info(context_id)?.item_info_map.get(&item_id) {
             Ok(info.height)
         } else {
             Err(HeifError::InvalidItemID)
@@ -171,23 +252,572 @@ impl HeifReader {
         &self.matrix
     }
 
+    // Sample groups of a track, with samples numbered from 0 in decoding
+    // order.
+    pub fn sample_groupings(&self, sequence_id: u32) -> Result<&Vec<SampleGrouping>> {
+        Ok(&self.get_track_properties(sequence_id)?.grouped_samples)
+    }
+
+    pub fn sample_equivalence(
+        &self,
+        sequence_id: u32,
+        sample_id: u32,
+    ) -> Result<Option<&SampleVisualEquivalence>> {
+        let index = match self.sample_group_description_index(sequence_id, "eqiv", sample_id)? {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        Ok(self
+            .get_track_properties(sequence_id)?
+            .equivalences
+            .iter()
+            .find(|e| e.sample_group_description_index == index))
+    }
+
+    // The items an eqiv sample group marks sample_id as equivalent to: the
+    // items of the eqiv entity groups which hold the track and whose timing
+    // for the track is the composition time of the sample. A group without
+    // a timing for the track applies to every sample of the eqiv grouping.
+    pub fn equivalent_items_for_sample(&self, sequence_id: u32, sample_id: u32) -> Result<IdVec> {
+        if self.sample_equivalence(sequence_id, sample_id)?.is_none()
+            || !self
+                .metabox_map
+                .contains_key(&self.file_properties.root_meta_box_properties.context_id)
+        {
+            return Ok(IdVec::new());
+        }
+        let composition_time = self.get_track_by_sequence_id(sequence_id)?.samples
+            [sample_id as usize]
+            .composition_times[0];
+        let mut item_ids = IdVec::new();
+        for group in self.entity_groups_containing(sequence_id)? {
+            let timings = match &group.group_type {
+                EntityGroupType::Equivalence(timings) => timings,
+                _ => continue,
+            };
+            // Timings are given per track, in the order of the entity ids.
+            let track_index = group
+                .entity_ids
+                .iter()
+                .take_while(|id| **id != sequence_id)
+                .filter(|id| self.track_info.contains_key(id))
+                .count();
+            if let Some(timing) = timings.get(track_index) {
+                if equivalence_time(timing) != composition_time {
+                    continue;
+                }
+            }
+            for entity_id in &group.entity_ids {
+                if *entity_id != sequence_id
+                    && !self.track_info.contains_key(entity_id)
+                    && self.is_valid_item(*entity_id)?
+                    && !item_ids.contains(entity_id)
+                {
+                    item_ids.push(*entity_id);
+                }
+            }
+        }
+        Ok(item_ids)
+    }
+
+    // The metadata items an stmi sample group links sample_id to.
+    pub fn metadata_items_for_sample(&self, sequence_id: u32, sample_id: u32) -> Result<IdVec> {
+        let index = match self.sample_group_description_index(sequence_id, "stmi", sample_id)? {
+            Some(index) => index,
+            None => return Ok(IdVec::new()),
+        };
+        Ok(self
+            .get_track_properties(sequence_id)?
+            .metadatas
+            .iter()
+            .find(|m| m.sample_group_description_index == index)
+            .map(|m| m.metadata_item_ids.clone())
+            .unwrap_or_default())
+    }
+
+    // The samples sample_id is predicted from, as given by a refs sample
+    // group.
+    pub fn decode_dependencies(&self, sequence_id: u32, sample_id: u32) -> Result<IdVec> {
+        self.check_sample_id(sequence_id, sample_id)?;
+        Ok(
+            self.get_track_by_sequence_id(sequence_id)?.samples[sample_id as usize]
+                .decode_dependencies
+                .clone(),
+        )
+    }
+
+    pub fn sample_type(&self, sequence_id: u32, sample_id: u32) -> Result<SampleType> {
+        Ok(self
+            .get_sample_properties(sequence_id, sample_id)?
+            .sample_type)
+    }
+
+    // Samples of a track of the given type. Display lists the output samples
+    // in composition order and Samples lists every sample in decoding order.
+    pub fn track_sample_list_by_type(
+        &self,
+        sequence_id: u32,
+        sample_type: TrackSampleType,
+    ) -> Result<IdVec> {
+        let track = self.get_track_properties(sequence_id)?;
+        let mut sample_ids: IdVec = track.sample_properties.keys().copied().collect();
+        sample_ids.sort_unstable();
+        let of_type = |wanted: SampleType| -> IdVec {
+            sample_ids
+                .iter()
+                .copied()
+                .filter(|id| track.sample_properties[id].sample_type == wanted)
+                .collect()
+        };
+        Ok(match sample_type {
+            TrackSampleType::OutRef => of_type(SampleType::OutputReferenceFrame),
+            TrackSampleType::OutNonRef => of_type(SampleType::OutputNonReferenceFrame),
+            TrackSampleType::NonOutRef => of_type(SampleType::NnonOutputReferenceFrame),
+            TrackSampleType::Display => {
+                let samples = &self.get_track_by_sequence_id(sequence_id)?.samples;
+                let mut output: IdVec = sample_ids
+                    .iter()
+                    .copied()
+                    .filter(|id| {
+                        track.sample_properties[id].sample_type
+                            != SampleType::NnonOutputReferenceFrame
+                    })
+                    .collect();
+                output.sort_by_key(|id| samples[*id as usize].composition_times[0]);
+                output
+            }
+            TrackSampleType::Samples => sample_ids,
+        })
+    }
+
+    // Whether decoding can start at sample_id: it is a sync sample, sdtp
+    // says it does not depend on other samples, or a tfra box lists it.
+    pub fn is_random_access_sample(&self, sequence_id: u32, sample_id: u32) -> Result<bool> {
+        self.check_sample_id(sequence_id, sample_id)?;
+        Ok(self.get_track_by_sequence_id(sequence_id)?.samples[sample_id as usize].is_sync)
+    }
+
+    pub fn random_access_samples(&self, sequence_id: u32) -> Result<IdVec> {
+        let sample_count = self.get_track_by_sequence_id(sequence_id)?.samples.len() as u32;
+        let mut sample_ids = IdVec::new();
+        for sample_id in 0..sample_count {
+            if self.is_random_access_sample(sequence_id, sample_id)? {
+                sample_ids.push(sample_id);
+            }
+        }
+        Ok(sample_ids)
+    }
+
+    // Whether every sample of a track is coded without references to other
+    // samples, by ccst or because every sample is a random access sample.
+    pub fn is_intra_only(&self, sequence_id: u32) -> Result<bool> {
+        let track = self.get_track_properties(sequence_id)?;
+        if track
+            .track_feature
+            .has_feature(TrackFeatureEnum::HasCodingConstraints)
+            && track
+                .sample_properties
+                .values()
+                .all(|sample| sample.coding_constraints.max_ref_per_pic == 0)
+        {
+            return Ok(true);
+        }
+        Ok(self.random_access_samples(sequence_id)?.len() == track.sample_properties.len())
+    }
+
+    // Frame accurate scrubbing is offered only when every sample entry has a
+    // ccst with all_ref_pics_intra set, so any sample decodes after at most
+    // its intra coded references.
+    pub fn supports_frame_accurate_scrubbing(&self, sequence_id: u32) -> Result<bool> {
+        let track = self.get_track_properties(sequence_id)?;
+        Ok(track
+            .track_feature
+            .has_feature(TrackFeatureEnum::HasCodingConstraints)
+            && track
+                .sample_properties
+                .values()
+                .all(|sample| sample.coding_constraints.all_ref_pics_intra))
+    }
+
+    // Tracks whose tref of the given type points to track_id, in track id
+    // order.
+    fn referencing_tracks(&self, track_id: u32, reference_type: &str) -> Result<IdVec> {
+        self.get_track_properties(track_id)?;
+        let mut track_ids: IdVec = self
+            .file_properties
+            .track_properties
+            .values()
+            .filter(|track| {
+                track
+                    .reference_track_ids
+                    .get(reference_type)
+                    .is_some_and(|track_ids| track_ids.contains(&track_id))
+            })
+            .map(|track| track.track_id)
+            .collect();
+        track_ids.sort_unstable();
+        Ok(track_ids)
+    }
+
+    // The auxiliary image sequences, such as alpha planes, linked to a track
+    // by their auxl track references.
+    pub fn auxiliary_tracks_for(&self, track_id: u32) -> Result<IdVec> {
+        self.referencing_tracks(track_id, "auxl")
+    }
+
+    // The thumbnail image sequence linked to a track by its thmb track
+    // reference. The lowest track id wins when there are several.
+    pub fn thumbnail_track_for(&self, track_id: u32) -> Result<Option<u32>> {
+        Ok(self.referencing_tracks(track_id, "thmb")?.first().copied())
+    }
+
+    // The auxi type of an auxiliary image sequence, from its first sample
+    // entry with one.
+    pub fn track_auxiliary_type(&self, track_id: u32) -> Result<Option<&AuxiliaryType>> {
+        let auxi_properties = &self.get_track_by_sequence_id(track_id)?.auxi_properties;
+        let mut indices: Vec<&u32> = auxi_properties.keys().collect();
+        indices.sort();
+        Ok(indices.first().map(|index| &auxi_properties[*index]))
+    }
+
+    pub fn get_track_sample_data(&self, sequence_id: u32, sample_id: u32) -> Result<Vec<u8>> {
+        self.check_sample_id(sequence_id, sample_id)?;
+        let sample = &self.get_track_by_sequence_id(sequence_id)?.samples[sample_id as usize];
+        Ok(self
+            .stream
+            .slice(sample.data_offset as usize, sample.data_length as usize)?
+            .to_vec())
+    }
+
+    // Timed metadata tracks which describe a track through cdsc track
+    // references.
+    pub fn metadata_tracks_for(&self, track_id: u32) -> Result<IdVec> {
+        Ok(self
+            .referencing_tracks(track_id, "cdsc")?
+            .into_iter()
+            .filter(|id| self.is_metadata_track(*id))
+            .collect())
+    }
+
+    // The sample of each metadata track of a sequence which is current when
+    // sample_id is presented: the last one whose presentation time is not
+    // after that of the image sample. Times are compared after applying the
+    // edit list of each track. Tracks whose first sample starts later are
+    // left out.
+    pub fn timed_metadata_for_sample(
+        &self,
+        sequence_id: u32,
+        sample_id: u32,
+    ) -> Result<Vec<MetadataSample>> {
+        self.check_sample_id(sequence_id, sample_id)?;
+        let (time, time_scale) = self.presentation_time(
+            sequence_id,
+            self.get_track_by_sequence_id(sequence_id)?.samples[sample_id as usize]
+                .composition_times[0],
+        )?;
+        let mut metadata = Vec::new();
+        for track_id in self.metadata_tracks_for(sequence_id)? {
+            // Compares metadata_time / metadata_time_scale with
+            // time / time_scale.
+            let mut current = None;
+            for sample in &self.get_track_by_sequence_id(track_id)?.samples {
+                let (metadata_time, metadata_time_scale) =
+                    self.presentation_time(track_id, sample.composition_times[0])?;
+                if metadata_time * time_scale <= time * metadata_time_scale
+                    && current.is_none_or(|(latest, _)| metadata_time >= latest)
+                {
+                    current = Some((metadata_time, sample));
+                }
+            }
+            let sample = match current {
+                Some((_, sample)) => sample,
+                None => continue,
+            };
+            let properties = self.get_sample_properties(track_id, sample.decoding_order)?;
+            let content_type = self
+                .get_track_box(track_id)?
+                .media_box()
+                .media_information_box()
+                .sample_table_box()
+                .sample_description_box()
+                .sample_entry(properties.sample_description_index)
+                .and_then(|entry| entry.metadata_content_type())
+                .unwrap_or_default();
+            metadata.push(MetadataSample {
+                track_id,
+                sample_id: sample.decoding_order,
+                sample_entry_type: properties.sample_entry_type.clone(),
+                content_type,
+                data: self.get_track_sample_data(track_id, sample.decoding_order)?,
+            });
+        }
+        Ok(metadata)
+    }
+
+    pub fn audio_tracks(&self) -> Result<IdVec> {
+        if !self.is_initialized() {
+            return Err(HeifError::Uninitialized);
+        }
+        let mut track_ids: IdVec = self
+            .file_properties
+            .track_properties
+            .values()
+            .filter(|track| {
+                track
+                    .track_feature
+                    .has_feature(TrackFeatureEnum::IsAudioTrack)
+            })
+            .map(|track| track.track_id)
+            .collect();
+        track_ids.sort_unstable();
+        Ok(track_ids)
+    }
+
+    // The format of the first sample entry of an audio track.
+    pub fn audio_format(&self, track_id: u32) -> Result<AudioFormat> {
+        if !self
+            .get_track_properties(track_id)?
+            .track_feature
+            .has_feature(TrackFeatureEnum::IsAudioTrack)
+        {
+            return Err(HeifError::NotApplicable);
+        }
+        let track_box = self.get_track_box(track_id)?;
+        let entry = match track_box
+            .media_box()
+            .media_information_box()
+            .sample_table_box()
+            .sample_description_box()
+            .sample_entry(1)
+        {
+            Some(entry) => entry,
+            None => return Err(HeifError::FileHeader),
+        };
+        let (channel_count, sample_size, sample_rate) = entry.audio_format()?;
+        let (object_type_indication, audio_specific_config) =
+            match entry.elementary_stream_descriptor_box()? {
+                Some(esds) => (
+                    esds.object_type_indication(),
+                    esds.configuration_map()
+                        .remove(&DecoderParameterType::AudioSpecificConfig)
+                        .unwrap_or_default(),
+                ),
+                None => (0, Vec::new()),
+            };
+        Ok(AudioFormat {
+            sample_entry_type: entry.box_type().clone(),
+            channel_count,
+            sample_size,
+            sample_rate: sample_rate >> 16,
+            object_type_indication,
+            audio_specific_config,
+            time_scale: track_box.media_box().media_header_box().time_scale(),
+        })
+    }
+
+    // The samples of an audio track in decoding order.
+    pub fn audio_samples(&self, track_id: u32) -> Result<AudioSamples<'_>> {
+        self.audio_format(track_id)?;
+        Ok(AudioSamples {
+            reader: self,
+            track_id,
+            next_sample_id: 0,
+        })
+    }
+
+    // One enabled master image sequence from each alternate group, in track
+    // id order. A track with alternate_group 0 is a group of its own. When
+    // several tracks of a group are enabled, such as HEVC and AVC codings of
+    // the same sequence, the lowest track id whose sample entries can all be
+    // decoded with the given capabilities is chosen.
+    pub fn default_tracks(&self, capabilities: &Capabilities) -> Result<IdVec> {
+        if !self.is_initialized() {
+            return Err(HeifError::Uninitialized);
+        }
+        let mut candidates: Vec<&TrackProperties> = self
+            .file_properties
+            .track_properties
+            .values()
+            .filter(|track| {
+                track
+                    .track_feature
+                    .has_feature(TrackFeatureEnum::IsMasterImageSequence)
+                    && track.track_feature.has_feature(TrackFeatureEnum::IsEnabled)
+            })
+            .collect();
+        candidates.sort_by_key(|track| track.track_id);
+        let mut groups = Vec::new();
+        let mut track_ids = IdVec::new();
+        for track in candidates {
+            if !self.is_track_decodable(track.track_id, capabilities)? {
+                continue;
+            }
+            if track.alternate_group_id != 0 {
+                if groups.contains(&track.alternate_group_id) {
+                    continue;
+                }
+                groups.push(track.alternate_group_id);
+            }
+            track_ids.push(track.track_id);
+        }
+        Ok(track_ids)
+    }
+
+    pub fn default_track(&self, capabilities: &Capabilities) -> Result<Option<u32>> {
+        Ok(self.default_tracks(capabilities)?.first().copied())
+    }
+
+    // Protected tracks have encv or resv entries and so are never decodable.
+    fn is_track_decodable(&self, track_id: u32, capabilities: &Capabilities) -> Result<bool> {
+        let entries = self
+            .get_track_box(track_id)?
+            .media_box()
+            .media_information_box()
+            .sample_table_box()
+            .sample_description_box()
+            .sample_entries();
+        Ok(!entries.is_empty()
+            && entries
+                .iter()
+                .all(|entry| capabilities.supports_item_type(&entry.box_type().to_string())))
+    }
+
+    // Meta boxes are keyed by context id: 0 for the root meta box, then the
+    // movie and track meta boxes in file order.
+    pub fn meta_contexts(&self) -> Result<IdVec> {
+        self.check_initialized(())?;
+        let mut context_ids: IdVec = self.metabox_map.keys().cloned().collect();
+        context_ids.sort_unstable();
+        Ok(context_ids)
+    }
+
+    pub fn movie_meta_context(&self) -> Result<Option<u32>> {
+        self.check_initialized(())?;
+        Ok(self
+            .file_properties
+            .moov_properties
+            .as_ref()
+            .map(|moov| moov.moov_id))
+    }
+
+    pub fn track_meta_context(&self, track_id: u32) -> Result<Option<u32>> {
+        Ok(self
+            .get_track_properties(track_id)?
+            .meta_box_properties
+            .as_ref()
+            .map(|properties| properties.context_id))
+    }
+
+    // Track meta boxes use their primary item for a cover image.
+    pub fn primary_item_in_context(&self, context_id: u32) -> Result<Option<u32>> {
+        Ok(self
+            .meta_box_properties(context_id)?
+            .item_features_map
+            .iter()
+            .find(|(_, feature)| feature.has_feature(ItemFeatureEnum::IsPrimaryImage))
+            .map(|(item_id, _)| *item_id))
+    }
+
+    pub fn get_item_type_in_context(&self, context_id: u32, item_id: u32) -> Result<&Byte4> {
+        if let Some(info) = self.meta_box_info(context_id)?.item_info_map.get(&item_id) {
+            Ok(&info.item_type)
+        } else {
+            Err(HeifError::InvalidItemID)
+        }
+    }
+
     pub fn grid_item_by_id(&self, item_id: u32) -> Result<&Grid> {
-        if self.is_protected(item_id)? {
+        self.grid_item_by_id_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn grid_item_by_id_in_context(&self, context_id: u32, item_id: u32) -> Result<&Grid> {
+        if self.is_protected_in_context(context_id, item_id)? {
             return Err(HeifError::ProtectedItem);
         }
-        if let Some(g) = self.root_meta_box_info()?.grid_items.get(&item_id) {
+        if let Some(g) = self.meta_box_info(context_id)?.grid_items.get(&item_id) {
             Ok(g)
         } else {
             Err(HeifError::InvalidItemID)
         }
     }
 
+    pub fn overlay_item_by_id(&self, item_id: u32) -> Result<&Overlay> {
+        self.overlay_item_by_id_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn overlay_item_by_id_in_context(&self, context_id: u32, item_id: u32) -> Result<&Overlay> {
+        if self.is_protected_in_context(context_id, item_id)? {
+            return Err(HeifError::ProtectedItem);
+        }
+        if let Some(o) = self.meta_box_info(context_id)?.iovl_items.get(&item_id) {
+            Ok(o)
+        } else {
+            Err(HeifError::InvalidItemID)
+        }
+    }
+
+    // Where each tile of a grid item goes on the output canvas. All tiles
+    // must be coded the same way with the same size and decoder configuration.
+    pub fn grid_layout(&self, grid_id: u32) -> Result<GridLayout> {
+        self.grid_layout_in_context(self.root_context_id(), grid_id)
+    }
+
+    pub fn grid_layout_in_context(&self, context_id: u32, grid_id: u32) -> Result<GridLayout> {
+        let grid = self.grid_item_by_id_in_context(context_id, grid_id)?;
+        let first_tile_id = match grid.image_ids.first() {
+            Some(id) => *id,
+            None => return Err(HeifError::Unknown("grid has no tiles")),
+        };
+        let item_type = self.get_item_type_in_context(context_id, first_tile_id)?;
+        let mut tile_size = None;
+        for tile_id in &grid.image_ids {
+            if self.is_protected_in_context(context_id, *tile_id)? {
+                return Err(HeifError::ProtectedItem);
+            }
+            if self.get_item_type_in_context(context_id, *tile_id)? != item_type {
+                return Err(HeifError::Unknown("grid tiles have different item types"));
+            }
+            let extents = match self.get_property_in_context::<ImageSpatialExtentsProperty>(
+                context_id,
+                PropertyType::ISPE,
+                *tile_id,
+            )? {
+                Some(ispe) => (ispe.width(), ispe.height()),
+                None => return Err(HeifError::FileHeader),
+            };
+            match tile_size {
+                None => tile_size = Some(extents),
+                Some(size) if size != extents => {
+                    return Err(HeifError::Unknown("grid tiles have different sizes"))
+                }
+                _ => (),
+            }
+            if self.tile_parameter_set_map(context_id, *tile_id)
+                != self.tile_parameter_set_map(context_id, first_tile_id)
+            {
+                return Err(HeifError::Unknown(
+                    "grid tiles have different decoder configurations",
+                ));
+            }
+        }
+        let (tile_width, tile_height) = tile_size.unwrap_or_default();
+        grid.layout(tile_width, tile_height)
+    }
+
     pub fn get_item_list_by_type(&self, item_type: Byte4) -> Result<IdVec> {
+        self.get_item_list_by_type_in_context(self.root_context_id(), item_type)
+    }
+
+    pub fn get_item_list_by_type_in_context(
+        &self,
+        context_id: u32,
+        item_type: Byte4,
+    ) -> Result<IdVec> {
         Ok(self
-            .image_item_ids()?
+            .image_item_ids_in_context(context_id)?
             .into_iter()
             .filter(|id| {
-                if let Ok(ty) = self.get_item_type(*id) {
+                if let Ok(ty) = self.get_item_type_in_context(context_id, *id) {
                     *ty == item_type
                 } else {
                     false
@@ -197,46 +827,107 @@ impl HeifReader {
     }
 
     pub fn get_item_data_with_decoder_parameters(&self, item_id: u32) -> Result<Vec<Vec<u8>>> {
-        let item = self.get_item_by_image_id(item_id)?;
+        self.get_item_data_with_decoder_parameters_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn get_item_data_with_decoder_parameters_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+    ) -> Result<Vec<Vec<u8>>> {
+        let item = self.get_item_in_context(context_id, item_id)?;
         if item.is_protected() {
             return Err(HeifError::ProtectedItem);
         }
-        let code_type = self.get_decoder_code_type(item_id)?;
-        if code_type != "hvc1" && code_type != "avc1" {
+        let code_type = self.get_decoder_code_type_in_context(context_id, item_id)?;
+        if code_type != "hvc1" && code_type != "lhv1" && code_type != "vvc1" && code_type != "avc1"
+        {
             return Err(HeifError::UnsupportedCodeType);
         }
-        let decoder_infos = self.get_decoder_parameter_sets(item_id)?;
+        let decoder_infos = self.get_decoder_parameter_sets_in_context(context_id, item_id)?;
         let mut data_buf = Vec::new();
         for config in decoder_infos.decoder_specific_info {
             data_buf.push(config.dec_spec_info_data);
         }
-        data_buf.push(self.get_item_data(item_id, true)?);
+        data_buf.push(self.get_item_data_in_context(context_id, item_id, true)?);
         Ok(data_buf)
     }
 
-    pub fn get_item_data(&self, item_id: u32, byte_stream_headers: bool) -> Result<Vec<u8>> {
-        if !self.is_valid_item(item_id)? {
-            return Err(HeifError::InvalidItemID);
+    pub fn get_hevc_parameter_sets(&self, item_id: u32) -> Result<HevcParameterSets> {
+        if self.is_protected(item_id)? {
+            return Err(HeifError::ProtectedItem);
         }
-        let mut past_references = LinkedList::new();
-        let root_metabox = self.root_meta_box()?;
-        let item_length = self.get_item_length(root_metabox, item_id, &mut past_references)?;
-        if item_length > self.stream.len() {
-            return Err(HeifError::FileHeader);
+        if self.get_decoder_code_type(item_id)? != "hvc1" {
+            return Err(HeifError::UnsupportedCodeType);
+        }
+        let mut vps = None;
+        let mut sps = None;
+        let mut pps = None;
+        for info in self
+            .get_decoder_parameter_sets(item_id)?
+            .decoder_specific_info
+        {
+            if info.dec_spec_info_data.is_empty() {
+                continue;
+            }
+            match info.dec_spec_info_type {
+                DecoderSpecInfoType::HevcVPS => {
+                    vps = Some(VideoParameterSet::from_nal_unit(&info.dec_spec_info_data)?)
+                }
+                DecoderSpecInfoType::HevcSPS => {
+                    sps = Some(SequenceParameterSet::from_nal_unit(
+                        &info.dec_spec_info_data,
+                    )?)
+                }
+                DecoderSpecInfoType::HevcPPS => {
+                    pps = Some(PictureParameterSet::from_nal_unit(
+                        &info.dec_spec_info_data,
+                    )?)
+                }
+                _ => {}
+            }
+        }
+        match sps {
+            Some(sps) => Ok(HevcParameterSets { vps, sps, pps }),
+            None => Err(HeifError::FileHeader),
         }
-        let mut buf = self.read_item(root_metabox, item_id, item_length)?;
-        let raw_type = match root_metabox.item_info_box().item_by_id(item_id) {
-            Some(i) => i.item_type(),
+    }
+
+    pub fn get_item_data(&self, item_id: u32, byte_stream_headers: bool) -> Result<Vec<u8>> {
+        self.get_item_data_in_context(self.root_context_id(), item_id, byte_stream_headers)
+    }
+
+    pub fn get_item_data_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+        byte_stream_headers: bool,
+    ) -> Result<Vec<u8>> {
+        let metabox = self.meta_box(context_id)?;
+        let item = match metabox.item_info_box().item_by_id(item_id) {
+            Some(i) => i,
             None => return Err(HeifError::InvalidItemID),
         };
-        let is_protected = self.is_protected(item_id)?;
-        let process_data = !is_protected && (raw_type == "hvc1" || raw_type == "avc1");
+        let mut past_references = LinkedList::new();
+        let item_length = self.get_item_length(metabox, item_id, &mut past_references)?;
+        if item_length > self.stream.len() && external_data_location(metabox, item_id)?.is_none() {
+            return Err(HeifError::FileHeader);
+        }
+        let mut buf = self.read_item(metabox, item_id, item_length)?;
+        let raw_type = item.item_type();
+        let is_protected = item.is_protected();
+        if !is_protected && byte_stream_headers && (raw_type == "hvt1" || raw_type == "lhv1") {
+            process_hevc_item_data(&mut buf);
+            return Ok(buf);
+        }
+        let process_data =
+            !is_protected && (raw_type == "hvc1" || raw_type == "vvc1" || raw_type == "avc1");
         if process_data && byte_stream_headers {
-            let code_type = self.get_decoder_code_type(item_id)?;
-            if code_type == "avc1" {
-                unimplemented!("get_item_data avc1");
-            } else if code_type == "hvc1" {
+            let code_type = self.get_decoder_code_type_in_context(context_id, item_id)?;
+            if code_type == "hvc1" {
                 process_hevc_item_data(&mut buf);
+            } else if code_type == "vvc1" {
+                process_nal_item_data(&mut buf, vvc_nal_length_size(metabox, item_id));
             } else {
                 return Err(HeifError::UnsupportedCodeType);
             }
@@ -244,72 +935,1143 @@ impl HeifReader {
         Ok(buf)
     }
 
-    pub fn get_master_image_ids(&self) -> Result<IdVec> {
-        let root_metabox = self.root_meta_box()?;
-        Ok(self
-            .image_item_ids()?
-            .into_iter()
-            .filter(|item_id| {
-                let ty = self
-                    .get_item_by_image_id(*item_id)
-                    .unwrap()
-                    .item_type()
-                    .to_string();
-                (ty == "avc1" || ty == "hvc1")
-                    && (!do_references_from_item_id_exist(
-                        root_metabox,
-                        *item_id,
-                        "auxl".parse().unwrap(),
-                    ) && !do_references_from_item_id_exist(
-                        root_metabox,
-                        *item_id,
-                        "thmb".parse().unwrap(),
-                    ))
-            })
-            .collect())
+    pub fn get_tile_base_item_id(&self, tile_item_id: u32) -> Result<u32> {
+        self.get_tile_base_item_id_in_context(self.root_context_id(), tile_item_id)
     }
-}
-
-// load
-impl HeifReader {
-    pub fn load(&mut self, file_path: &str) -> Result<()> {
-        let mut file = File::open(file_path).map_err(|_| HeifError::FileOpen)?;
-        self.reset();
 
-        self.stream = BitStream::from(&mut file)?;
-        self.read_stream()?;
+    pub fn get_tile_base_item_id_in_context(
+        &self,
+        context_id: u32,
+        tile_item_id: u32,
+    ) -> Result<u32> {
+        if *self
+            .get_item_in_context(context_id, tile_item_id)?
+            .item_type()
+            != "hvt1"
+        {
+            return Err(HeifError::NotApplicable);
+        }
+        match self
+            .get_referenced_from_item_list_by_type_in_context(
+                context_id,
+                tile_item_id,
+                "tbas".parse().unwrap(),
+            )?
+            .first()
+        {
+            Some(base_item_id) => Ok(*base_item_id),
+            None => Err(HeifError::FileHeader),
+        }
+    }
 
-        self.file_information.root_meta_box_information =
-            self.convert_root_meta_box_information(&self.file_properties.root_meta_box_properties)?;
-        // TODO: convertTrackInformation
-        self.file_information.features = self.file_properties.file_feature.feature_mask();
-        self.file_information.movie_timescale = self.file_properties.movie_timescale;
+    pub fn get_tile_item_ids(&self, base_item_id: u32) -> Result<IdVec> {
+        self.get_tile_item_ids_in_context(self.root_context_id(), base_item_id)
+    }
 
-        println!(">> fileProperties: {:?}\n", self.file_properties);
-        println!(">> decoderCodeTypeMap: {:?}\n", self.decoder_code_type_map);
-        println!(">> parameterSetMap: {:?}\n", self.parameter_set_map);
-        println!(
-            ">> imageToParameterSetMap: {:?}\n",
-            self.image_to_parameter_set_map
-        );
-        println!(">> ftyp: {:?}\n", self.ftyp);
-        println!(">> fileInformation: {:?}\n", self.file_information);
-        println!(">> metaboxMap: {:?}\n", self.metabox_map);
-        println!(">> metaboxInfo: {:?}\n", self.metabox_info);
-        println!(">> trackInfo: {:?}\n", self.track_info);
+    pub fn get_tile_item_ids_in_context(
+        &self,
+        context_id: u32,
+        base_item_id: u32,
+    ) -> Result<IdVec> {
+        self.get_item_in_context(context_id, base_item_id)?;
+        Ok(self
+            .meta_box(context_id)?
+            .item_info_box()
+            .item_info_list()
+            .iter()
+            .filter(|item| *item.item_type() == "hvt1")
+            .map(ItemInfoEntry::item_id)
+            .filter(|tile_item_id| {
+                self.get_tile_base_item_id_in_context(context_id, *tile_item_id)
+                    .ok()
+                    == Some(base_item_id)
+            })
+            .collect())
+    }
 
-        Ok(())
+    pub fn relative_location(&self, item_id: u32) -> Result<RelativeLocation> {
+        self.relative_location_in_context(self.root_context_id(), item_id)
     }
 
-    fn get_item_type(&self, item_id: u32) -> Result<&Byte4> {
-        let root_metabox_info = self.root_meta_box_info()?;
-        if let Some(info) = root_metabox_info.item_info_map.get(&item_id) {
-            Ok(&info.item_type)
-        } else {
-            Err(HeifError::InvalidItemID)
+    pub fn relative_location_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+    ) -> Result<RelativeLocation> {
+        self.get_item_in_context(context_id, item_id)?;
+        match self.get_property_in_context::<RelativeLocationProperty>(
+            context_id,
+            PropertyType::RLOC,
+            item_id,
+        )? {
+            Some(rloc) => Ok(RelativeLocation {
+                horizontal_offset: rloc.horizontal_offset(),
+                vertical_offset: rloc.vertical_offset(),
+            }),
+            None => Err(HeifError::NotApplicable),
         }
     }
 
+    pub fn get_tile_item_data_with_decoder_parameters(
+        &self,
+        tile_item_id: u32,
+    ) -> Result<Vec<Vec<u8>>> {
+        self.get_tile_item_data_with_decoder_parameters_in_context(
+            self.root_context_id(),
+            tile_item_id,
+        )
+    }
+
+    pub fn get_tile_item_data_with_decoder_parameters_in_context(
+        &self,
+        context_id: u32,
+        tile_item_id: u32,
+    ) -> Result<Vec<Vec<u8>>> {
+        if self.is_protected_in_context(context_id, tile_item_id)? {
+            return Err(HeifError::ProtectedItem);
+        }
+        let base_item_id = self.get_tile_base_item_id_in_context(context_id, tile_item_id)?;
+        if self.get_decoder_code_type_in_context(context_id, base_item_id)? != "hvc1" {
+            return Err(HeifError::UnsupportedCodeType);
+        }
+        let decoder_infos = self.get_decoder_parameter_sets_in_context(context_id, base_item_id)?;
+        let mut data_buf = Vec::new();
+        for config in decoder_infos.decoder_specific_info {
+            data_buf.push(config.dec_spec_info_data);
+        }
+        data_buf.push(self.get_item_data_in_context(context_id, tile_item_id, true)?);
+        Ok(data_buf)
+    }
+
+    // Returns the tiles of base_item_id which intersect the given rectangle.
+    pub fn get_tile_item_ids_in_region(
+        &self,
+        base_item_id: u32,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<IdVec> {
+        self.get_tile_item_ids_in_region_in_context(
+            self.root_context_id(),
+            base_item_id,
+            x,
+            y,
+            width,
+            height,
+        )
+    }
+
+    pub fn get_tile_item_ids_in_region_in_context(
+        &self,
+        context_id: u32,
+        base_item_id: u32,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<IdVec> {
+        let mut item_ids = IdVec::new();
+        for tile_item_id in self.get_tile_item_ids_in_context(context_id, base_item_id)? {
+            let location = self.relative_location_in_context(context_id, tile_item_id)?;
+            let extents = match self.get_property_in_context::<ImageSpatialExtentsProperty>(
+                context_id,
+                PropertyType::ISPE,
+                tile_item_id,
+            )? {
+                Some(ispe) => ispe,
+                None => return Err(HeifError::FileHeader),
+            };
+            let tile_left = u64::from(location.horizontal_offset);
+            let tile_top = u64::from(location.vertical_offset);
+            let tile_right = tile_left + u64::from(extents.width());
+            let tile_bottom = tile_top + u64::from(extents.height());
+            if tile_left < u64::from(x) + u64::from(width)
+                && u64::from(x) < tile_right
+                && tile_top < u64::from(y) + u64::from(height)
+                && u64::from(y) < tile_bottom
+            {
+                item_ids.push(tile_item_id);
+            }
+        }
+        Ok(item_ids)
+    }
+
+    pub fn get_operating_points(&self, item_id: u32) -> Result<Vec<OperatingPoint>> {
+        self.get_item_by_image_id(item_id)?;
+        let oinf = match self
+            .get_property::<OperatingPointsInformationProperty>(PropertyType::OINF, item_id)?
+        {
+            Some(oinf) => oinf,
+            None => return Err(HeifError::NotApplicable),
+        };
+        Ok(oinf
+            .operating_points()
+            .iter()
+            .map(|op| OperatingPoint {
+                output_layer_set_idx: op.output_layer_set_idx,
+                max_temporal_id: op.max_temporal_id,
+                layer_ids: op.layers.iter().map(|l| l.layer_id).collect(),
+                output_layer_ids: op
+                    .layers
+                    .iter()
+                    .filter(|l| l.is_output_layer)
+                    .map(|l| l.layer_id)
+                    .collect(),
+                max_pic_width: op.max_pic_width,
+                max_pic_height: op.max_pic_height,
+            })
+            .collect())
+    }
+
+    pub fn get_target_output_layer_set(&self, item_id: u32) -> Result<u16> {
+        self.get_item_by_image_id(item_id)?;
+        match self.get_property::<TargetOlsProperty>(PropertyType::TOLS, item_id)? {
+            Some(tols) => Ok(tols.target_ols_idx()),
+            None => Err(HeifError::NotApplicable),
+        }
+    }
+
+    // The nuh_layer_id values carried by an item. A plain hvc1 item without
+    // linf only holds the base layer.
+    pub fn get_layer_ids(&self, item_id: u32) -> Result<Vec<u8>> {
+        self.get_layer_ids_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn get_layer_ids_in_context(&self, context_id: u32, item_id: u32) -> Result<Vec<u8>> {
+        let item_type = self
+            .get_item_in_context(context_id, item_id)?
+            .item_type()
+            .to_string();
+        if let Some(linf) = self.get_property_in_context::<LayerInformationProperty>(
+            context_id,
+            PropertyType::LINF,
+            item_id,
+        )? {
+            return Ok(linf.layers().iter().map(|l| l.layer_id).collect());
+        }
+        match item_type.as_str() {
+            "hvc1" => Ok(vec![0]),
+            "lhv1" => Err(HeifError::FileHeader),
+            _ => Err(HeifError::NotApplicable),
+        }
+    }
+
+    // Collects the items of the layered image item_id belongs to which are
+    // needed for the output layer set target_ols_idx. Parameter sets of all
+    // selected items come first in layer order, followed by the item data.
+    pub fn get_layered_item_data_with_decoder_parameters(
+        &self,
+        item_id: u32,
+        target_ols_idx: u16,
+    ) -> Result<Vec<Vec<u8>>> {
+        self.get_layered_item_data_with_decoder_parameters_in_context(
+            self.root_context_id(),
+            item_id,
+            target_ols_idx,
+        )
+    }
+
+    pub fn get_layered_item_data_with_decoder_parameters_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+        target_ols_idx: u16,
+    ) -> Result<Vec<Vec<u8>>> {
+        let iprp = self.meta_box(context_id)?.item_properties_box();
+        let oinf_index = iprp.find_property_index(PropertyType::OINF, item_id);
+        let oinf = match self.get_property_in_context::<OperatingPointsInformationProperty>(
+            context_id,
+            PropertyType::OINF,
+            item_id,
+        )? {
+            Some(oinf) => oinf,
+            None => return Err(HeifError::NotApplicable),
+        };
+        let operating_point = match oinf.operating_point_by_ols_idx(target_ols_idx) {
+            Some(op) => op,
+            None => return Err(HeifError::NotApplicable),
+        };
+        let required_layers: Vec<u8> = operating_point.layers.iter().map(|l| l.layer_id).collect();
+
+        let mut selected = Vec::new();
+        for candidate in self.image_item_ids_in_context(context_id)? {
+            let item_type = self
+                .get_item_in_context(context_id, candidate)?
+                .item_type()
+                .to_string();
+            if item_type != "hvc1" && item_type != "lhv1" {
+                continue;
+            }
+            if iprp.find_property_index(PropertyType::OINF, candidate) != oinf_index {
+                continue;
+            }
+            let layer_ids = self.get_layer_ids_in_context(context_id, candidate)?;
+            if layer_ids.iter().any(|id| required_layers.contains(id)) {
+                let lowest_layer = layer_ids.iter().min().copied().unwrap_or(0);
+                selected.push((lowest_layer, candidate, layer_ids));
+            }
+        }
+        for layer_id in &required_layers {
+            if !selected.iter().any(|(_, _, ids)| ids.contains(layer_id)) {
+                return Err(HeifError::FileHeader);
+            }
+        }
+        selected.sort_by_key(|(lowest_layer, _, _)| *lowest_layer);
+
+        let mut data_buf = Vec::new();
+        for (_, selected_item_id, _) in &selected {
+            if self.is_protected_in_context(context_id, *selected_item_id)? {
+                return Err(HeifError::ProtectedItem);
+            }
+            for config in self
+                .get_decoder_parameter_sets_in_context(context_id, *selected_item_id)?
+                .decoder_specific_info
+            {
+                data_buf.push(config.dec_spec_info_data);
+            }
+        }
+        for (_, selected_item_id, _) in &selected {
+            data_buf.push(self.get_item_data_in_context(context_id, *selected_item_id, true)?);
+        }
+        Ok(data_buf)
+    }
+
+    // Decodes an uncompressed (unci) image item into a pixel buffer.
+    pub fn get_uncompressed_image(&self, item_id: u32, layout: PixelLayout) -> Result<PixelBuffer> {
+        self.get_uncompressed_image_in_context(self.root_context_id(), item_id, layout)
+    }
+
+    pub fn get_uncompressed_image_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+        layout: PixelLayout,
+    ) -> Result<PixelBuffer> {
+        if *self.get_item_in_context(context_id, item_id)?.item_type() != "unci" {
+            return Err(HeifError::UnsupportedCodeType);
+        }
+        if self.is_protected_in_context(context_id, item_id)? {
+            return Err(HeifError::ProtectedItem);
+        }
+        let uncc = match self.get_property_in_context::<UncompressedFrameConfigBox>(
+            context_id,
+            PropertyType::UNCC,
+            item_id,
+        )? {
+            Some(uncc) => uncc,
+            None => return Err(HeifError::FileHeader),
+        };
+        let component_types = match self.get_property_in_context::<ComponentDefinitionBox>(
+            context_id,
+            PropertyType::CMPD,
+            item_id,
+        )? {
+            Some(cmpd) => cmpd.components().iter().map(|c| c.component_type).collect(),
+            None => match uncc.profile_component_types() {
+                Some(types) => types,
+                None => return Err(HeifError::FileHeader),
+            },
+        };
+        let extents = match self.get_property_in_context::<ImageSpatialExtentsProperty>(
+            context_id,
+            PropertyType::ISPE,
+            item_id,
+        )? {
+            Some(ispe) => ispe,
+            None => return Err(HeifError::FileHeader),
+        };
+        let data = self.get_item_data_in_context(context_id, item_id, false)?;
+        decode_uncompressed_image(
+            &data,
+            &component_types,
+            uncc,
+            extents.width(),
+            extents.height(),
+            layout,
+        )
+    }
+
+    pub fn get_pixel_aspect_ratio(&self, item_id: u32) -> Result<PixelAspectRatio> {
+        self.get_pixel_aspect_ratio_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn get_pixel_aspect_ratio_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+    ) -> Result<PixelAspectRatio> {
+        self.get_item_in_context(context_id, item_id)?;
+        match self.get_property_in_context::<PixelAspectRatioProperty>(
+            context_id,
+            PropertyType::PASP,
+            item_id,
+        )? {
+            Some(pasp) => Ok(PixelAspectRatio {
+                relative_width: pasp.h_spacing(),
+                relative_height: pasp.v_spacing(),
+            }),
+            None => Err(HeifError::NotApplicable),
+        }
+    }
+
+    // The size item_id is shown at: its size after the transformative
+    // properties, stretched so that non-square pixels described by pasp
+    // become square.
+    pub fn display_size(&self, item_id: u32) -> Result<(u32, u32)> {
+        self.display_size_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn display_size_in_context(&self, context_id: u32, item_id: u32) -> Result<(u32, u32)> {
+        let resolved = self.resolve_identity_item_in_context(context_id, item_id)?;
+        let source_item_id = resolved.source_item_id;
+        let (width, height) = (
+            self.width_in_context(context_id, source_item_id)?,
+            self.height_in_context(context_id, source_item_id)?,
+        );
+        if width == 0 || height == 0 {
+            return Err(HeifError::FileHeader);
+        }
+        let (mut width, mut height) = transformed_size(width, height, &resolved.transforms)?;
+        let pasp = match self.get_pixel_aspect_ratio_in_context(context_id, source_item_id) {
+            Err(HeifError::NotApplicable) => return Ok((width, height)),
+            pasp => pasp?,
+        };
+        if pasp.relative_width == 0 || pasp.relative_height == 0 {
+            return Err(HeifError::Unknown("pasp has a zero spacing"));
+        }
+        // pasp describes the coded pixels, so a quarter turn swaps it.
+        let quarter_turns = resolved
+            .transforms
+            .iter()
+            .filter(|t| matches!(t, ImageTransform::Rotate(r) if r.angle % 180 == 90))
+            .count();
+        let (h_spacing, v_spacing) = if quarter_turns % 2 == 1 {
+            (pasp.relative_height, pasp.relative_width)
+        } else {
+            (pasp.relative_width, pasp.relative_height)
+        };
+        let stretch = |size: u32, n: u32, d: u32| {
+            ((u64::from(size) * u64::from(n) + u64::from(d) / 2) / u64::from(d)) as u32
+        };
+        if h_spacing > v_spacing {
+            width = stretch(width, h_spacing, v_spacing);
+        } else if v_spacing > h_spacing {
+            height = stretch(height, v_spacing, h_spacing);
+        }
+        Ok((width, height))
+    }
+
+    // The transformative properties (clap, irot, imir, iscl) of an item in the
+    // order they are associated with it, which is the order to apply them.
+    pub fn get_transforms(&self, item_id: u32) -> Result<Vec<ImageTransform>> {
+        self.get_transforms_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn get_transforms_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+    ) -> Result<Vec<ImageTransform>> {
+        self.get_item_in_context(context_id, item_id)?;
+        let iprp = self.meta_box(context_id)?.item_properties_box();
+        let mut transforms = Vec::new();
+        for info in iprp.get_item_properties(item_id)? {
+            let property = match iprp.property_by_index(info.index) {
+                Some(p) => p.as_any(),
+                None => continue,
+            };
+            if let Some(clap) = property.downcast_ref::<CleanApertureProperty>() {
+                transforms.push(ImageTransform::CleanAperture(CleanAperture {
+                    width_n: clap.width_n(),
+                    width_d: clap.width_d(),
+                    height_n: clap.height_n(),
+                    height_d: clap.height_d(),
+                    horizontal_offset_n: clap.horizontal_offset_n(),
+                    horizontal_offset_d: clap.horizontal_offset_d(),
+                    vertical_offset_n: clap.vertical_offset_n(),
+                    vertical_offset_d: clap.vertical_offset_d(),
+                }));
+            } else if let Some(irot) = property.downcast_ref::<ImageRotationProperty>() {
+                transforms.push(ImageTransform::Rotate(Rotate {
+                    angle: irot.angle_in_degrees(),
+                }));
+            } else if let Some(imir) = property.downcast_ref::<ImageMirrorProperty>() {
+                transforms.push(ImageTransform::Mirror(Mirror {
+                    horizontal_axis: imir.axis() == 1,
+                }));
+            } else if let Some(iscl) = property.downcast_ref::<ImageScalingProperty>() {
+                transforms.push(ImageTransform::Scale(ImageScaling {
+                    width_n: iscl.target_width_numerator(),
+                    width_d: iscl.target_width_denominator(),
+                    height_n: iscl.target_height_numerator(),
+                    height_d: iscl.target_height_denominator(),
+                }));
+            }
+        }
+        Ok(transforms)
+    }
+
+    // Follows the dimg references of iden items down to the first item which
+    // is not an iden. The transforms of that item come first, followed by
+    // those of each iden item on the way back up to item_id.
+    pub fn resolve_identity_item(&self, item_id: u32) -> Result<ResolvedImage> {
+        self.resolve_identity_item_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn resolve_identity_item_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+    ) -> Result<ResolvedImage> {
+        let mut derivation_chain = IdVec::new();
+        let mut current = item_id;
+        while *self.get_item_in_context(context_id, current)?.item_type() == "iden" {
+            if derivation_chain.contains(&current) {
+                return Err(HeifError::Unknown("iden items reference each other"));
+            }
+            if derivation_chain.len() >= MAX_DERIVATION_DEPTH {
+                return Err(HeifError::Unknown("iden derivation chain is too deep"));
+            }
+            derivation_chain.push(current);
+            let inputs = self.get_referenced_from_item_list_by_type_in_context(
+                context_id,
+                current,
+                "dimg".parse().unwrap(),
+            )?;
+            if inputs.len() != 1 {
+                return Err(HeifError::FileHeader);
+            }
+            current = inputs[0];
+        }
+        let mut transforms = self.get_transforms_in_context(context_id, current)?;
+        for iden_item_id in derivation_chain.iter().rev() {
+            transforms.append(&mut self.get_transforms_in_context(context_id, *iden_item_id)?);
+        }
+        Ok(ResolvedImage {
+            source_item_id: current,
+            derivation_chain,
+            transforms,
+        })
+    }
+
+    // Decoders are looked up by the item type of the coded images, e.g. hvc1.
+    pub fn register_decoder(&mut self, code_type: &str, decoder: Box<dyn ImageDecoder>) {
+        self.decoders.register(code_type, decoder);
+    }
+
+    // Items whose dref entry points outside the file are read through the
+    // resolver, and fail with NoDataResolver without one.
+    pub fn set_data_resolver(&mut self, resolver: Box<dyn DataResolver>) {
+        self.data_references.set_resolver(resolver);
+    }
+
+    // The item types decode_image can reconstruct with the registered
+    // decoders, and the properties it applies.
+    pub fn capabilities(&self) -> Capabilities {
+        let mut item_types: HashSet<String> = self.decoders.code_types().into_iter().collect();
+        for item_type in ["grid", "iden", "iovl", "unci"].iter() {
+            item_types.insert(item_type.to_string());
+        }
+        let properties = [
+            "auxC", "clap", "cmpd", "colr", "hvcC", "imir", "irot", "iscl", "ispe", "lhvC", "pixi",
+            "uncC", "vvcC",
+        ]
+        .iter()
+        .map(|p| p.to_string())
+        .collect();
+        Capabilities {
+            item_types,
+            properties,
+        }
+    }
+
+    // The item to show in place of the primary item: the primary item if
+    // it can be displayed, otherwise the first displayable alternative of
+    // an altr group holding it. An item is displayable when it and all of
+    // its inputs are unprotected, of a supported type and have no
+    // unsupported essential property.
+    pub fn displayable_primary(&self, capabilities: &Capabilities) -> Result<Option<u32>> {
+        let primary_item_id = match self.primary_item_id {
+            Some(id) => id,
+            None => return Err(HeifError::FileHeader),
+        };
+        let mut candidates = vec![primary_item_id];
+        for group in self.entity_groups_containing(primary_item_id)? {
+            if group.group_type != EntityGroupType::Alternatives {
+                continue;
+            }
+            for entity_id in group.entity_ids {
+                if !candidates.contains(&entity_id) {
+                    candidates.push(entity_id);
+                }
+            }
+        }
+        for item_id in candidates {
+            if self.is_displayable(item_id, capabilities)? {
+                return Ok(Some(item_id));
+            }
+        }
+        Ok(None)
+    }
+
+    fn is_displayable(&self, item_id: u32, capabilities: &Capabilities) -> Result<bool> {
+        // Alternatives may also be tracks, which are not items.
+        if !self.is_valid_item(item_id)? || !self.is_valid_image_item(item_id)? {
+            return Ok(false);
+        }
+        let decode_order = match self.derivation_graph()?.decode_order(item_id) {
+            Ok(order) => order,
+            Err(_) => return Ok(false),
+        };
+        let iprp = self.root_meta_box()?.item_properties_box();
+        for id in decode_order {
+            if self.is_protected(id)?
+                || !capabilities.supports_item_type(&self.get_item_type(id)?.to_string())
+            {
+                return Ok(false);
+            }
+            for property in iprp.get_item_properties(id)? {
+                if !property.is_essential {
+                    continue;
+                }
+                let property_type = match iprp.property_by_index(property.index) {
+                    Some(p) => p.box_type().to_string(),
+                    None => return Ok(false),
+                };
+                if !capabilities.supports_property(&property_type) {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    // The auxiliary image holding the alpha plane of item_id, if there is one.
+    pub fn alpha_item_id(&self, item_id: u32) -> Result<Option<u32>> {
+        self.alpha_item_id_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn alpha_item_id_in_context(&self, context_id: u32, item_id: u32) -> Result<Option<u32>> {
+        self.get_item_in_context(context_id, item_id)?;
+        for aux_item_id in self
+            .derivation_graph_in_context(context_id)?
+            .references_to(item_id, ReferenceType::Auxl)
+        {
+            if let Some(auxc) = self.get_property_in_context::<AuxiliaryTypeProperty>(
+                context_id,
+                PropertyType::AUXC,
+                aux_item_id,
+            )? {
+                if auxc.is_alpha() {
+                    return Ok(Some(aux_item_id));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    // The gain map of image_id, either from a tmap item whose first input is
+    // image_id or from an Apple HDR gain map auxiliary image. A tmap item id
+    // may also be given directly.
+    pub fn gain_map_for(&self, image_id: u32) -> Result<Option<GainMap>> {
+        self.gain_map_for_in_context(self.root_context_id(), image_id)
+    }
+
+    pub fn gain_map_for_in_context(
+        &self,
+        context_id: u32,
+        image_id: u32,
+    ) -> Result<Option<GainMap>> {
+        let graph = self.derivation_graph_in_context(context_id)?;
+        let mut tone_map_item_ids = graph.references_to(image_id, ReferenceType::Dimg);
+        if *self.get_item_type_in_context(context_id, image_id)? == "tmap" {
+            tone_map_item_ids.insert(0, image_id);
+        }
+        for tone_map_item_id in tone_map_item_ids {
+            if *self.get_item_type_in_context(context_id, tone_map_item_id)? != "tmap" {
+                continue;
+            }
+            let inputs = graph.references_from(tone_map_item_id, ReferenceType::Dimg);
+            if inputs.len() != 2 {
+                return Err(HeifError::FileHeader);
+            }
+            if tone_map_item_id != image_id && inputs[0] != image_id {
+                continue;
+            }
+            if self.is_protected_in_context(context_id, tone_map_item_id)? {
+                return Err(HeifError::ProtectedItem);
+            }
+            let metadata = parse_tone_map(self.get_item_data_in_context(
+                context_id,
+                tone_map_item_id,
+                false,
+            )?)?;
+            return Ok(Some(GainMap {
+                base_item_id: inputs[0],
+                gain_map_item_id: inputs[1],
+                tone_map_item_id: Some(tone_map_item_id),
+                metadata: Some(metadata),
+            }));
+        }
+        for aux_item_id in graph.references_to(image_id, ReferenceType::Auxl) {
+            if let Some(auxc) = self.get_property_in_context::<AuxiliaryTypeProperty>(
+                context_id,
+                PropertyType::AUXC,
+                aux_item_id,
+            )? {
+                if auxc.aux_type() == AUX_TYPE_APPLE_GAIN_MAP {
+                    return Ok(Some(GainMap {
+                        base_item_id: image_id,
+                        gain_map_item_id: aux_item_id,
+                        tone_map_item_id: None,
+                        metadata: None,
+                    }));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    // The entity groups of the root meta box, in file order.
+    pub fn entity_groups(&self) -> Result<Vec<EntityGroup>> {
+        self.entity_groups_in_context(self.root_context_id())
+    }
+
+    pub fn entity_groups_in_context(&self, context_id: u32) -> Result<Vec<EntityGroup>> {
+        self.meta_box(context_id)?
+            .group_list_box()
+            .entity_to_group_box_vector()
+            .iter()
+            .map(entity_group)
+            .collect()
+    }
+
+    pub fn entity_groups_containing(&self, entity_id: u32) -> Result<Vec<EntityGroup>> {
+        self.entity_groups_containing_in_context(self.root_context_id(), entity_id)
+    }
+
+    pub fn entity_groups_containing_in_context(
+        &self,
+        context_id: u32,
+        entity_id: u32,
+    ) -> Result<Vec<EntityGroup>> {
+        Ok(self
+            .entity_groups_in_context(context_id)?
+            .into_iter()
+            .filter(|group| group.entity_ids.contains(&entity_id))
+            .collect())
+    }
+
+    // The ster group holding image_id. Its first entity is the left view
+    // and its second the right view.
+    pub fn stereo_pair_for(&self, image_id: u32) -> Result<Option<StereoPair>> {
+        self.stereo_pair_for_in_context(self.root_context_id(), image_id)
+    }
+
+    pub fn stereo_pair_for_in_context(
+        &self,
+        context_id: u32,
+        image_id: u32,
+    ) -> Result<Option<StereoPair>> {
+        for group in self.entity_groups_containing_in_context(context_id, image_id)? {
+            if group.group_type != EntityGroupType::StereoPair {
+                continue;
+            }
+            if group.entity_ids.len() != 2 {
+                return Err(HeifError::FileHeader);
+            }
+            return Ok(Some(StereoPair {
+                group_id: group.group_id,
+                left: group.entity_ids[0],
+                right: group.entity_ids[1],
+            }));
+        }
+        Ok(None)
+    }
+
+    // A prem reference from item_id to its alpha image means the colour
+    // samples of item_id have been multiplied by alpha.
+    pub fn is_alpha_premultiplied(&self, item_id: u32) -> Result<bool> {
+        self.is_alpha_premultiplied_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn is_alpha_premultiplied_in_context(&self, context_id: u32, item_id: u32) -> Result<bool> {
+        Ok(match self.alpha_item_id_in_context(context_id, item_id)? {
+            Some(alpha_item_id) => self
+                .derivation_graph_in_context(context_id)?
+                .references_from(item_id, ReferenceType::Prem)
+                .contains(&alpha_item_id),
+            None => false,
+        })
+    }
+
+    // Reconstructs the image of item_id: decodes its coded images with the
+    // registered decoders, assembles grids, composites overlays, applies the
+    // transformative properties and attaches the alpha plane.
+    pub fn decode_image(&self, item_id: u32) -> Result<PixelBuffer> {
+        self.decode_image_in_context(self.root_context_id(), item_id)
+    }
+
+    // Track meta boxes may hold a cover image of their own.
+    pub fn decode_image_in_context(&self, context_id: u32, item_id: u32) -> Result<PixelBuffer> {
+        self.decode_image_with_alpha_in_context(context_id, item_id, AlphaHandling::Keep)
+    }
+
+    pub fn decode_image_with_alpha(
+        &self,
+        item_id: u32,
+        alpha_handling: AlphaHandling,
+    ) -> Result<PixelBuffer> {
+        self.decode_image_with_alpha_in_context(self.root_context_id(), item_id, alpha_handling)
+    }
+
+    pub fn decode_image_with_alpha_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+        alpha_handling: AlphaHandling,
+    ) -> Result<PixelBuffer> {
+        self.derivation_graph_in_context(context_id)?
+            .decode_order(item_id)?;
+        let image = self.reconstruct_image(context_id, item_id)?;
+        let alpha_item_id = match self.alpha_item_id_in_context(context_id, item_id)? {
+            Some(id) => id,
+            None => return Ok(image),
+        };
+        let image = attach_alpha(image, &self.reconstruct_image(context_id, alpha_item_id)?)?;
+        if alpha_handling == AlphaHandling::Unpremultiply
+            && self.is_alpha_premultiplied_in_context(context_id, item_id)?
+        {
+            unpremultiply_alpha(
+                &image,
+                self.nclx_color_information(context_id, item_id)?
+                    .full_range_flag,
+            )
+        } else {
+            Ok(image)
+        }
+    }
+
+    // The colr properties of an item in association order.
+    pub fn get_color_information(&self, item_id: u32) -> Result<Vec<ColorInformation>> {
+        self.get_color_information_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn get_color_information_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+    ) -> Result<Vec<ColorInformation>> {
+        self.get_item_in_context(context_id, item_id)?;
+        let iprp = self.meta_box(context_id)?.item_properties_box();
+        let mut infos = Vec::new();
+        for info in iprp.get_item_properties(item_id)? {
+            if info.property_type != PropertyType::COLR {
+                continue;
+            }
+            if let Some(colr) = iprp
+                .property_by_index(info.index)
+                .and_then(|p| p.as_any().downcast_ref::<ColorInformationProperty>())
+            {
+                infos.push(ColorInformation {
+                    color_type: colr.color_type().clone(),
+                    color_primaries: colr.color_primaries(),
+                    transfer_characteristics: colr.transfer_characteristics(),
+                    matrix_coefficients: colr.matrix_coefficients(),
+                    full_range_flag: colr.full_range_flag(),
+                    icc_profile: colr.icc_profile().clone(),
+                });
+            }
+        }
+        Ok(infos)
+    }
+
+    pub fn get_content_light_level(&self, item_id: u32) -> Result<ContentLightLevel> {
+        self.get_content_light_level_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn get_content_light_level_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+    ) -> Result<ContentLightLevel> {
+        self.get_item_in_context(context_id, item_id)?;
+        match self.get_property_in_context::<ContentLightLevelProperty>(
+            context_id,
+            PropertyType::CLLI,
+            item_id,
+        )? {
+            Some(clli) => Ok(ContentLightLevel {
+                max_content_light_level: clli.max_content_light_level(),
+                max_pic_average_light_level: clli.max_pic_average_light_level(),
+            }),
+            None => Err(HeifError::NotApplicable),
+        }
+    }
+
+    pub fn get_mastering_display_color_volume(
+        &self,
+        item_id: u32,
+    ) -> Result<MasteringDisplayColorVolume> {
+        self.get_mastering_display_color_volume_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn get_mastering_display_color_volume_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+    ) -> Result<MasteringDisplayColorVolume> {
+        self.get_item_in_context(context_id, item_id)?;
+        let mdcv = match self.get_property_in_context::<MasteringDisplayColorVolumeProperty>(
+            context_id,
+            PropertyType::MDCV,
+            item_id,
+        )? {
+            Some(mdcv) => mdcv,
+            None => return Err(HeifError::NotApplicable),
+        };
+        // Stored in green, blue, red order.
+        let primaries = mdcv.display_primaries();
+        Ok(MasteringDisplayColorVolume {
+            display_primaries: [
+                chromaticity(primaries[2].0.into(), primaries[2].1.into()),
+                chromaticity(primaries[0].0.into(), primaries[0].1.into()),
+                chromaticity(primaries[1].0.into(), primaries[1].1.into()),
+            ],
+            white_point: chromaticity(mdcv.white_point().0.into(), mdcv.white_point().1.into()),
+            max_luminance: f64::from(mdcv.max_display_mastering_luminance()) * 0.0001,
+            min_luminance: f64::from(mdcv.min_display_mastering_luminance()) * 0.0001,
+        })
+    }
+
+    pub fn get_content_color_volume(&self, item_id: u32) -> Result<ContentColorVolume> {
+        self.get_content_color_volume_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn get_content_color_volume_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+    ) -> Result<ContentColorVolume> {
+        self.get_item_in_context(context_id, item_id)?;
+        let cclv = match self.get_property_in_context::<ContentColorVolumeProperty>(
+            context_id,
+            PropertyType::CCLV,
+            item_id,
+        )? {
+            Some(cclv) => cclv,
+            None => return Err(HeifError::NotApplicable),
+        };
+        let luminance = |value: Option<u32>| value.map(|v| f64::from(v) * 0.000_000_1);
+        // Stored in green, blue, red order, like mdcv.
+        Ok(ContentColorVolume {
+            primaries: cclv.primaries().map(|p| {
+                [
+                    chromaticity(p[2].0.into(), p[2].1.into()),
+                    chromaticity(p[0].0.into(), p[0].1.into()),
+                    chromaticity(p[1].0.into(), p[1].1.into()),
+                ]
+            }),
+            min_luminance: luminance(cclv.min_luminance_value()),
+            max_luminance: luminance(cclv.max_luminance_value()),
+            avg_luminance: luminance(cclv.avg_luminance_value()),
+        })
+    }
+
+    pub fn get_ambient_viewing_environment(
+        &self,
+        item_id: u32,
+    ) -> Result<AmbientViewingEnvironment> {
+        self.get_ambient_viewing_environment_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn get_ambient_viewing_environment_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+    ) -> Result<AmbientViewingEnvironment> {
+        self.get_item_in_context(context_id, item_id)?;
+        match self.get_property_in_context::<AmbientViewingEnvironmentProperty>(
+            context_id,
+            PropertyType::AMVE,
+            item_id,
+        )? {
+            Some(amve) => Ok(AmbientViewingEnvironment {
+                ambient_illuminance: f64::from(amve.ambient_illuminance()) * 0.0001,
+                ambient_light: chromaticity(
+                    amve.ambient_light_x().into(),
+                    amve.ambient_light_y().into(),
+                ),
+            }),
+            None => Err(HeifError::NotApplicable),
+        }
+    }
+
+    // The aebr, wbbr, fobr or afbr property of an image of a bracketing
+    // group.
+    pub fn get_bracket_value(&self, item_id: u32) -> Result<BracketValue> {
+        self.get_bracket_value_in_context(self.root_context_id(), item_id)
+    }
+
+    pub fn get_bracket_value_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+    ) -> Result<BracketValue> {
+        self.get_item_in_context(context_id, item_id)?;
+        if let Some(aebr) = self.get_property_in_context::<AutoExposureProperty>(
+            context_id,
+            PropertyType::AEBR,
+            item_id,
+        )? {
+            return Ok(BracketValue::AutoExposure {
+                exposure_step: aebr.exposure_step(),
+                exposure_numerator: aebr.exposure_numerator(),
+            });
+        }
+        if let Some(wbbr) = self.get_property_in_context::<WhiteBalanceProperty>(
+            context_id,
+            PropertyType::WBBR,
+            item_id,
+        )? {
+            return Ok(BracketValue::WhiteBalance {
+                blue_amber: wbbr.blue_amber(),
+                green_magenta: wbbr.green_magenta(),
+            });
+        }
+        if let Some(fobr) =
+            self.get_property_in_context::<FocusProperty>(context_id, PropertyType::FOBR, item_id)?
+        {
+            return Ok(BracketValue::Focus {
+                focus_distance_numerator: fobr.focus_distance_numerator(),
+                focus_distance_denominator: fobr.focus_distance_denominator(),
+            });
+        }
+        if let Some(afbr) = self.get_property_in_context::<FlashExposureProperty>(
+            context_id,
+            PropertyType::AFBR,
+            item_id,
+        )? {
+            return Ok(BracketValue::FlashExposure {
+                flash_exposure_numerator: afbr.flash_exposure_numerator(),
+                flash_exposure_denominator: afbr.flash_exposure_denominator(),
+            });
+        }
+        Err(HeifError::NotApplicable)
+    }
+
+    // Decodes item_id and converts it to RGB with the nclx colour information
+    // of the item. Derived images without their own nclx use that of their
+    // inputs.
+    pub fn decode_image_rgb(
+        &self,
+        item_id: u32,
+        conversion: &RgbConversion,
+    ) -> Result<PixelBuffer> {
+        self.decode_image_rgb_in_context(self.root_context_id(), item_id, conversion)
+    }
+
+    pub fn decode_image_rgb_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+        conversion: &RgbConversion,
+    ) -> Result<PixelBuffer> {
+        let image =
+            self.decode_image_with_alpha_in_context(context_id, item_id, conversion.alpha)?;
+        convert_to_rgb(
+            &image,
+            &self.nclx_color_information(context_id, item_id)?,
+            conversion,
+        )
+    }
+
+    // The nclx colour information of item_id, or of the nearest of its
+    // inputs which has one.
+    fn nclx_color_information(&self, context_id: u32, item_id: u32) -> Result<ColorInformation> {
+        for id in self
+            .derivation_graph_in_context(context_id)?
+            .decode_order(item_id)?
+            .iter()
+            .rev()
+        {
+            if let Some(color) = self
+                .get_color_information_in_context(context_id, *id)?
+                .into_iter()
+                .find(|info| info.color_type == "nclx")
+            {
+                return Ok(color);
+            }
+        }
+        Ok(ColorInformation::default())
+    }
+
+    pub fn derivation_graph(&self) -> Result<&DerivationGraph> {
+        self.check_initialized(
+            &self
+                .file_properties
+                .root_meta_box_properties
+                .derivation_graph,
+        )
+    }
+
+    fn derivation_graph_in_context(&self, context_id: u32) -> Result<&DerivationGraph> {
+        Ok(&self.meta_box_properties(context_id)?.derivation_graph)
+    }
+
+    pub fn get_master_image_ids(&self) -> Result<IdVec> {
+        self.get_master_image_ids_in_context(self.root_context_id())
+    }
+
+    pub fn get_master_image_ids_in_context(&self, context_id: u32) -> Result<IdVec> {
+        Ok(master_image_ids(
+            self.meta_box(context_id)?,
+            &self.meta_box_properties(context_id)?.derivation_graph,
+        ))
+    }
+}
+
+// load
+impl HeifReader {
+    pub fn load(&mut self, file_path: &str) -> Result<()> {
+        let mut file = File::open(file_path).map_err(|_| HeifError::FileOpen)?;
+        self.reset();
+
+        self.stream = BitStream::from(&mut file)?;
+        self.read_stream()?;
+
+        self.file_information.root_meta_box_information =
+            self.convert_meta_box_information(&self.file_properties.root_meta_box_properties)?;
+        self.file_information.moov_meta_box_information =
+            match &self.file_properties.moov_properties {
+                Some(moov) => Some(self.convert_meta_box_information(&moov.meta_box_properties)?),
+                None => None,
+            };
+        self.file_information.track_information = self.convert_track_information();
+        self.file_information.features = self.file_properties.file_feature.feature_mask();
+        self.file_information.movie_timescale = self.file_properties.movie_timescale;
+
+        println!(">> fileProperties: {:?}\n", self.file_properties);
+        println!(">> decoderCodeTypeMap: {:?}\n", self.decoder_code_type_map);
+        println!(">> parameterSetMap: {:?}\n", self.parameter_set_map);
+        println!(
+            ">> imageToParameterSetMap: {:?}\n",
+            self.image_to_parameter_set_map
+        );
+        println!(">> ftyp: {:?}\n", self.ftyp);
+        println!(">> fileInformation: {:?}\n", self.file_information);
+        println!(">> metaboxMap: {:?}\n", self.metabox_map);
+        println!(">> metaboxInfo: {:?}\n", self.metabox_info);
+        println!(">> trackInfo: {:?}\n", self.track_info);
+
+        Ok(())
+    }
+
+    fn get_item_type(&self, item_id: u32) -> Result<&Byte4> {
+        self.get_item_type_in_context(self.root_context_id(), item_id)
+    }
+
     fn reset(&mut self) {
         self.state = State::Uninitialized;
         self.stream.clear();
@@ -324,6 +2086,7 @@ impl HeifReader {
         self.metabox_info.clear();
         self.matrix.clear();
         self.track_info.clear();
+        self.movie_box = MovieBox::default();
     }
 
     fn read_stream(&mut self) -> Result<()> {
@@ -334,6 +2097,7 @@ impl HeifReader {
         let mut movie_found = false;
 
         while !self.stream.is_eof() {
+            let box_offset = self.stream.get_byte_offset() as u64;
             let header = BoxHeader::from_stream(&mut self.stream)?;
             let box_type = header.box_type();
             match box_type.to_string().as_str() {
@@ -358,6 +2122,15 @@ impl HeifReader {
                     movie_found = true;
                     self.read_moov(header)?;
                 }
+                "moof" => {
+                    if !movie_found {
+                        return Err(HeifError::FileHeader);
+                    }
+                    self.read_moof(header, box_offset)?;
+                }
+                "mfra" => {
+                    self.read_mfra(header)?;
+                }
                 "mdat" | "free" | "skip" => {
                     self.stream.skip_bytes(header.body_size() as usize)?;
                 }
@@ -375,6 +2148,121 @@ impl HeifReader {
         Ok(())
     }
 
+    // Appends the samples of each track fragment to its track.
+    fn read_moof(&mut self, header: BoxHeader, moof_offset: u64) -> Result<()> {
+        let mut ex = self.stream.extract_from(&header)?;
+        let moof = MovieFragmentBox::from_stream_header(&mut ex, header)?;
+        let mut data_end = moof_offset;
+        for (i, traf) in moof.track_fragment_boxes().iter().enumerate() {
+            let tfhd = traf.track_fragment_header_box();
+            let track_id = tfhd.track_id();
+            let track_box = match self
+                .movie_box
+                .tracks()
+                .iter()
+                .find(|track| track.track_header_box().track_id() == track_id)
+            {
+                Some(track_box) => track_box,
+                None => return Err(HeifError::InvalidSequenceID),
+            };
+            let trex = self
+                .movie_box
+                .movie_extends_box()
+                .and_then(|mvex| mvex.track_extends_box(track_id));
+            // Without an explicit base, the first track fragment starts at
+            // the moof and the others where the previous one's data ends.
+            let base_data_offset = match tfhd.base_data_offset() {
+                Some(offset) => offset,
+                None if i == 0 || tfhd.default_base_is_moof() => moof_offset,
+                None => data_end,
+            };
+            let track_info = match self.track_info.get_mut(&track_id) {
+                Some(track_info) => track_info,
+                None => return Err(HeifError::InvalidSequenceID),
+            };
+            let track = match self.file_properties.track_properties.get_mut(&track_id) {
+                Some(track) => track,
+                None => return Err(HeifError::InvalidSequenceID),
+            };
+            let decode_time = match track_info.samples.last() {
+                Some(last) => {
+                    last.decode_time
+                        + track.sample_properties[&last.decoding_order].sample_duration_ts as i64
+                }
+                None => 0,
+            };
+            let first_sample_id = track_info.samples.len() as u32;
+            let (samples, end) = extract_fragment_samples(
+                track_box,
+                trex,
+                traf,
+                base_data_offset,
+                first_sample_id,
+                decode_time,
+            )?;
+            data_end = end;
+            let positions = traf
+                .track_run_boxes()
+                .iter()
+                .enumerate()
+                .flat_map(|(j, trun)| {
+                    (0..trun.samples().len()).map(move |k| (j as u32 + 1, k as u32 + 1))
+                });
+            for ((sample, properties), (trun_number, sample_number)) in
+                samples.into_iter().zip(positions)
+            {
+                track_info.fragment_sample_ids.insert(
+                    (moof_offset, i as u32 + 1, trun_number, sample_number),
+                    sample.decoding_order,
+                );
+                track.max_sample_size = track.max_sample_size.max(properties.size);
+                track
+                    .sample_properties
+                    .insert(properties.sample_id, properties);
+                track_info.samples.push(sample);
+            }
+            let stbl = track_box
+                .media_box()
+                .media_information_box()
+                .sample_table_box();
+            add_fragment_sample_groups(
+                track,
+                stbl,
+                traf,
+                first_sample_id,
+                track_info.samples.len() as u32 - first_sample_id,
+            )?;
+        }
+        Ok(())
+    }
+
+    // Marks the samples which tfra boxes list as random access points. The
+    // entries locate samples by moof, traf, trun and sample, so the moof
+    // boxes must come first.
+    fn read_mfra(&mut self, header: BoxHeader) -> Result<()> {
+        let mut ex = self.stream.extract_from(&header)?;
+        let mfra = MovieFragmentRandomAccessBox::from_stream_header(&mut ex, header)?;
+        for tfra in mfra.track_fragment_random_access_boxes() {
+            let track_info = match self.track_info.get_mut(&tfra.track_id()) {
+                Some(track_info) => track_info,
+                None => continue,
+            };
+            for entry in tfra.entries() {
+                let sample_id = match track_info.fragment_sample_ids.get(&(
+                    entry.moof_offset,
+                    entry.traf_number,
+                    entry.trun_number,
+                    entry.sample_number,
+                )) {
+                    Some(sample_id) => *sample_id,
+                    None => continue,
+                };
+                track_info.samples[sample_id as usize].is_sync = true;
+            }
+        }
+        Ok(())
+    }
+
     fn read_ftyp(&mut self, header: BoxHeader) -> Result<()> {
         let mut ex = self.stream.extract_from(&header)?;
         self.ftyp = FileTypeBox::new(&mut ex, header)?;
@@ -383,21 +2271,8 @@ impl HeifReader {
 
     fn read_meta(&mut self, header: BoxHeader) -> Result<()> {
         let mut ex = self.stream.extract_from(&header)?;
-        let context_id = 0;
-        self.metabox_map
-            .insert(context_id, MetaBox::from_stream_header(&mut ex, header)?);
-        let metabox = &self.metabox_map[&context_id];
-
-        self.file_properties.root_meta_box_properties = extract_metabox_properties(&metabox);
-        self.metabox_info
-            .insert(context_id, self.extract_items(&metabox, context_id)?);
-
-        self.process_decoder_config_properties(context_id);
-
-        let master_image_ids = self.get_master_image_ids()?;
-        if let Some(m) = self.metabox_info.get_mut(&context_id) {
-            m.displayable_master_images = master_image_ids.len();
-        }
+        let metabox = MetaBox::from_stream_header(&mut ex, header)?;
+        self.file_properties.root_meta_box_properties = self.load_meta_box(0, metabox)?;
 
         for (id, item_feature) in &self
             .file_properties
@@ -411,12 +2286,182 @@ impl HeifReader {
         Ok(())
     }
 
+    // Registers a meta box under context_id and extracts its items.
+    fn load_meta_box(&mut self, context_id: u32, metabox: MetaBox) -> Result<MetaBoxProperties> {
+        self.metabox_map.insert(context_id, metabox);
+        let metabox = &self.metabox_map[&context_id];
+        let properties = extract_metabox_properties(metabox, context_id);
+        self.metabox_info
+            .insert(context_id, self.extract_items(metabox, context_id)?);
+        self.process_decoder_config_properties(context_id);
+
+        let master_image_count =
+            master_image_ids(&self.metabox_map[&context_id], &properties.derivation_graph).len();
+        if let Some(m) = self.metabox_info.get_mut(&context_id) {
+            m.displayable_master_images = master_image_count;
+        }
+        Ok(properties)
+    }
+
+    // Context 0 is the root meta box, the movie and track meta boxes take
+    // the following ids in file order.
+    fn next_context_id(&self) -> u32 {
+        self.metabox_map.keys().max().map_or(0, |id| *id) + 1
+    }
+
     fn read_moov(&mut self, header: BoxHeader) -> Result<()> {
         let mut ex = self.stream.extract_from(&header)?;
-        let movie_box = MovieBox::new(&mut ex, header)?;
+        let mut movie_box = MovieBox::new(&mut ex, header)?;
+        if let Some(metabox) = movie_box.take_meta_box() {
+            let moov_id = self.next_context_id();
+            self.file_properties.moov_properties = Some(MoovProperties {
+                moov_id,
+                meta_box_properties: self.load_meta_box(moov_id, metabox)?,
+            });
+        }
+        let track_meta_boxes: Vec<(u32, MetaBox)> = movie_box
+            .tracks_mut()
+            .iter_mut()
+            .filter_map(|track_box| {
+                let track_id = track_box.track_header_box().track_id();
+                track_box.take_meta_box().map(|metabox| (track_id, metabox))
+            })
+            .collect();
+        let movie_header_box = movie_box.movie_header_box();
+        self.file_properties.movie_timescale = movie_header_box.time_scale();
+        self.matrix = movie_header_box.matrix().clone();
+        for track_box in movie_box.tracks() {
+            let track_id = track_box.track_header_box().track_id();
+            if *track_box.media_box().handler_box().handler_type() == "pict" {
+                self.file_properties
+                    .file_feature
+                    .set_feature(FileFeatureEnum::HasImageSequence);
+            }
+            self.track_info
+                .insert(track_id, extract_track_info(track_box)?);
+            self.file_properties
+                .track_properties
+                .insert(track_id, extract_track_properties(track_box));
+        }
+        for (track_id, metabox) in track_meta_boxes {
+            let context_id = self.next_context_id();
+            let properties = self.load_meta_box(context_id, metabox)?;
+            if let Some(track) = self.file_properties.track_properties.get_mut(&track_id) {
+                track.meta_box_properties = Some(properties);
+            }
+        }
+        self.set_linked_track_features();
+        self.set_alternate_tracks();
+        self.movie_box = movie_box;
         Ok(())
     }
 
+    // Tracks sharing a non-zero alternate_group are alternatives of each
+    // other.
+    fn set_alternate_tracks(&mut self) {
+        let groups: Vec<(u32, u32)> = self
+            .file_properties
+            .track_properties
+            .values()
+            .map(|track| (track.track_id, track.alternate_group_id))
+            .collect();
+        for track in self.file_properties.track_properties.values_mut() {
+            if track.alternate_group_id == 0 {
+                continue;
+            }
+            let mut alternate_track_ids: IdVec = groups
+                .iter()
+                .filter(|(track_id, group_id)| {
+                    *group_id == track.alternate_group_id && *track_id != track.track_id
+                })
+                .map(|(track_id, _)| *track_id)
+                .collect();
+            alternate_track_ids.sort_unstable();
+            if !alternate_track_ids.is_empty() {
+                track
+                    .track_feature
+                    .set_feature(TrackFeatureEnum::HasAlternatives);
+            }
+            track.alternate_track_ids = alternate_track_ids;
+        }
+    }
+
+    // Marks the tracks which thmb and auxl track references point to.
+    fn set_linked_track_features(&mut self) {
+        let mut linked = Vec::new();
+        for track in self.file_properties.track_properties.values() {
+            for (reference_type, feature) in [
+                ("thmb", TrackFeatureEnum::HasLinkedThumbnailImageSequence),
+                ("auxl", TrackFeatureEnum::HasLinkedAuxiliaryImageSequence),
+            ] {
+                if let Some(track_ids) = track.reference_track_ids.get(reference_type) {
+                    linked.extend(track_ids.iter().map(|track_id| (*track_id, feature)));
+                }
+            }
+        }
+        for (track_id, feature) in linked {
+            if let Some(track) = self.file_properties.track_properties.get_mut(&track_id) {
+                track.track_feature.set_feature(feature);
+            }
+        }
+    }
+
+    fn convert_track_information(&self) -> Vec<TrackInformation> {
+        let mut track_ids: Vec<&u32> = self.file_properties.track_properties.keys().collect();
+        track_ids.sort();
+        track_ids
+            .into_iter()
+            .map(|track_id| {
+                let track = &self.file_properties.track_properties[track_id];
+                let mut sample_properties: Vec<&SampleProperties> =
+                    track.sample_properties.values().collect();
+                sample_properties.sort_by_key(|sample| sample.sample_id);
+                let mut reference_track_ids: Vec<Byte4ToIds> = track
+                    .reference_track_ids
+                    .iter()
+                    .map(|(box_type, track_ids)| Byte4ToIds {
+                        box_type: box_type.parse().unwrap(),
+                        track_ids: track_ids.clone(),
+                    })
+                    .collect();
+                reference_track_ids.sort_by_key(|ids| ids.box_type.to_u32());
+                TrackInformation {
+                    track_id: track.track_id,
+                    alternate_group_id: track.alternate_group_id,
+                    features: track.track_feature.feature_mask(),
+                    alternate_track_ids: track.alternate_track_ids.clone(),
+                    reference_track_ids,
+                    sample_groups: track.grouped_samples.clone(),
+                    sample_properties: sample_properties
+                        .into_iter()
+                        .map(|sample| SampleInformation {
+                            sample_id: sample.sample_id,
+                            sample_entry_type: sample.sample_entry_type.clone(),
+                            sample_description_index: sample.sample_description_index,
+                            sample_type: sample.sample_type,
+                            sample_duration_ts: sample.sample_duration_ts,
+                            sample_composition_offset_ts: sample.sample_composition_offset_ts,
+                            has_clap: sample.has_clap,
+                            has_auxi: sample.has_auxi,
+                            coding_constraints: sample.coding_constraints,
+                            size: sample.size,
+                        })
+                        .collect(),
+                    equivalences: track.equivalences.clone(),
+                    metadatas: track.metadatas.clone(),
+                    reference_samples: track.reference_samples.clone(),
+                    max_sample_size: track.max_sample_size,
+                    time_scale: track.time_scale,
+                    edit_list: track.edit_list.clone(),
+                    meta_box_information: track
+                        .meta_box_properties
+                        .as_ref()
+                        .and_then(|properties| self.convert_meta_box_information(properties).ok()),
+                }
+            })
+            .collect()
+    }
+
     fn extract_items(&self, metabox: &MetaBox, context_id: u32) -> Result<MetaBoxInfo> {
         let mut metabox_info = MetaBoxInfo::default();
         for item in metabox.item_info_box().item_info_list() {
@@ -435,15 +2480,23 @@ impl HeifReader {
                             rows: u32::from(image_grid.rows_minus_one) + 1,
                             output_width: image_grid.output_width,
                             output_height: image_grid.output_height,
-                            image_ids: self.get_referenced_from_item_list_by_type(
+                            image_ids: self.get_referenced_from_item_list_by_type_in_context(
+                                context_id,
                                 item.item_id(),
                                 "dimg".parse().unwrap(),
                             )?,
                         },
                     );
                 } else {
-                    // iovl
-                    unimplemented!("extract_items iovl {:?}", ex_stream);
+                    let image_ids = self.get_referenced_from_item_list_by_type_in_context(
+                        context_id,
+                        item.item_id(),
+                        "dimg".parse().unwrap(),
+                    )?;
+                    metabox_info.iovl_items.insert(
+                        item.item_id(),
+                        parse_image_overlay(&mut ex_stream, image_ids)?,
+                    );
                 }
             }
         }
@@ -457,8 +2510,21 @@ impl HeifReader {
         from_item_id: u32,
         reference_type: Byte4,
     ) -> Result<Vec<u32>> {
-        self.get_item_by_image_id(from_item_id)?;
-        let item_reference_box = self.root_meta_box()?.item_reference_box();
+        self.get_referenced_from_item_list_by_type_in_context(
+            self.root_context_id(),
+            from_item_id,
+            reference_type,
+        )
+    }
+
+    fn get_referenced_from_item_list_by_type_in_context(
+        &self,
+        context_id: u32,
+        from_item_id: u32,
+        reference_type: Byte4,
+    ) -> Result<Vec<u32>> {
+        self.get_item_in_context(context_id, from_item_id)?;
+        let item_reference_box = self.meta_box(context_id)?.item_reference_box();
         let references = item_reference_box.references_of_type(reference_type);
         let mut item_id_vec = IdVec::new();
         for reference in references {
@@ -473,7 +2539,9 @@ impl HeifReader {
     fn load_item_data(&self, metabox: &MetaBox, item_id: u32) -> Result<BitStream> {
         let mut past_references = LinkedList::new();
         let item_length = self.get_item_length(metabox, item_id, &mut past_references)?;
-        if !self.stream.has_bytes(item_length) {
+        if !self.stream.has_bytes(item_length)
+            && external_data_location(metabox, item_id)?.is_none()
+        {
             return Err(HeifError::FileHeader);
         }
         Ok(BitStream::new(self.read_item(
@@ -484,7 +2552,7 @@ impl HeifReader {
     }
 
     fn read_item(&self, metabox: &MetaBox, item_id: u32, max_size: usize) -> Result<Vec<u8>> {
-        if !self.is_valid_item(item_id)? {
+        if metabox.item_info_box().item_by_id(item_id).is_none() {
             return Err(HeifError::InvalidItemID);
         }
 
@@ -505,13 +2573,23 @@ impl HeifReader {
         let mut res = Vec::with_capacity(max_size);
         let mut total_length = 0;
         if version == 0 || (version >= 1 && construction_method == ConstructionMethod::FileOffset) {
+            let external_location = external_data_location(metabox, item_id)?;
             for extent in extent_list {
                 let offset = base_offset + extent.extent_offset;
                 if total_length + extent.extent_length > max_size {
                     return Err(HeifError::FileRead);
                 }
-                let slice = self.stream.slice(offset, extent.extent_length)?;
-                res.append(&mut slice.to_vec());
+                match external_location {
+                    Some(location) => res.append(&mut self.data_references.read(
+                        location,
+                        offset,
+                        extent.extent_length,
+                    )?),
+                    None => {
+                        let slice = self.stream.slice(offset, extent.extent_length)?;
+                        res.append(&mut slice.to_vec());
+                    }
+                }
                 total_length += extent.extent_length;
             }
         } else if version >= 1 && (construction_method == ConstructionMethod::IdatOffset) {
@@ -558,26 +2636,32 @@ impl HeifReader {
     }
 
     fn get_decoder_code_type(&self, item_id: u32) -> Result<&Byte4> {
-        if !self.is_valid_image_item(item_id)? {
+        self.get_decoder_code_type_in_context(self.root_context_id(), item_id)
+    }
+
+    fn get_decoder_code_type_in_context(&self, context_id: u32, item_id: u32) -> Result<&Byte4> {
+        if !is_image_item_type(self.get_item_in_context(context_id, item_id)?.item_type()) {
             return Err(HeifError::InvalidItemID);
         }
-        match self.decoder_code_type_map.get(&(
-            self.file_properties.root_meta_box_properties.context_id,
-            item_id,
-        )) {
+        match self.decoder_code_type_map.get(&(context_id, item_id)) {
             Some(ty) => Ok(ty),
             None => Err(HeifError::InvalidItemID),
         }
     }
 
     fn get_decoder_parameter_sets(&self, item_id: u32) -> Result<DecoderConfiguration> {
-        if !self.is_valid_image_item(item_id)? {
+        self.get_decoder_parameter_sets_in_context(self.root_context_id(), item_id)
+    }
+
+    fn get_decoder_parameter_sets_in_context(
+        &self,
+        context_id: u32,
+        item_id: u32,
+    ) -> Result<DecoderConfiguration> {
+        if !is_image_item_type(self.get_item_in_context(context_id, item_id)?.item_type()) {
             return Err(HeifError::InvalidItemID);
         }
-        let image_full_id = (
-            self.file_properties.root_meta_box_properties.context_id,
-            item_id,
-        );
+        let image_full_id = (context_id, item_id);
         let parameter_set_id = match self.image_to_parameter_set_map.get(&image_full_id) {
             Some(id) => id,
             None => return Err(HeifError::InvalidItemID),
@@ -602,24 +2686,107 @@ impl HeifReader {
         })
     }
 
+    fn reconstruct_image(&self, context_id: u32, item_id: u32) -> Result<PixelBuffer> {
+        let resolved = self.resolve_identity_item_in_context(context_id, item_id)?;
+        let source_item_id = resolved.source_item_id;
+        let item_type = self
+            .get_item_type_in_context(context_id, source_item_id)?
+            .to_string();
+        let mut image = match item_type.as_str() {
+            "grid" => {
+                let layout = self.grid_layout_in_context(context_id, source_item_id)?;
+                let tiles = layout
+                    .tiles
+                    .iter()
+                    .map(|tile| self.decode_coded_image(context_id, tile.item_id))
+                    .collect::<Result<Vec<_>>>()?;
+                assemble_grid(&layout, &tiles)?
+            }
+            "iovl" => {
+                let overlay = self.overlay_item_by_id_in_context(context_id, source_item_id)?;
+                let images = overlay
+                    .image_ids
+                    .iter()
+                    .map(|id| self.reconstruct_image(context_id, *id))
+                    .collect::<Result<Vec<_>>>()?;
+                composite_overlay(overlay, &images)?
+            }
+            _ => self.decode_coded_image(context_id, source_item_id)?,
+        };
+        for transform in &resolved.transforms {
+            image = apply_transform(&image, transform)?;
+        }
+        Ok(image)
+    }
+
+    fn decode_coded_image(&self, context_id: u32, item_id: u32) -> Result<PixelBuffer> {
+        if self.is_protected_in_context(context_id, item_id)? {
+            return Err(HeifError::ProtectedItem);
+        }
+        let item_type = self
+            .get_item_type_in_context(context_id, item_id)?
+            .to_string();
+        if item_type == "unci" {
+            return self.get_uncompressed_image_in_context(
+                context_id,
+                item_id,
+                PixelLayout::Planar,
+            );
+        }
+        let decoder = match self.decoders.get(&item_type) {
+            Some(decoder) => decoder,
+            None => return Err(HeifError::UnsupportedCodeType),
+        };
+        let mut decoder_config = Vec::new();
+        if self
+            .image_to_parameter_set_map
+            .contains_key(&(context_id, item_id))
+        {
+            let mut infos = self
+                .get_decoder_parameter_sets_in_context(context_id, item_id)?
+                .decoder_specific_info;
+            infos.sort_by_key(|info| info.dec_spec_info_type as u32);
+            decoder_config = infos
+                .into_iter()
+                .map(|info| info.dec_spec_info_data)
+                .collect();
+        }
+        let data = self.get_item_data_in_context(context_id, item_id, true)?;
+        let image = decoder.decode(&item_type, &decoder_config, &data)?;
+        if image.layout != PixelLayout::Planar {
+            return Err(HeifError::Unknown("decoder returned an interleaved image"));
+        }
+        Ok(image)
+    }
+
+    fn tile_parameter_set_map(&self, context_id: u32, item_id: u32) -> Option<&ParameterSetMap> {
+        self.image_to_parameter_set_map
+            .get(&(context_id, item_id))
+            .and_then(|id| self.parameter_set_map.get(id))
+    }
+
     fn process_decoder_config_properties(&mut self, context_id: u32) {
-        if let Some(iprp) = self.metabox_map.get(&context_id) {
-            let iprp = iprp.item_properties_box();
-            for image_properties in &self
-                .file_properties
-                .root_meta_box_properties
-                .item_features_map
-            {
-                let image_id = *image_properties.0;
+        if let Some(metabox) = self.metabox_map.get(&context_id) {
+            let iprp = metabox.item_properties_box();
+            for image_id in metabox.item_info_box().item_ids() {
                 let id: Id = (context_id, image_id);
                 let hvcc_index = iprp.find_property_index(PropertyType::HVCC, image_id);
+                let lhvc_index = iprp.find_property_index(PropertyType::LHVC, image_id);
+                let vvcc_index = iprp.find_property_index(PropertyType::VVCC, image_id);
                 let avcc_index = iprp.find_property_index(PropertyType::AVCC, image_id);
                 let mut config_index: Id;
                 if hvcc_index != 0 {
                     config_index = (context_id, hvcc_index);
+                } else if lhvc_index != 0 {
+                    config_index = (context_id, lhvc_index);
+                } else if vvcc_index != 0 {
+                    config_index = (context_id, vvcc_index);
                 } else if avcc_index != 0 {
-                    // TODO
-                    unimplemented!("avcc_index");
+                    // avcC is not parsed, so there are no parameter sets
+                    // and the item data cannot be converted.
+                    self.decoder_code_type_map
+                        .insert(id, "avc1".parse().unwrap());
+                    continue;
                 } else {
                     continue;
                 }
@@ -635,6 +2802,30 @@ impl HeifReader {
                         self.image_to_parameter_set_map.insert(id, config_index);
                         self.decoder_code_type_map
                             .insert(id, "hvc1".parse().unwrap());
+                    } else if let Some(lhevc_box) =
+                        prop.as_any().downcast_ref::<LHevcConfigurationBox>()
+                    {
+                        if !self.parameter_set_map.contains_key(&config_index) {
+                            self.parameter_set_map.insert(
+                                config_index,
+                                self.make_decoder_parameter_set_map(lhevc_box.config()),
+                            );
+                        }
+                        self.image_to_parameter_set_map.insert(id, config_index);
+                        self.decoder_code_type_map
+                            .insert(id, "lhv1".parse().unwrap());
+                    } else if let Some(vvc_box) =
+                        prop.as_any().downcast_ref::<VvcConfigurationBox>()
+                    {
+                        if !self.parameter_set_map.contains_key(&config_index) {
+                            self.parameter_set_map.insert(
+                                config_index,
+                                self.make_decoder_parameter_set_map(vvc_box.config()),
+                            );
+                        }
+                        self.image_to_parameter_set_map.insert(id, config_index);
+                        self.decoder_code_type_map
+                            .insert(id, "vvc1".parse().unwrap());
                     }
                 }
             }
@@ -659,18 +2850,19 @@ impl HeifReader {
         pm
     }
 
-    fn convert_root_meta_box_information(
+    fn convert_meta_box_information(
         &self,
         metabox_properties: &MetaBoxProperties,
     ) -> Result<MetaBoxInformation> {
         let mut item_informations = Vec::new();
+        let context_id = metabox_properties.context_id;
         for (id, item) in &metabox_properties.item_features_map {
-            let root_meta_info = self.root_meta_box_info()?;
-            if let Some(item_info) = root_meta_info.item_info_map.get(id) {
+            let meta_info = self.meta_box_info(context_id)?;
+            if let Some(item_info) = meta_info.item_info_map.get(id) {
                 let mut past_references = LinkedList::new();
-                let root_metabox = self.root_meta_box().unwrap();
+                let metabox = self.meta_box(context_id)?;
                 let size = self
-                    .get_item_length(root_metabox, *id, &mut past_references)
+                    .get_item_length(metabox, *id, &mut past_references)
                     .unwrap_or(0);
                 item_informations.push(ItemInformation {
                     item_id: *id,
@@ -699,7 +2891,7 @@ impl HeifReader {
         item_id: u32,
         past_references: &mut LinkedList<u32>,
     ) -> Result<usize> {
-        if !self.is_valid_item(item_id)? {
+        if metabox.item_info_box().item_by_id(item_id).is_none() {
             return Err(HeifError::InvalidItemID);
         }
 
@@ -771,25 +2963,86 @@ impl HeifReader {
 impl HeifReader {
     // Root MetaBox and MetaBoxInfo
 
+    fn root_context_id(&self) -> u32 {
+        self.file_properties.root_meta_box_properties.context_id
+    }
+
+    // Files with only a movie box have no root meta box.
     fn root_meta_box(&self) -> Result<&MetaBox> {
-        self.check_initialized(
-            &self.metabox_map[&self.file_properties.root_meta_box_properties.context_id],
-        )
+        self.meta_box(self.root_context_id())
     }
 
     fn root_meta_box_info(&self) -> Result<&MetaBoxInfo> {
-        self.check_initialized(
-            &self.metabox_info[&self.file_properties.root_meta_box_properties.context_id],
-        )
+        self.meta_box_info(self.root_context_id())
+    }
+
+    fn meta_box(&self, context_id: u32) -> Result<&MetaBox> {
+        self.check_initialized(())?;
+        self.metabox_map
+            .get(&context_id)
+            .ok_or(HeifError::NotApplicable)
+    }
+
+    fn meta_box_info(&self, context_id: u32) -> Result<&MetaBoxInfo> {
+        self.check_initialized(())?;
+        self.metabox_info
+            .get(&context_id)
+            .ok_or(HeifError::NotApplicable)
+    }
+
+    fn meta_box_properties(&self, context_id: u32) -> Result<&MetaBoxProperties> {
+        self.meta_box(context_id)?;
+        if context_id == self.root_context_id() {
+            return Ok(&self.file_properties.root_meta_box_properties);
+        }
+        if let Some(moov) = &self.file_properties.moov_properties {
+            if moov.moov_id == context_id {
+                return Ok(&moov.meta_box_properties);
+            }
+        }
+        self.file_properties
+            .track_properties
+            .values()
+            .filter_map(|track| track.meta_box_properties.as_ref())
+            .find(|properties| properties.context_id == context_id)
+            .ok_or(HeifError::NotApplicable)
+    }
+
+    fn get_property<T: 'static>(&self, p_type: PropertyType, item_id: u32) -> Result<Option<&T>> {
+        self.get_property_in_context(self.root_context_id(), p_type, item_id)
+    }
+
+    fn get_property_in_context<T: 'static>(
+        &self,
+        context_id: u32,
+        p_type: PropertyType,
+        item_id: u32,
+    ) -> Result<Option<&T>> {
+        let iprp = self.meta_box(context_id)?.item_properties_box();
+        let index = iprp.find_property_index(p_type, item_id);
+        if index == 0 {
+            return Ok(None);
+        }
+        Ok(iprp
+            .property_by_index(index as usize - 1)
+            .and_then(|prop| prop.as_any().downcast_ref::<T>()))
     }
 
     // Item
 
     fn get_item_by_image_id(&self, image_id: u32) -> Result<&ItemInfoEntry> {
+        self.get_item_in_context(self.root_context_id(), image_id)
+    }
+
+    fn get_item_in_context(&self, context_id: u32, item_id: u32) -> Result<&ItemInfoEntry> {
         if !self.is_initialized() {
             return Err(HeifError::Uninitialized);
         }
-        match self.root_meta_box()?.item_info_box().item_by_id(image_id) {
+        match self
+            .meta_box(context_id)?
+            .item_info_box()
+            .item_by_id(item_id)
+        {
             Some(item) => Ok(item),
             None => Err(HeifError::InvalidItemID),
         }
@@ -810,12 +3063,22 @@ impl HeifReader {
     }
 
     fn is_protected(&self, item_id: u32) -> Result<bool> {
-        Ok(self.get_item_by_image_id(item_id)?.is_protected())
+        self.is_protected_in_context(self.root_context_id(), item_id)
+    }
+
+    fn is_protected_in_context(&self, context_id: u32, item_id: u32) -> Result<bool> {
+        Ok(self
+            .get_item_in_context(context_id, item_id)?
+            .is_protected())
     }
 
     fn image_item_ids(&self) -> Result<IdVec> {
+        self.image_item_ids_in_context(self.root_context_id())
+    }
+
+    fn image_item_ids_in_context(&self, context_id: u32) -> Result<IdVec> {
         Ok(self
-            .root_meta_box()?
+            .meta_box(context_id)?
             .item_info_box()
             .item_info_list()
             .iter()
@@ -836,6 +3099,89 @@ impl HeifReader {
         }
     }
 
+    fn get_track_properties(&self, sequence_id: u32) -> Result<&TrackProperties> {
+        if !self.is_initialized() {
+            return Err(HeifError::Uninitialized);
+        }
+        match self.file_properties.track_properties.get(&sequence_id) {
+            Some(t) => Ok(t),
+            None => Err(HeifError::InvalidSequenceID),
+        }
+    }
+
+    fn get_track_box(&self, sequence_id: u32) -> Result<&TrackBox> {
+        self.get_track_by_sequence_id(sequence_id)?;
+        self.movie_box
+            .tracks()
+            .iter()
+            .find(|track| track.track_header_box().track_id() == sequence_id)
+            .ok_or(HeifError::InvalidSequenceID)
+    }
+
+    fn is_metadata_track(&self, track_id: u32) -> bool {
+        self.get_track_box(track_id)
+            .is_ok_and(|track| *track.media_box().handler_box().handler_type() == "meta")
+    }
+
+    // The presentation time of a composition time of a track, as a fraction
+    // of a second. Leading empty edits, in the movie time scale, delay the
+    // track and the media_time of the first edit is where it starts.
+    fn presentation_time(&self, track_id: u32, composition_time: i64) -> Result<(i128, i128)> {
+        let properties = self.get_track_properties(track_id)?;
+        let time_scale = i128::from(properties.time_scale.max(1));
+        let movie_time_scale = i128::from(self.file_properties.movie_timescale.max(1));
+        let (empty_duration, media_time) = edit_list_start(&properties.edit_list);
+        Ok((
+            (i128::from(composition_time) - i128::from(media_time)) * movie_time_scale
+                + i128::from(empty_duration) * time_scale,
+            time_scale * movie_time_scale,
+        ))
+    }
+
+    // The presentation time of a composition time of a track in its media
+    // time scale. Samples before the media_time of the first edit, such as
+    // AAC priming samples, get negative times.
+    fn presentation_time_ts(&self, track_id: u32, composition_time: i64) -> Result<i64> {
+        let properties = self.get_track_properties(track_id)?;
+        let (empty_duration, media_time) = edit_list_start(&properties.edit_list);
+        let delay = i128::from(empty_duration) * i128::from(properties.time_scale)
+            / i128::from(self.file_properties.movie_timescale.max(1));
+        Ok(composition_time - media_time + delay as i64)
+    }
+
+    fn get_sample_properties(&self, sequence_id: u32, sample_id: u32) -> Result<&SampleProperties> {
+        self.get_track_properties(sequence_id)?
+            .sample_properties
+            .get(&sample_id)
+            .ok_or(HeifError::InvalidItemID)
+    }
+
+    fn check_sample_id(&self, sequence_id: u32, sample_id: u32) -> Result<()> {
+        if (sample_id as usize) < self.get_track_by_sequence_id(sequence_id)?.samples.len() {
+            Ok(())
+        } else {
+            Err(HeifError::InvalidItemID)
+        }
+    }
+
+    // The sgpd entry index of sample_id in the grouping of the given type.
+    fn sample_group_description_index(
+        &self,
+        sequence_id: u32,
+        grouping_type: &str,
+        sample_id: u32,
+    ) -> Result<Option<u32>> {
+        self.check_sample_id(sequence_id, sample_id)?;
+        Ok(self
+            .get_track_properties(sequence_id)?
+            .grouped_samples
+            .iter()
+            .filter(|grouping| grouping.grouping_type == grouping_type)
+            .flat_map(|grouping| grouping.samples.iter())
+            .find(|sample| sample.sample_id == sample_id)
+            .map(|sample| sample.sample_group_description_index))
+    }
+
     fn get_sequence_items(&self, sequence_id: u32) -> Result<IdVec> {
         Ok(self
             .get_track_by_sequence_id(sequence_id)?
@@ -871,25 +3217,131 @@ fn parse_image_grid(stream: &mut BitStream) -> Result<ImageGrid> {
     })
 }
 
-fn do_references_from_item_id_exist(metabox: &MetaBox, item_id: u32, ref_type: Byte4) -> bool {
+// Chromaticity coordinates of the HDR properties are in units of 0.00002.
+// The time in the track timescale an eqiv entity group timing refers to.
+// time_offset is in units of the timescale times timescale_multiplier / 2^14,
+// where a multiplier of 0 is taken as 1.
+fn equivalence_time(timing: &EquivalenceTiming) -> i64 {
+    let multiplier = match timing.timescale_multiplier {
+        0 => 1 << 14,
+        multiplier => i64::from(multiplier),
+    };
+    i64::from(timing.time_offset) * (1 << 14) / multiplier
+}
+
+fn chromaticity(x: i64, y: i64) -> Chromaticity {
+    Chromaticity {
+        x: x as f64 * 0.00002,
+        y: y as f64 * 0.00002,
+    }
+}
+
+fn parse_image_overlay(stream: &mut BitStream, image_ids: IdVec) -> Result<Overlay> {
+    stream.read_byte()?;
+    let read_4bytes_fields = (stream.read_byte()? & 1) != 0;
+    let rgba = (
+        stream.read_2bytes()?.to_u16(),
+        stream.read_2bytes()?.to_u16(),
+        stream.read_2bytes()?.to_u16(),
+        stream.read_2bytes()?.to_u16(),
+    );
+    let read_field = |stream: &mut BitStream| -> Result<u32> {
+        if read_4bytes_fields {
+            Ok(stream.read_4bytes()?.to_u32())
+        } else {
+            Ok(stream.read_2bytes()?.to_u32())
+        }
+    };
+    let output_width = read_field(stream)?;
+    let output_height = read_field(stream)?;
+    let mut offsets = Vec::new();
+    for _ in 0..image_ids.len() {
+        // Offsets are signed, so sign extend the 16 bit form.
+        let (horizontal, vertical) = if read_4bytes_fields {
+            (read_field(stream)? as i32, read_field(stream)? as i32)
+        } else {
+            (
+                i32::from(read_field(stream)? as u16 as i16),
+                i32::from(read_field(stream)? as u16 as i16),
+            )
+        };
+        offsets.push(Offset {
+            horizontal,
+            vertical,
+        });
+    }
+    Ok(Overlay {
+        rgba,
+        output_width,
+        output_height,
+        offsets,
+        image_ids,
+    })
+}
+
+// Coded images which are neither auxiliary images nor thumbnails.
+fn master_image_ids(metabox: &MetaBox, graph: &DerivationGraph) -> IdVec {
     metabox
-        .item_reference_box()
-        .references_of_type(ref_type)
+        .item_info_box()
+        .item_info_list()
         .iter()
-        .any(|r| r.get_from_item_id() == item_id)
+        .filter(|item| {
+            let ty = item.item_type().to_string();
+            (ty == "avc1" || ty == "hvc1" || ty == "vvc1" || ty == "unci")
+                && !graph.has_references_from(item.item_id(), ReferenceType::Auxl)
+                && !graph.has_references_from(item.item_id(), ReferenceType::Thmb)
+        })
+        .map(ItemInfoEntry::item_id)
+        .collect()
+}
+
+// Where the data of item_id lives when its dref entry places it outside the
+// file. Data reference index 0 and self-contained url entries mean this file.
+fn external_data_location(metabox: &MetaBox, item_id: u32) -> Result<Option<&str>> {
+    let data_ref_index = match metabox.item_location_box().item_location_by_id(item_id) {
+        Some(item_location) => item_location.data_ref_index(),
+        None => return Ok(None),
+    };
+    if data_ref_index == 0 {
+        return Ok(None);
+    }
+    let entry = match metabox
+        .data_information_box()
+        .data_reference_box()
+        .data_entry(data_ref_index)
+    {
+        Some(entry) => entry,
+        None => return Err(HeifError::FileHeader),
+    };
+    if entry.is_self_contained() {
+        Ok(None)
+    } else if !entry.location().is_empty() {
+        Ok(Some(entry.location()))
+    } else if !entry.name().is_empty() {
+        Ok(Some(entry.name()))
+    } else {
+        Err(HeifError::FileHeader)
+    }
 }
 
 fn is_image_item_type(item_type: &Byte4) -> bool {
     let item_type = item_type.to_string();
     item_type == "avc1"
         || item_type == "hvc1"
+        || item_type == "lhv1"
+        || item_type == "vvc1"
+        || item_type == "unci"
         || item_type == "grid"
         || item_type == "iovl"
         || item_type == "iden"
+        || item_type == "tmap"
         || item_type == "jpeg"
 }
 
-fn extract_metabox_item_properties_map(metabox: &MetaBox) -> HashMap<u32, ItemFeature> {
+fn extract_metabox_item_properties_map(
+    metabox: &MetaBox,
+    graph: &DerivationGraph,
+) -> HashMap<u32, ItemFeature> {
     let mut map = HashMap::new();
     let item_ids = metabox.item_info_box().item_ids();
     for item_id in item_ids {
@@ -904,16 +3356,16 @@ fn extract_metabox_item_properties_map(metabox: &MetaBox) -> HashMap<u32, ItemFe
             if item.item_protection_index() > 0 {
                 item_features.set_feature(ItemFeatureEnum::IsProtected);
             }
-            if do_references_from_item_id_exist(metabox, item_id, "thmb".parse().unwrap()) {
+            if graph.has_references_from(item_id, ReferenceType::Thmb) {
                 item_features.set_feature(ItemFeatureEnum::IsThumbnailImage);
             }
-            if do_references_from_item_id_exist(metabox, item_id, "auxl".parse().unwrap()) {
+            if graph.has_references_from(item_id, ReferenceType::Auxl) {
                 item_features.set_feature(ItemFeatureEnum::IsAuxiliaryImage);
             }
-            if do_references_from_item_id_exist(metabox, item_id, "base".parse().unwrap()) {
+            if graph.has_references_from(item_id, ReferenceType::Base) {
                 item_features.set_feature(ItemFeatureEnum::IsPreComputedDerivedImage);
             }
-            if do_references_from_item_id_exist(metabox, item_id, "dimg".parse().unwrap()) {
+            if graph.has_references_from(item_id, ReferenceType::Dimg) {
                 item_features.set_feature(ItemFeatureEnum::IsDerivedImage);
             }
             if !item_features.has_feature(ItemFeatureEnum::IsThumbnailImage)
@@ -921,22 +3373,22 @@ fn extract_metabox_item_properties_map(metabox: &MetaBox) -> HashMap<u32, ItemFe
             {
                 item_features.set_feature(ItemFeatureEnum::IsMasterImage);
             }
-            if do_references_from_item_id_exist(metabox, item_id, "thmb".parse().unwrap()) {
+            if graph.has_references_from(item_id, ReferenceType::Thmb) {
                 item_features.set_feature(ItemFeatureEnum::HasLinkedThumbnails);
             }
-            if do_references_from_item_id_exist(metabox, item_id, "auxl".parse().unwrap()) {
+            if graph.has_references_from(item_id, ReferenceType::Auxl) {
                 item_features.set_feature(ItemFeatureEnum::HasLinkedAuxiliaryImage);
             }
-            if do_references_from_item_id_exist(metabox, item_id, "cdsc".parse().unwrap()) {
+            if graph.has_references_from(item_id, ReferenceType::Cdsc) {
                 item_features.set_feature(ItemFeatureEnum::HasLinkedMetadata);
             }
-            if do_references_from_item_id_exist(metabox, item_id, "base".parse().unwrap()) {
+            if graph.has_references_from(item_id, ReferenceType::Base) {
                 item_features.set_feature(ItemFeatureEnum::HasLinkedPreComputedDerivedImage);
             }
-            if do_references_from_item_id_exist(metabox, item_id, "tbas".parse().unwrap()) {
+            if graph.has_references_from(item_id, ReferenceType::Tbas) {
                 item_features.set_feature(ItemFeatureEnum::HasLinkedTiles);
             }
-            if do_references_from_item_id_exist(metabox, item_id, "dimg".parse().unwrap()) {
+            if graph.has_references_from(item_id, ReferenceType::Dimg) {
                 item_features.set_feature(ItemFeatureEnum::HasLinkedDerivedImage);
             }
 
@@ -952,7 +3404,7 @@ fn extract_metabox_item_properties_map(metabox: &MetaBox) -> HashMap<u32, ItemFe
             if item.item_protection_index() > 0 {
                 item_features.set_feature(ItemFeatureEnum::IsProtected);
             }
-            if do_references_from_item_id_exist(metabox, item_id, "cdsc".parse().unwrap()) {
+            if graph.has_references_from(item_id, ReferenceType::Cdsc) {
                 item_features.set_feature(ItemFeatureEnum::IsMetadataItem);
             }
             if item_type == "Exif" {
@@ -1015,22 +3467,794 @@ fn extract_metabox_feature(
         if features.has_feature(ItemFeatureEnum::IsPreComputedDerivedImage) {
             meta_box_feature.set_feature(MetaBoxFeatureEnum::HasPreComputedDerivedImages);
         }
-        if features.has_feature(ItemFeatureEnum::IsHiddenImage) {
-            meta_box_feature.set_feature(MetaBoxFeatureEnum::HasHiddenImages);
+        if features.has_feature(ItemFeatureEnum::IsHiddenImage) {
+            meta_box_feature.set_feature(MetaBoxFeatureEnum::HasHiddenImages);
+        }
+    }
+    meta_box_feature
+}
+
+pub struct AudioSamples<'a> {
+    reader: &'a HeifReader,
+    track_id: u32,
+    next_sample_id: u32,
+}
+
+impl<'a> Iterator for AudioSamples<'a> {
+    type Item = Result<AudioSample>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let track = match self.reader.get_track_by_sequence_id(self.track_id) {
+            Ok(track) => track,
+            Err(e) => return Some(Err(e)),
+        };
+        let sample = track.samples.get(self.next_sample_id as usize)?;
+        self.next_sample_id += 1;
+        let duration_ts = match self
+            .reader
+            .get_sample_properties(self.track_id, sample.decoding_order)
+        {
+            Ok(properties) => properties.sample_duration_ts,
+            Err(e) => return Some(Err(e)),
+        };
+        let presentation_time_ts = match self
+            .reader
+            .presentation_time_ts(self.track_id, sample.composition_times[0])
+        {
+            Ok(time) => time,
+            Err(e) => return Some(Err(e)),
+        };
+        Some(
+            self.reader
+                .get_track_sample_data(self.track_id, sample.decoding_order)
+                .map(|data| AudioSample {
+                    sample_id: sample.decoding_order,
+                    presentation_time_ts,
+                    duration_ts,
+                    data,
+                }),
+        )
+    }
+}
+
+fn extract_track_info(track_box: &TrackBox) -> Result<TrackInfo> {
+    let track_header_box = track_box.track_header_box();
+    let media_box = track_box.media_box();
+    let stbl = media_box.media_information_box().sample_table_box();
+    let sample_count = stbl.sample_count();
+    let offsets = stbl.sample_offsets()?;
+    let deltas = stbl.time_to_sample_box().sample_deltas(sample_count);
+    let composition_offsets = match stbl.composition_offset_box() {
+        Some(ctts) => ctts.sample_offsets(sample_count),
+        None => vec![0; sample_count as usize],
+    };
+    let description_indices = stbl.sample_description_indices();
+    let decode_dependencies = extract_decode_dependencies(stbl, sample_count);
+    let mut samples = SampleInfoVector::new();
+    let mut decode_time = 0i64;
+    for sample in 0..sample_count {
+        let index = sample as usize;
+        let (width, height) = description_indices
+            .get(index)
+            .and_then(|i| stbl.sample_description_box().sample_entry(*i))
+            .and_then(|entry| entry.visual_size().ok())
+            .map(|(w, h)| (u32::from(w), u32::from(h)))
+            .unwrap_or((track_header_box.width(), track_header_box.height()));
+        samples.push(SampleInfo {
+            decoding_order: sample,
+            decode_time,
+            composition_times: vec![decode_time + composition_offsets[index]],
+            data_offset: offsets[index],
+            data_length: u64::from(stbl.sample_size_box().sample_size(sample)),
+            width,
+            height,
+            decode_dependencies: decode_dependencies[index].clone(),
+            is_sync: stbl.is_sync_sample(sample)
+                || stbl
+                    .sample_dependency_type_box()
+                    .and_then(|sdtp| sdtp.entry(sample))
+                    .is_some_and(|entry| entry.sample_depends_on == 2),
+        });
+        decode_time += i64::from(deltas[index]);
+    }
+    let time_scale = media_box.media_header_box().time_scale();
+    let duration = if time_scale == 0 {
+        0.0
+    } else {
+        media_box.media_header_box().duration() as f64 / f64::from(time_scale)
+    };
+    Ok(TrackInfo {
+        samples,
+        width: track_header_box.width(),
+        height: track_header_box.height(),
+        matrix: track_header_box.matrix().clone(),
+        duration,
+        clap_properties: HashMap::new(),
+        auxi_properties: extract_auxi_properties(stbl),
+        repetitions: 0.0,
+        fragment_sample_ids: HashMap::new(),
+    })
+}
+
+fn coding_constraints_of(entry: Option<&SampleEntryBox>) -> Option<CodingConstraints> {
+    entry
+        .and_then(|entry| entry.coding_constraints_box().ok().flatten())
+        .map(|ccst| CodingConstraints {
+            all_ref_pics_intra: ccst.all_ref_pics_intra(),
+            intra_pred_used: ccst.intra_pred_used(),
+            max_ref_per_pic: ccst.max_ref_per_pic(),
+        })
+}
+
+// The properties samples take from their sample entry, with the fields of
+// the sample itself left empty.
+fn sample_entry_properties(
+    entry: Option<&SampleEntryBox>,
+    sample_description_index: u32,
+) -> SampleProperties {
+    SampleProperties {
+        sample_id: 0,
+        sample_entry_type: entry
+            .map(|entry| entry.box_type().clone())
+            .unwrap_or_default(),
+        sample_description_index,
+        sample_type: SampleType::OutputReferenceFrame,
+        sample_duration_ts: 0,
+        sample_composition_offset_ts: 0,
+        has_clap: entry.is_some_and(|entry| entry.has_visual_child_box("clap")),
+        has_auxi: entry.is_some_and(|entry| entry.has_visual_child_box("auxi")),
+        coding_constraints: coding_constraints_of(entry).unwrap_or_default(),
+        size: 0,
+    }
+}
+
+fn new_sample_properties(
+    entry_properties: &SampleProperties,
+    sample_id: u32,
+    sample_type: SampleType,
+    sample_duration_ts: u64,
+    sample_composition_offset_ts: i64,
+    size: u64,
+) -> SampleProperties {
+    SampleProperties {
+        sample_id,
+        sample_type,
+        sample_duration_ts,
+        sample_composition_offset_ts,
+        size,
+        ..entry_properties.clone()
+    }
+}
+
+// The samples of a track fragment, numbered on from first_sample_id and
+// timed on from decode_time unless tfdt gives the time. Values missing from
+// trun come from tfhd and then from the trex of the track. Returns the end of
+// the sample data as well.
+fn extract_fragment_samples(
+    track_box: &TrackBox,
+    trex: Option<&TrackExtendsBox>,
+    traf: &TrackFragmentBox,
+    base_data_offset: u64,
+    first_sample_id: u32,
+    decode_time: i64,
+) -> Result<(Vec<(SampleInfo, SampleProperties)>, u64)> {
+    let tfhd = traf.track_fragment_header_box();
+    let stbl = track_box
+        .media_box()
+        .media_information_box()
+        .sample_table_box();
+    let sample_description_index = tfhd
+        .sample_description_index()
+        .or_else(|| trex.map(|trex| trex.default_sample_description_index()))
+        .unwrap_or(1);
+    let entry = stbl
+        .sample_description_box()
+        .sample_entry(sample_description_index);
+    let (width, height) = entry
+        .and_then(|entry| entry.visual_size().ok())
+        .map(|(w, h)| (u32::from(w), u32::from(h)))
+        .unwrap_or((
+            track_box.track_header_box().width(),
+            track_box.track_header_box().height(),
+        ));
+    let entry_properties = sample_entry_properties(entry, sample_description_index);
+    let intra_only = coding_constraints_of(entry).is_some_and(|ccst| ccst.max_ref_per_pic == 0);
+    let default_duration = tfhd
+        .default_sample_duration()
+        .or_else(|| trex.map(|trex| trex.default_sample_duration()))
+        .unwrap_or(0);
+    let default_size = tfhd
+        .default_sample_size()
+        .or_else(|| trex.map(|trex| trex.default_sample_size()))
+        .unwrap_or(0);
+    let default_flags = tfhd
+        .default_sample_flags()
+        .or_else(|| trex.map(|trex| trex.default_sample_flags()))
+        .unwrap_or(0);
+
+    let mut samples = Vec::new();
+    let mut decode_time = match traf.base_media_decode_time() {
+        Some(time) => time as i64,
+        None => decode_time,
+    };
+    let mut data_offset = base_data_offset;
+    for trun in traf.track_run_boxes() {
+        if let Some(offset) = trun.data_offset() {
+            data_offset = (base_data_offset as i64 + i64::from(offset)) as u64;
+        }
+        for (i, run_sample) in trun.samples().iter().enumerate() {
+            let duration = run_sample.duration.unwrap_or(default_duration);
+            let size = run_sample.size.unwrap_or(default_size);
+            let flags = match (i, trun.first_sample_flags()) {
+                (0, Some(flags)) => flags,
+                _ => run_sample.flags.unwrap_or(default_flags),
+            };
+            let sample_id = first_sample_id + samples.len() as u32;
+            let sample_type = match sample_is_depended_on(flags) {
+                2 => SampleType::OutputNonReferenceFrame,
+                1 => SampleType::OutputReferenceFrame,
+                _ if intra_only => SampleType::OutputNonReferenceFrame,
+                _ => SampleType::OutputReferenceFrame,
+            };
+            samples.push((
+                SampleInfo {
+                    decoding_order: sample_id,
+                    decode_time,
+                    composition_times: vec![decode_time + run_sample.composition_time_offset],
+                    data_offset,
+                    data_length: u64::from(size),
+                    width,
+                    height,
+                    decode_dependencies: IdVec::new(),
+                    is_sync: !sample_is_non_sync_sample(flags) || sample_depends_on(flags) == 2,
+                },
+                new_sample_properties(
+                    &entry_properties,
+                    sample_id,
+                    sample_type,
+                    u64::from(duration),
+                    run_sample.composition_time_offset,
+                    u64::from(size),
+                ),
+            ));
+            decode_time += i64::from(duration);
+            data_offset += u64::from(size);
+        }
+    }
+    Ok((samples, data_offset))
+}
+
+// The auxiliary track type of each sample entry with an auxi box, keyed by
+// sample description index.
+fn extract_auxi_properties(stbl: &SampleTableBox) -> HashMap<u32, AuxiliaryType> {
+    let mut auxi_properties = HashMap::new();
+    for (i, entry) in stbl
+        .sample_description_box()
+        .sample_entries()
+        .iter()
+        .enumerate()
+    {
+        if let Ok(Some(auxi)) = entry.auxiliary_type_info_box() {
+            auxi_properties.insert(
+                i as u32 + 1,
+                AuxiliaryType {
+                    aux_type: auxi.aux_track_type().to_string(),
+                    sub_type: String::new(),
+                },
+            );
+        }
+    }
+    auxi_properties
+}
+
+// The default sgpd entry index of samples of a grouping type which no sbgp
+// maps, 0 if there is none.
+fn default_sample_group_index(stbl: &SampleTableBox, grouping_type: &Byte4) -> u32 {
+    stbl.sample_group_description_box(grouping_type)
+        .map(|sgpd| sgpd.default_sample_description_index())
+        .unwrap_or(0)
+}
+
+// The sgpd entry index of each sample in the grouping of one sbgp, with the
+// default index for the samples it leaves out.
+fn sbgp_sample_indices(
+    stbl: &SampleTableBox,
+    sbgp: &SampleToGroupBox,
+    sample_count: u32,
+) -> Vec<u32> {
+    let default_index = default_sample_group_index(stbl, sbgp.grouping_type());
+    sbgp.sample_indices(sample_count)
+        .into_iter()
+        .map(|index| if index == 0 { default_index } else { index })
+        .collect()
+}
+
+// The sgpd entry index of each sample for a grouping type over all of its
+// sbgps, whatever their grouping_type_parameter, or the default index of the
+// sgpd when there is no sbgp.
+fn sample_group_indices(
+    stbl: &SampleTableBox,
+    grouping_type: &Byte4,
+    sample_count: u32,
+) -> Option<Vec<u32>> {
+    let mut indices: Option<Vec<u32>> = None;
+    for sbgp in stbl
+        .sample_to_group_boxes()
+        .iter()
+        .filter(|sbgp| sbgp.grouping_type() == grouping_type)
+    {
+        let sbgp_indices = sbgp_sample_indices(stbl, sbgp, sample_count);
+        indices = Some(match indices {
+            Some(indices) => indices
+                .into_iter()
+                .zip(sbgp_indices)
+                .map(|(index, sbgp_index)| if index == 0 { sbgp_index } else { index })
+                .collect(),
+            None => sbgp_indices,
+        });
+    }
+    let default_index = default_sample_group_index(stbl, grouping_type);
+    match indices {
+        None if default_index != 0 => Some(vec![default_index; sample_count as usize]),
+        indices => indices,
+    }
+}
+
+// Sample numbers of the references of each sample. refs entries give each
+// sample an id and list the ids of the samples it references.
+fn extract_decode_dependencies(stbl: &SampleTableBox, sample_count: u32) -> Vec<IdVec> {
+    let mut dependencies = vec![IdVec::new(); sample_count as usize];
+    let refs: Byte4 = "refs".parse().unwrap();
+    let (sgpd, indices) = match (
+        stbl.sample_group_description_box(&refs),
+        sample_group_indices(stbl, &refs, sample_count),
+    ) {
+        (Some(sgpd), Some(indices)) => (sgpd, indices),
+        _ => return dependencies,
+    };
+    let mut sample_numbers = HashMap::new();
+    for (sample, index) in indices.iter().enumerate() {
+        if let Some(SampleGroupEntry::DirectReferenceSamples { sample_id, .. }) = sgpd.entry(*index)
+        {
+            sample_numbers.entry(*sample_id).or_insert(sample as u32);
+        }
+    }
+    for (sample, index) in indices.iter().enumerate() {
+        if let Some(SampleGroupEntry::DirectReferenceSamples {
+            reference_sample_ids,
+            ..
+        }) = sgpd.entry(*index)
+        {
+            dependencies[sample] = reference_sample_ids
+                .iter()
+                .filter_map(|id| sample_numbers.get(id).copied())
+                .collect();
+        }
+    }
+    dependencies
+}
+
+// Whether each sample is used as a reference. A refs sample group says which
+// samples others are predicted from, sdtp says it per sample, and samples of
+// entries whose ccst allows no references reference nothing. Anything else is
+// taken to be a reference frame. All samples are output samples.
+fn extract_sample_types(stbl: &SampleTableBox, sample_count: u32) -> Vec<SampleType> {
+    let refs: Byte4 = "refs".parse().unwrap();
+    let refs_sgpd = stbl.sample_group_description_box(&refs);
+    let ref_indices = sample_group_indices(stbl, &refs, sample_count);
+    let referenced: HashSet<u32> = extract_decode_dependencies(stbl, sample_count)
+        .into_iter()
+        .flatten()
+        .collect();
+    let description_indices = stbl.sample_description_indices();
+    // Whether the ccst of each sample entry allows no references.
+    let mut intra_only_entries: HashMap<u32, bool> = HashMap::new();
+    let mut types = Vec::with_capacity(sample_count as usize);
+    for sample in 0..sample_count {
+        let index = sample as usize;
+        let in_refs_group = match (&ref_indices, refs_sgpd) {
+            (Some(indices), Some(sgpd)) => matches!(
+                sgpd.entry(indices[index]),
+                Some(SampleGroupEntry::DirectReferenceSamples { .. })
+            ),
+            _ => false,
+        };
+        let is_reference = if in_refs_group {
+            Some(referenced.contains(&sample))
+        } else {
+            match stbl
+                .sample_dependency_type_box()
+                .and_then(|sdtp| sdtp.entry(sample))
+                .map(|entry| entry.sample_is_depended_on)
+            {
+                Some(1) => Some(true),
+                Some(2) => Some(false),
+                _ => description_indices.get(index).and_then(|i| {
+                    let intra_only = *intra_only_entries.entry(*i).or_insert_with(|| {
+                        stbl.sample_description_box()
+                            .sample_entry(*i)
+                            .and_then(|entry| entry.coding_constraints_box().ok().flatten())
+                            .is_some_and(|ccst| ccst.max_ref_per_pic() == 0)
+                    });
+                    if intra_only {
+                        Some(false)
+                    } else {
+                        None
+                    }
+                }),
+            }
+        };
+        types.push(if is_reference.unwrap_or(true) {
+            SampleType::OutputReferenceFrame
+        } else {
+            SampleType::OutputNonReferenceFrame
+        });
+    }
+    types
+}
+
+// The total duration of the leading empty edits, in the movie time scale,
+// and the media_time of the first edit which is not empty.
+fn edit_list_start(edit_list: &EditList) -> (u64, i64) {
+    let mut empty_duration = 0;
+    for edit_unit in &edit_list.edit_units {
+        if edit_unit.edit_type != EditType::Empty {
+            return (empty_duration, edit_unit.media_time_in_track_ts);
+        }
+        empty_duration += edit_unit.duration_in_movie_ts;
+    }
+    (empty_duration, 0)
+}
+
+fn extract_edit_list(track_box: &TrackBox) -> EditList {
+    let elst = match track_box.edit_box() {
+        Some(elst) => elst,
+        None => {
+            return EditList {
+                looping: false,
+                repetitions: 0.0,
+                edit_units: Vec::new(),
+            }
+        }
+    };
+    EditList {
+        looping: elst.full_box_header().flags() & 1 != 0,
+        repetitions: 0.0,
+        edit_units: elst
+            .entries()
+            .iter()
+            .map(|entry| EditUnit {
+                edit_type: if entry.media_time == -1 {
+                    EditType::Empty
+                } else if entry.media_rate_integer == 0 {
+                    EditType::Dwell
+                } else {
+                    EditType::Shift
+                },
+                media_time_in_track_ts: entry.media_time,
+                duration_in_movie_ts: entry.segment_duration,
+            })
+            .collect(),
+    }
+}
+
+fn extract_track_properties(track_box: &TrackBox) -> TrackProperties {
+    let track_header_box = track_box.track_header_box();
+    let media_box = track_box.media_box();
+    let stbl = media_box.media_information_box().sample_table_box();
+    let sample_count = stbl.sample_count();
+    let mut track_feature = TrackFeature::default();
+    let mut grouped_samples = Vec::new();
+
+    let flags = track_header_box.full_box_header().flags();
+    for (flag, feature) in [
+        (0x01, TrackFeatureEnum::IsEnabled),
+        (0x02, TrackFeatureEnum::IsInMovie),
+        (0x04, TrackFeatureEnum::IsInPreview),
+    ] {
+        if flags & flag != 0 {
+            track_feature.set_feature(feature);
+        }
+    }
+
+    if *media_box.handler_box().handler_type() == "soun" {
+        track_feature.set_feature(TrackFeatureEnum::IsAudioTrack);
+    }
+    if *media_box.handler_box().handler_type() == "meta" {
+        for entry in stbl.sample_description_box().sample_entries() {
+            match entry.box_type().to_string().as_str() {
+                "exif" => track_feature.set_feature(TrackFeatureEnum::HasExifSampleEntry),
+                "metx" => track_feature.set_feature(TrackFeatureEnum::HasXmlSampleEntry),
+                _ => {}
+            }
+        }
+    }
+
+    let mut reference_track_ids: HashMap<String, IdVec> = HashMap::new();
+    for tref in track_box.track_reference_box().track_ref_type_boxes() {
+        reference_track_ids
+            .entry(tref.reference_type().to_string())
+            .or_default()
+            .extend(tref.track_ids());
+    }
+    let is_thumbnail = reference_track_ids.contains_key("thmb");
+    let is_auxiliary = reference_track_ids.contains_key("auxl")
+        || *media_box.handler_box().handler_type() == "auxv";
+    if is_thumbnail {
+        track_feature.set_feature(TrackFeatureEnum::IsThumbnailImageSequence);
+    }
+    if is_auxiliary {
+        track_feature.set_feature(TrackFeatureEnum::IsAuxiliaryImageSequence);
+    }
+    if *media_box.handler_box().handler_type() == "pict" && !is_thumbnail && !is_auxiliary {
+        track_feature.set_feature(TrackFeatureEnum::IsMasterImageSequence);
+    }
+
+    // One grouping per sbgp, as sbgps of a type differ in their
+    // grouping_type_parameter, and one for an sgpd without any sbgp whose
+    // default index maps every sample.
+    for sbgp in stbl.sample_to_group_boxes() {
+        grouped_samples.push(to_sample_grouping(
+            sbgp.grouping_type(),
+            sbgp.grouping_type_parameter(),
+            0,
+            sbgp_sample_indices(stbl, sbgp, sample_count),
+        ));
+    }
+    for sgpd in stbl.sample_group_description_boxes() {
+        let grouping_type = sgpd.grouping_type();
+        let default_index = sgpd.default_sample_description_index();
+        if default_index != 0
+            && !stbl
+                .sample_to_group_boxes()
+                .iter()
+                .any(|sbgp| sbgp.grouping_type() == grouping_type)
+        {
+            grouped_samples.push(to_sample_grouping(
+                grouping_type,
+                0,
+                0,
+                vec![default_index; sample_count as usize],
+            ));
+        }
+    }
+    if !stbl.sample_to_group_boxes().is_empty() || !stbl.sample_group_description_boxes().is_empty()
+    {
+        track_feature.set_feature(TrackFeatureEnum::HasSampleGroups);
+    }
+
+    // Fragmented tracks may have no samples in stbl, so the sample entries
+    // are checked rather than the samples.
+    if stbl
+        .sample_description_box()
+        .sample_entries()
+        .iter()
+        .any(|entry| coding_constraints_of(Some(entry)).is_some())
+    {
+        track_feature.set_feature(TrackFeatureEnum::HasCodingConstraints);
+    }
+
+    let description_indices = stbl.sample_description_indices();
+    let deltas = stbl.time_to_sample_box().sample_deltas(sample_count);
+    let composition_offsets = match stbl.composition_offset_box() {
+        Some(ctts) => ctts.sample_offsets(sample_count),
+        None => vec![0; sample_count as usize],
+    };
+    let sample_types = extract_sample_types(stbl, sample_count);
+    let mut sample_properties = HashMap::new();
+    let mut entry_properties: HashMap<u32, SampleProperties> = HashMap::new();
+    for sample in 0..sample_count {
+        let index = sample as usize;
+        let sample_description_index = description_indices.get(index).copied().unwrap_or(0);
+        let properties = entry_properties
+            .entry(sample_description_index)
+            .or_insert_with(|| {
+                let entry = stbl
+                    .sample_description_box()
+                    .sample_entry(sample_description_index);
+                sample_entry_properties(entry, sample_description_index)
+            });
+        sample_properties.insert(
+            sample,
+            new_sample_properties(
+                properties,
+                sample,
+                sample_types[index],
+                u64::from(deltas[index]),
+                composition_offsets[index],
+                u64::from(stbl.sample_size_box().sample_size(sample)),
+            ),
+        );
+    }
+
+    let max_sample_size = (0..sample_count)
+        .map(|sample| u64::from(stbl.sample_size_box().sample_size(sample)))
+        .max()
+        .unwrap_or(0);
+    let mut track_properties = TrackProperties {
+        track_id: track_header_box.track_id(),
+        alternate_group_id: u32::from(track_header_box.alternate_group()),
+        track_feature,
+        sample_properties,
+        alternate_track_ids: IdVec::new(),
+        reference_track_ids,
+        grouped_samples,
+        fragment_group_entry_counts: HashMap::new(),
+        equivalences: Vec::new(),
+        metadatas: Vec::new(),
+        reference_samples: Vec::new(),
+        max_sample_size,
+        time_scale: media_box.media_header_box().time_scale(),
+        edit_list: extract_edit_list(track_box),
+        meta_box_properties: None,
+    };
+    for sgpd in stbl.sample_group_description_boxes() {
+        add_sample_group_descriptions(&mut track_properties, sgpd, 1);
+    }
+    track_properties
+}
+
+// A grouping of samples numbered on from first_sample_id, leaving out the
+// samples with index 0 which belong to no group.
+fn to_sample_grouping(
+    grouping_type: &Byte4,
+    type_param: u32,
+    first_sample_id: u32,
+    indices: Vec<u32>,
+) -> SampleGrouping {
+    SampleGrouping {
+        grouping_type: grouping_type.clone(),
+        type_param,
+        samples: indices
+            .into_iter()
+            .enumerate()
+            .filter(|(_, index)| *index != 0)
+            .map(|(sample, index)| SampleAndEntryIDs {
+                sample_id: first_sample_id + sample as u32,
+                sample_group_description_index: index,
+            })
+            .collect(),
+    }
+}
+
+// Records the entries of an sgpd, numbered on from first_index, which the
+// reader interprets.
+fn add_sample_group_descriptions(
+    track: &mut TrackProperties,
+    sgpd: &SampleGroupDescriptionBox,
+    first_index: u32,
+) {
+    for (i, entry) in sgpd.entries().iter().enumerate() {
+        let sample_group_description_index = first_index + i as u32;
+        match entry {
+            SampleGroupEntry::VisualEquivalence {
+                time_offset,
+                timescale_multiplier,
+            } => {
+                track
+                    .track_feature
+                    .set_feature(TrackFeatureEnum::HasSampleEquivalenceGrouping);
+                track.equivalences.push(SampleVisualEquivalence {
+                    sample_group_description_index,
+                    time_offset: *time_offset,
+                    timescale_multiplier: *timescale_multiplier,
+                });
+            }
+            SampleGroupEntry::SampleToMetadataItem { item_ids, .. } => {
+                track
+                    .track_feature
+                    .set_feature(TrackFeatureEnum::HasSampleToItemGrouping);
+                track.metadatas.push(SampleToMetadataItem {
+                    sample_group_description_index,
+                    metadata_item_ids: item_ids.clone(),
+                });
+            }
+            SampleGroupEntry::DirectReferenceSamples {
+                sample_id,
+                reference_sample_ids,
+            } => {
+                track.reference_samples.push(DirectReferenceSamples {
+                    sample_group_description_index,
+                    sample_id: *sample_id,
+                    reference_item_ids: reference_sample_ids.clone(),
+                });
+            }
+            SampleGroupEntry::Unknown(_) => {}
+        }
+    }
+}
+
+// Adds the sample groups of a track fragment whose samples start at
+// first_sample_id. The entries of its sgpd boxes are renumbered after those
+// of earlier fragments, so that traf-local indices stay unique in the track.
+// Samples no traf sbgp maps take the default index of the stbl sgpd.
+fn add_fragment_sample_groups(
+    track: &mut TrackProperties,
+    stbl: &SampleTableBox,
+    traf: &TrackFragmentBox,
+    first_sample_id: u32,
+    sample_count: u32,
+) -> Result<()> {
+    let mut local_index_offsets = HashMap::new();
+    for sgpd in traf.sample_group_description_boxes() {
+        let grouping_type = sgpd.grouping_type().to_string();
+        let entry_count = track
+            .fragment_group_entry_counts
+            .get(&grouping_type)
+            .copied()
+            .unwrap_or(0);
+        local_index_offsets.insert(grouping_type.clone(), entry_count);
+        add_sample_group_descriptions(
+            track,
+            sgpd,
+            TRAF_GROUP_DESCRIPTION_INDEX_BASE + entry_count + 1,
+        );
+        track
+            .fragment_group_entry_counts
+            .insert(grouping_type, entry_count + sgpd.entries().len() as u32);
+    }
+    for sbgp in traf.sample_to_group_boxes() {
+        let grouping_type = sbgp.grouping_type();
+        let default_index = default_sample_group_index(stbl, grouping_type);
+        let offset = local_index_offsets
+            .get(&grouping_type.to_string())
+            .copied()
+            .unwrap_or(0);
+        let indices = sbgp
+            .sample_indices(sample_count)
+            .into_iter()
+            .map(|index| match index {
+                0 => Some(default_index),
+                index if index > TRAF_GROUP_DESCRIPTION_INDEX_BASE => index.checked_add(offset),
+                index => Some(index),
+            })
+            .collect::<Option<_>>()
+            .ok_or(HeifError::FileHeader)?;
+        track.grouped_samples.push(to_sample_grouping(
+            grouping_type,
+            sbgp.grouping_type_parameter(),
+            first_sample_id,
+            indices,
+        ));
+    }
+    for sgpd in stbl.sample_group_description_boxes() {
+        let grouping_type = sgpd.grouping_type();
+        let default_index = sgpd.default_sample_description_index();
+        if default_index != 0
+            && !traf
+                .sample_to_group_boxes()
+                .iter()
+                .any(|sbgp| sbgp.grouping_type() == grouping_type)
+        {
+            track.grouped_samples.push(to_sample_grouping(
+                grouping_type,
+                0,
+                first_sample_id,
+                vec![default_index; sample_count as usize],
+            ));
         }
     }
-    meta_box_feature
+    if !traf.sample_to_group_boxes().is_empty() || !traf.sample_group_description_boxes().is_empty()
+    {
+        track
+            .track_feature
+            .set_feature(TrackFeatureEnum::HasSampleGroups);
+    }
+    Ok(())
 }
 
-fn extract_metabox_properties(metabox: &MetaBox) -> MetaBoxProperties {
-    let item_features_map = extract_metabox_item_properties_map(metabox);
+fn extract_metabox_properties(metabox: &MetaBox, context_id: u32) -> MetaBoxProperties {
+    let mut derivation_graph = DerivationGraph::from_meta_box(metabox);
+    let item_features_map = extract_metabox_item_properties_map(metabox, &derivation_graph);
+    derivation_graph.set_feature_masks(&item_features_map);
     let entity_groupings = extract_metabox_entity_to_group_maps(metabox);
     let meta_box_feature = extract_metabox_feature(&item_features_map, entity_groupings.clone());
     MetaBoxProperties {
-        context_id: 0,
+        context_id,
         meta_box_feature,
         item_features_map,
         entity_groupings,
+        derivation_graph,
     }
 }
 
@@ -1075,22 +4299,1413 @@ fn extract_item_info_map(metabox: &MetaBox) -> ItemInfoMap {
 }
 
 fn process_hevc_item_data(buf: &mut Vec<u8>) {
-    let mut output_offset = 0;
-    let mut byte_offset = 0;
-    let len = buf.len();
-    while output_offset < len {
-        let mut nal_length = u32::from(buf[output_offset + byte_offset]);
-        buf[output_offset + byte_offset] = 0;
-        byte_offset += 1;
-        nal_length = (nal_length << 8) | u32::from(buf[output_offset + byte_offset]);
-        buf[output_offset + byte_offset] = 0;
-        byte_offset += 1;
-        nal_length = (nal_length << 8) | u32::from(buf[output_offset + byte_offset]);
-        buf[output_offset + byte_offset] = 0;
-        byte_offset += 1;
-        nal_length = (nal_length << 8) | u32::from(buf[output_offset + byte_offset]);
-        buf[output_offset + byte_offset] = 1;
-        output_offset += (nal_length + 4) as usize;
-        byte_offset = 0;
+    process_nal_item_data(buf, 4);
+}
+
+// Replaces the NAL unit length fields of length_size bytes with start codes.
+fn process_nal_item_data(buf: &mut Vec<u8>, length_size: usize) {
+    let mut output = Vec::with_capacity(buf.len());
+    let mut offset = 0;
+    while offset + length_size <= buf.len() {
+        let nal_length = buf[offset..offset + length_size]
+            .iter()
+            .fold(0usize, |acc, b| (acc << 8) | usize::from(*b));
+        offset += length_size;
+        let end = buf.len().min(offset + nal_length);
+        output.extend_from_slice(&[0, 0, 0, 1]);
+        output.extend_from_slice(&buf[offset..end]);
+        offset = end;
+    }
+    *buf = output;
+}
+
+// The size of the NAL unit length fields of a vvc1 item, from its vvcC.
+fn vvc_nal_length_size(metabox: &MetaBox, item_id: u32) -> usize {
+    let iprp = metabox.item_properties_box();
+    let index = iprp.find_property_index(PropertyType::VVCC, item_id);
+    if index == 0 {
+        return 4;
+    }
+    iprp.property_by_index(index as usize - 1)
+        .and_then(|prop| prop.as_any().downcast_ref::<VvcConfigurationBox>())
+        .map_or(4, |vvcc| {
+            usize::from(vvcc.config().length_size_minus_one()) + 1
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::testutil::*;
+
+    fn rloc(horizontal_offset: u32, vertical_offset: u32) -> Vec<u8> {
+        let mut body = u32_be(horizontal_offset);
+        body.extend(u32_be(vertical_offset));
+        full_box("rloc", 0, 0, &body)
+    }
+
+    // A 128x64 hvc1 image split into four 64x32 hvt1 tiles, 2 to 5 in
+    // raster order.
+    fn tiled_image() -> Meta {
+        let mut meta = Meta {
+            primary_item_id: Some(1),
+            properties: vec![
+                ispe(128, 64),
+                ispe(64, 32),
+                rloc(0, 0),
+                rloc(64, 0),
+                rloc(0, 32),
+                rloc(64, 32),
+            ],
+            ..Meta::default()
+        };
+        meta.items.push(
+            Item::new(1, "hvc1")
+                .data(&[0, 0, 0, 1, 0x26])
+                .property(1, false),
+        );
+        for tile in 0..4 {
+            let item_id = tile + 2;
+            meta.items.push(
+                Item::new(item_id, "hvt1")
+                    .data(&[0, 0, 0, 1, 0x26])
+                    .property(2, false)
+                    .property(3 + tile as u16, false),
+            );
+            meta.references.push(("tbas", item_id, vec![1]));
+        }
+        meta
+    }
+
+    #[test]
+    fn test_tile_items() {
+        let reader = load(&image_file(&tiled_image()));
+        assert_eq!(reader.get_tile_item_ids(1).unwrap(), vec![2, 3, 4, 5]);
+        assert_eq!(reader.get_tile_base_item_id(4).unwrap(), 1);
+        assert!(matches!(
+            reader.get_tile_base_item_id(1),
+            Err(HeifError::NotApplicable)
+        ));
+        let location = reader.relative_location(5).unwrap();
+        assert_eq!(location.horizontal_offset, 64);
+        assert_eq!(location.vertical_offset, 32);
+    }
+
+    #[test]
+    fn test_tile_item_ids_in_region() {
+        let reader = load(&image_file(&tiled_image()));
+        assert_eq!(
+            reader
+                .get_tile_item_ids_in_region(1, 0, 0, 128, 64)
+                .unwrap(),
+            vec![2, 3, 4, 5]
+        );
+        assert_eq!(
+            reader
+                .get_tile_item_ids_in_region(1, 10, 10, 20, 10)
+                .unwrap(),
+            vec![2]
+        );
+        // A region across the vertical tile boundary.
+        assert_eq!(
+            reader.get_tile_item_ids_in_region(1, 60, 40, 8, 8).unwrap(),
+            vec![4, 5]
+        );
+        // Edges are exclusive: x 64 starts the right column.
+        assert_eq!(
+            reader.get_tile_item_ids_in_region(1, 64, 0, 1, 1).unwrap(),
+            vec![3]
+        );
+        assert!(reader
+            .get_tile_item_ids_in_region(1, 200, 0, 10, 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_vvc_item_data_length_size() {
+        // vvcC with length_size_minus_one 1 and no parameter sets.
+        let vvcc = full_box("vvcC", 0, 0, &[0xFA, 0x00]);
+        let meta = Meta {
+            primary_item_id: Some(1),
+            properties: vec![vvcc, ispe(64, 64)],
+            items: vec![Item::new(1, "vvc1")
+                .data(&[0x00, 0x02, 0xAA, 0xBB, 0x00, 0x01, 0xCC])
+                .property(1, true)
+                .property(2, false)],
+            ..Meta::default()
+        };
+        let reader = load(&image_file(&meta));
+        assert_eq!(
+            reader.get_item_data(1, true).unwrap(),
+            vec![0, 0, 0, 1, 0xAA, 0xBB, 0, 0, 0, 1, 0xCC]
+        );
+        assert_eq!(
+            reader.get_item_data(1, false).unwrap(),
+            vec![0x00, 0x02, 0xAA, 0xBB, 0x00, 0x01, 0xCC]
+        );
+    }
+
+    // An hvc1 item 1 followed by iden items 2, 3, ... each deriving from
+    // the one before.
+    fn iden_chain(length: u32) -> Meta {
+        let mut meta = Meta {
+            primary_item_id: Some(length + 1),
+            properties: vec![ispe(64, 32), irot(1), imir(1)],
+            ..Meta::default()
+        };
+        meta.items.push(
+            Item::new(1, "hvc1")
+                .data(&[0, 0, 0, 1, 0x26])
+                .property(1, false)
+                .property(2, true),
+        );
+        for item_id in 2..=length + 1 {
+            meta.items.push(
+                Item::new(item_id, "iden")
+                    .property(1, false)
+                    .property(3, true),
+            );
+            meta.references.push(("dimg", item_id, vec![item_id - 1]));
+        }
+        meta
+    }
+
+    #[test]
+    fn test_resolve_identity_chain() {
+        let reader = load(&image_file(&iden_chain(2)));
+        let resolved = reader.resolve_identity_item(3).unwrap();
+        assert_eq!(resolved.source_item_id, 1);
+        assert_eq!(resolved.derivation_chain, vec![3, 2]);
+        let mirror = ImageTransform::Mirror(Mirror {
+            horizontal_axis: true,
+        });
+        assert_eq!(
+            resolved.transforms,
+            vec![ImageTransform::Rotate(Rotate { angle: 90 }), mirror, mirror]
+        );
+        assert_eq!(reader.get_transforms(3).unwrap(), vec![mirror]);
+
+        let resolved = reader.resolve_identity_item(1).unwrap();
+        assert_eq!(resolved.source_item_id, 1);
+        assert!(resolved.derivation_chain.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_identity_cycle() {
+        let mut meta = iden_chain(2);
+        meta.references[0].2 = vec![3];
+        let reader = load(&image_file(&meta));
+        assert!(matches!(
+            reader.resolve_identity_item(3),
+            Err(HeifError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_identity_depth_limit() {
+        let reader = load(&image_file(&iden_chain(MAX_DERIVATION_DEPTH as u32)));
+        assert_eq!(
+            reader
+                .resolve_identity_item(MAX_DERIVATION_DEPTH as u32 + 1)
+                .unwrap()
+                .derivation_chain
+                .len(),
+            MAX_DERIVATION_DEPTH
+        );
+
+        let reader = load(&image_file(&iden_chain(MAX_DERIVATION_DEPTH as u32 + 1)));
+        assert!(matches!(
+            reader.resolve_identity_item(MAX_DERIVATION_DEPTH as u32 + 2),
+            Err(HeifError::Unknown(_))
+        ));
+    }
+
+    // code type, decoder configuration and data of each decode call
+    type DecodeCalls = Rc<RefCell<Vec<(String, Vec<Vec<u8>>, Vec<u8>)>>>;
+
+    struct MockDecoder {
+        calls: DecodeCalls,
+    }
+
+    impl ImageDecoder for MockDecoder {
+        fn decode(
+            &self,
+            code_type: &str,
+            decoder_config: &[Vec<u8>],
+            data: &[u8],
+        ) -> Result<PixelBuffer> {
+            self.calls.borrow_mut().push((
+                code_type.to_string(),
+                decoder_config.to_vec(),
+                data.to_vec(),
+            ));
+            Ok(PixelBuffer {
+                width: 2,
+                height: 1,
+                layout: PixelLayout::Planar,
+                components: vec![PixelComponent {
+                    component_type: 0,
+                    bit_depth: 8,
+                    width: 2,
+                    height: 1,
+                }],
+                data: vec![10, 20],
+            })
+        }
+    }
+
+    fn hvcc(nal_units: &[&[u8]]) -> Vec<u8> {
+        let mut body = vec![0x01, 0x01, 0x60, 0x00, 0x00, 0x00];
+        body.extend(vec![0; 6]);
+        body.extend(vec![
+            0x5D, 0xF0, 0x00, 0xFC, 0xFD, 0xF8, 0xF8, 0x00, 0x00, 0x0F,
+        ]);
+        body.push(nal_units.len() as u8);
+        for nal_unit in nal_units {
+            body.push(0x80 | (nal_unit[0] >> 1));
+            body.extend(u16_be(1));
+            body.extend(u16_be(nal_unit.len() as u16));
+            body.extend_from_slice(nal_unit);
+        }
+        bx("hvcC", &body)
+    }
+
+    #[test]
+    fn test_decode_image_with_registered_decoder() {
+        let vps: &[u8] = &[0x40, 0x01, 0xAA];
+        let sps: &[u8] = &[0x42, 0x01, 0xBB];
+        let pps: &[u8] = &[0x44, 0x01, 0xCC];
+        let meta = Meta {
+            primary_item_id: Some(1),
+            properties: vec![hvcc(&[pps, sps, vps]), ispe(2, 1), irot(1)],
+            items: vec![
+                Item::new(1, "hvc1")
+                    .data(&[0, 0, 0, 2, 0x26, 0x01])
+                    .property(1, true)
+                    .property(2, false)
+                    .property(3, true),
+                // No decoder configuration property.
+                Item::new(2, "j2k1").data(&[0xFF, 0x4F]).property(2, false),
+            ],
+            ..Meta::default()
+        };
+        let mut reader = load(&image_file(&meta));
+        assert!(matches!(
+            reader.decode_image(1),
+            Err(HeifError::UnsupportedCodeType)
+        ));
+
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        for code_type in ["hvc1", "j2k1"].iter() {
+            reader.register_decoder(
+                code_type,
+                Box::new(MockDecoder {
+                    calls: calls.clone(),
+                }),
+            );
+        }
+        let image = reader.decode_image(1).unwrap();
+        assert_eq!((image.width, image.height), (1, 2));
+        assert_eq!(image.data, vec![20, 10]);
+        let with_start_code = |nal_unit: &[u8]| [&[0, 0, 0, 1], nal_unit].concat();
+        assert_eq!(
+            calls.borrow()[0],
+            (
+                "hvc1".to_string(),
+                vec![
+                    with_start_code(vps),
+                    with_start_code(sps),
+                    with_start_code(pps)
+                ],
+                vec![0, 0, 0, 1, 0x26, 0x01]
+            )
+        );
+
+        let image = reader.decode_image(2).unwrap();
+        assert_eq!((image.width, image.height), (2, 1));
+        assert_eq!(
+            calls.borrow()[1],
+            ("j2k1".to_string(), vec![], vec![0xFF, 0x4F])
+        );
+    }
+
+    #[test]
+    fn test_decode_avc_image() {
+        // avcC with lengthSizeMinusOne 3 and no parameter sets.
+        let avcc = bx("avcC", &[1, 0x42, 0, 0x1E, 0xFF, 0xE0, 0]);
+        let meta = Meta {
+            primary_item_id: Some(1),
+            properties: vec![avcc, ispe(2, 1)],
+            items: vec![Item::new(1, "avc1")
+                .data(&[0, 0, 0, 2, 0x65, 0x88])
+                .property(1, true)
+                .property(2, false)],
+            ..Meta::default()
+        };
+        let mut reader = load(&image_file(&meta));
+        reader.register_decoder(
+            "avc1",
+            Box::new(MockDecoder {
+                calls: Rc::new(RefCell::new(Vec::new())),
+            }),
+        );
+        assert!(matches!(
+            reader.decode_image(1),
+            Err(HeifError::UnsupportedCodeType)
+        ));
+        assert!(matches!(
+            reader.get_item_data(1, true),
+            Err(HeifError::UnsupportedCodeType)
+        ));
+        assert_eq!(
+            reader.get_item_data(1, false).unwrap(),
+            vec![0, 0, 0, 2, 0x65, 0x88]
+        );
+        assert!(!reader.capabilities().properties.contains("avcC"));
+    }
+
+    #[test]
+    fn test_hdr_properties() {
+        let clli = bx("clli", &[u16_be(1000), u16_be(400)].concat());
+        let mut mdcv = Vec::new();
+        // green, blue, red, white point
+        for value in [8500, 39850, 6550, 2300, 35400, 14600, 15635, 16450].iter() {
+            mdcv.extend(u16_be(*value));
+        }
+        mdcv.extend(u32_be(10_000_000));
+        mdcv.extend(u32_be(50));
+        let amve = bx(
+            "amve",
+            &[u32_be(3_140_000), u16_be(15635), u16_be(16450)].concat(),
+        );
+        // All fields present, primaries in green, blue, red order.
+        let mut cclv = vec![0x3C];
+        for value in [8500, 39850, 6550, 2300, 35400, 14600].iter() {
+            cclv.extend(u32_be(*value));
+        }
+        for value in [50_000, 4_000_000_000, 1_000_000_000].iter() {
+            cclv.extend(u32_be(*value));
+        }
+        let meta = Meta {
+            primary_item_id: Some(1),
+            properties: vec![
+                ispe(64, 64),
+                clli,
+                bx("mdcv", &mdcv),
+                amve,
+                bx("cclv", &cclv),
+            ],
+            items: vec![
+                Item::new(1, "hvc1")
+                    .property(1, false)
+                    .property(2, false)
+                    .property(3, false)
+                    .property(4, false)
+                    .property(5, false),
+                Item::new(2, "hvc1").property(1, false),
+            ],
+            ..Meta::default()
+        };
+        let reader = load(&image_file(&meta));
+
+        let clli = reader.get_content_light_level(1).unwrap();
+        assert_eq!(clli.max_content_light_level, 1000);
+        assert_eq!(clli.max_pic_average_light_level, 400);
+
+        let mdcv = reader.get_mastering_display_color_volume(1).unwrap();
+        let rounded = |c: Chromaticity| ((c.x * 1e4).round(), (c.y * 1e4).round());
+        assert_eq!(rounded(mdcv.display_primaries[0]), (7080.0, 2920.0));
+        assert_eq!(rounded(mdcv.display_primaries[1]), (1700.0, 7970.0));
+        assert_eq!(rounded(mdcv.display_primaries[2]), (1310.0, 460.0));
+        assert_eq!(rounded(mdcv.white_point), (3127.0, 3290.0));
+        assert!((mdcv.max_luminance - 1000.0).abs() < 1e-9);
+        assert!((mdcv.min_luminance - 0.005).abs() < 1e-9);
+
+        let amve = reader.get_ambient_viewing_environment(1).unwrap();
+        assert!((amve.ambient_illuminance - 314.0).abs() < 1e-9);
+        assert_eq!(rounded(amve.ambient_light), (3127.0, 3290.0));
+
+        let cclv = reader.get_content_color_volume(1).unwrap();
+        let primaries = cclv.primaries.unwrap();
+        assert_eq!(rounded(primaries[0]), (7080.0, 2920.0));
+        assert_eq!(rounded(primaries[1]), (1700.0, 7970.0));
+        assert_eq!(rounded(primaries[2]), (1310.0, 460.0));
+        let near = |value: Option<f64>, expected: f64| (value.unwrap() - expected).abs() < 1e-9;
+        assert!(near(cclv.min_luminance, 0.005));
+        assert!(near(cclv.max_luminance, 400.0));
+        assert!(near(cclv.avg_luminance, 100.0));
+
+        assert!(matches!(
+            reader.get_content_light_level(2),
+            Err(HeifError::NotApplicable)
+        ));
+        assert!(matches!(
+            reader.get_content_light_level(9),
+            Err(HeifError::InvalidItemID)
+        ));
+        assert!(matches!(
+            reader.get_mastering_display_color_volume(9),
+            Err(HeifError::InvalidItemID)
+        ));
+        assert!(matches!(
+            reader.get_ambient_viewing_environment(9),
+            Err(HeifError::InvalidItemID)
+        ));
+        assert!(matches!(
+            reader.get_content_color_volume(2),
+            Err(HeifError::NotApplicable)
+        ));
+        assert!(matches!(
+            reader.get_content_color_volume(9),
+            Err(HeifError::InvalidItemID)
+        ));
+    }
+
+    #[test]
+    fn test_display_size() {
+        let pasp = bx("pasp", &[u32_be(4), u32_be(3)].concat());
+        let iscl = full_box(
+            "iscl",
+            0,
+            0,
+            &[u16_be(1), u16_be(2), u16_be(1), u16_be(2)].concat(),
+        );
+        let meta = Meta {
+            primary_item_id: Some(1),
+            properties: vec![ispe(120, 90), pasp, irot(1), iscl],
+            items: vec![
+                Item::new(1, "hvc1").property(1, false),
+                Item::new(2, "hvc1").property(1, false).property(2, false),
+                Item::new(3, "hvc1")
+                    .property(1, false)
+                    .property(2, false)
+                    .property(3, true),
+                Item::new(4, "hvc1").property(1, false).property(4, true),
+                // An iden rotating item 2, which carries the pasp.
+                Item::new(5, "iden").property(1, false).property(3, true),
+            ],
+            references: vec![("dimg", 5, vec![2])],
+            ..Meta::default()
+        };
+        let reader = load(&image_file(&meta));
+        assert_eq!(reader.display_size(1).unwrap(), (120, 90));
+        // 4:3 wide pixels stretch the width.
+        assert_eq!(reader.display_size(2).unwrap(), (160, 90));
+        // After a quarter turn the wide pixels are tall, stretching the height.
+        assert_eq!(reader.display_size(3).unwrap(), (90, 160));
+        assert_eq!(reader.display_size(4).unwrap(), (60, 45));
+        assert_eq!(reader.display_size(5).unwrap(), (90, 160));
+        assert_eq!(reader.get_pixel_aspect_ratio(2).unwrap().relative_width, 4);
+    }
+
+    fn capabilities(item_types: &[&str], properties: &[&str]) -> Capabilities {
+        Capabilities {
+            item_types: item_types.iter().map(|t| t.to_string()).collect(),
+            properties: properties.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_displayable_primary() {
+        let meta = Meta {
+            primary_item_id: Some(1),
+            properties: vec![ispe(64, 64), irot(1), imir(0)],
+            items: vec![
+                // Needs irot.
+                Item::new(1, "hvc1").property(1, false).property(2, true),
+                Item::new(2, "hvc1").property(1, false).protected(),
+                Item::new(3, "jpeg").property(1, false),
+                // imir is not essential here.
+                Item::new(4, "hvc1").property(1, false).property(3, false),
+                Item::new(5, "hvc1").property(1, false),
+            ],
+            groups: vec![entity_to_group_box("altr", 100, &[1, 2, 3, 4])],
+            ..Meta::default()
+        };
+        let reader = load(&image_file(&meta));
+        assert!(reader.is_protected(2).unwrap());
+
+        let supported = capabilities(&["hvc1", "jpeg"], &["ispe", "irot"]);
+        assert_eq!(reader.displayable_primary(&supported).unwrap(), Some(1));
+        // Without irot the primary item is skipped, as are the protected
+        // item 2 and the jpeg item 3.
+        let no_irot = capabilities(&["hvc1"], &["ispe"]);
+        assert_eq!(reader.displayable_primary(&no_irot).unwrap(), Some(4));
+        let no_irot_with_jpeg = capabilities(&["hvc1", "jpeg"], &["ispe"]);
+        assert_eq!(
+            reader.displayable_primary(&no_irot_with_jpeg).unwrap(),
+            Some(3)
+        );
+        // Item 5 is not an alternative of the primary item.
+        let nothing = capabilities(&["vvc1"], &["ispe"]);
+        assert_eq!(reader.displayable_primary(&nothing).unwrap(), None);
+
+        assert!(reader.capabilities().supports_item_type("grid"));
+        assert!(!reader.capabilities().supports_item_type("hvc1"));
+    }
+
+    fn eqiv_group(group_id: u32, entity_ids: &[u32], time_offset: i16) -> Vec<u8> {
+        let mut body = u32_be(group_id);
+        body.extend(u32_be(entity_ids.len() as u32));
+        for id in entity_ids {
+            body.extend(u32_be(*id));
+        }
+        body.extend(u16_be(time_offset as u16));
+        body.extend(u16_be(1 << 14));
+        full_box("eqiv", 0, 0, &body)
+    }
+
+    fn refs_entry(sample_id: u32, reference_sample_ids: &[u32]) -> Vec<u8> {
+        let mut entry = u32_be(sample_id);
+        entry.push(reference_sample_ids.len() as u8);
+        for id in reference_sample_ids {
+            entry.extend(u32_be(*id));
+        }
+        entry
+    }
+
+    fn stmi_entry(item_ids: &[u32]) -> Vec<u8> {
+        let mut entry = b"meta".to_vec();
+        entry.extend(u32_be(item_ids.len() as u32));
+        for id in item_ids {
+            entry.extend(u32_be(*id));
+        }
+        entry
+    }
+
+    // A three frame pict track 1, 100 ticks per frame, with eqiv, stmi and
+    // refs sample groups, and still items 10 and 11 equivalent to its first
+    // and last frame.
+    fn grouped_sequence() -> Vec<u8> {
+        let meta = Meta {
+            primary_item_id: Some(10),
+            properties: vec![ispe(64, 48)],
+            items: vec![
+                Item::new(10, "hvc1").property(1, false),
+                Item::new(11, "hvc1").property(1, false),
+                Item::new(20, "Exif"),
+                Item::new(21, "mime"),
+            ],
+            groups: vec![eqiv_group(100, &[1, 10], 0), eqiv_group(101, &[11, 1], 200)],
+            ..Meta::default()
+        };
+        let mut sample_table =
+            SampleTable::new(visual_entry("hvc1", 64, 48, &[]), &[10, 10, 10], 100);
+        sample_table.extra = [
+            sbgp("eqiv", None, &[(3, 1)]),
+            sgpd("eqiv", &[[u16_be(0), u16_be(1 << 14)].concat()]),
+            // Two stmi sbgps told apart by their grouping_type_parameter.
+            sbgp("stmi", None, &[(1, 1)]),
+            sbgp("stmi", Some(7), &[(2, 0), (1, 2)]),
+            sgpd("stmi", &[stmi_entry(&[20]), stmi_entry(&[21])]),
+            sbgp("refs", None, &[(1, 1), (1, 2), (1, 3)]),
+            sgpd(
+                "refs",
+                &[refs_entry(5, &[]), refs_entry(6, &[5]), refs_entry(7, &[5])],
+            ),
+        ]
+        .concat();
+        sequence_file(Some(&meta), &[Track::new(1, "pict", sample_table)], &[])
+    }
+
+    #[test]
+    fn test_sample_groups() {
+        let reader = load(&grouped_sequence());
+        let groupings = reader.sample_groupings(1).unwrap();
+        let stmi: Vec<u32> = groupings
+            .iter()
+            .filter(|g| g.grouping_type == "stmi")
+            .map(|g| g.type_param)
+            .collect();
+        assert_eq!(stmi, vec![0, 7]);
+
+        assert_eq!(reader.equivalent_items_for_sample(1, 0).unwrap(), vec![10]);
+        assert!(reader.equivalent_items_for_sample(1, 1).unwrap().is_empty());
+        assert_eq!(reader.equivalent_items_for_sample(1, 2).unwrap(), vec![11]);
+
+        assert_eq!(reader.metadata_items_for_sample(1, 0).unwrap(), vec![20]);
+        assert!(reader.metadata_items_for_sample(1, 1).unwrap().is_empty());
+        assert_eq!(reader.metadata_items_for_sample(1, 2).unwrap(), vec![21]);
+
+        assert!(reader.decode_dependencies(1, 0).unwrap().is_empty());
+        assert_eq!(reader.decode_dependencies(1, 1).unwrap(), vec![0]);
+        assert_eq!(reader.decode_dependencies(1, 2).unwrap(), vec![0]);
+        assert!(matches!(
+            reader.decode_dependencies(1, 3),
+            Err(HeifError::InvalidItemID)
+        ));
+    }
+
+    fn ccst(all_ref_pics_intra: bool, intra_pred_used: bool, max_ref_per_pic: u8) -> Vec<u8> {
+        let byte = (u8::from(all_ref_pics_intra) << 7)
+            | (u8::from(intra_pred_used) << 6)
+            | max_ref_per_pic << 2;
+        full_box("ccst", 0, 0, &[byte, 0, 0, 0])
+    }
+
+    #[test]
+    fn test_sample_types() {
+        // Intra only by ccst.
+        let intra = SampleTable::new(
+            visual_entry("hvc1", 64, 48, &ccst(true, true, 0)),
+            &[10, 10, 10],
+            100,
+        );
+        // One reference per picture, with sdtp for the first two samples.
+        let mut predicted = SampleTable::new(
+            visual_entry("hvc1", 64, 48, &ccst(true, false, 1)),
+            &[10, 10, 10],
+            100,
+        );
+        predicted.sync_samples = Some(vec![1]);
+        predicted.extra = full_box("sdtp", 0, 0, &[0x04, 0x08]);
+        // No ccst, but every sample is a sync sample.
+        let all_sync = SampleTable::new(visual_entry("hvc1", 64, 48, &[]), &[10, 10], 100);
+        let reader = load(&sequence_file(
+            None,
+            &[
+                Track::new(1, "pict", intra),
+                Track::new(2, "pict", predicted),
+                Track::new(3, "pict", all_sync),
+            ],
+            &[],
+        ));
+
+        assert!(reader.is_intra_only(1).unwrap());
+        assert!(reader.supports_frame_accurate_scrubbing(1).unwrap());
+        assert_eq!(
+            reader.sample_type(1, 1).unwrap(),
+            SampleType::OutputNonReferenceFrame
+        );
+
+        assert!(!reader.is_intra_only(2).unwrap());
+        assert!(reader.supports_frame_accurate_scrubbing(2).unwrap());
+        let types: Vec<SampleType> = (0..3).map(|i| reader.sample_type(2, i).unwrap()).collect();
+        assert_eq!(
+            types,
+            vec![
+                SampleType::OutputReferenceFrame,
+                SampleType::OutputNonReferenceFrame,
+                SampleType::OutputReferenceFrame
+            ]
+        );
+
+        assert!(reader.is_intra_only(3).unwrap());
+        assert!(!reader.supports_frame_accurate_scrubbing(3).unwrap());
+        assert!(matches!(
+            reader.sample_type(3, 2),
+            Err(HeifError::InvalidItemID)
+        ));
+
+        // A refs sample group marks the samples others reference.
+        let reader = load(&grouped_sequence());
+        let types: Vec<SampleType> = (0..3).map(|i| reader.sample_type(1, i).unwrap()).collect();
+        assert_eq!(
+            types,
+            vec![
+                SampleType::OutputReferenceFrame,
+                SampleType::OutputNonReferenceFrame,
+                SampleType::OutputNonReferenceFrame
+            ]
+        );
+    }
+
+    #[test]
+    fn test_linked_tracks() {
+        let alpha_type = "urn:mpeg:mpegB:cicp:systems:auxiliary:alpha";
+        let auxi = full_box("auxi", 0, 0, &[alpha_type.as_bytes(), &[0]].concat());
+        let table = |children: &[u8]| {
+            SampleTable::new(visual_entry("hvc1", 64, 48, children), &[10, 10], 100)
+        };
+        let master = Track::new(1, "pict", table(&[]));
+        let mut alpha = Track::new(2, "auxv", table(&auxi));
+        alpha.extra = tref("auxl", &[1]);
+        let mut thumbnails = Track::new(4, "pict", table(&[]));
+        thumbnails.extra = tref("thmb", &[1]);
+        let mut small_thumbnails = Track::new(3, "pict", table(&[]));
+        small_thumbnails.extra = tref("thmb", &[1]);
+        let reader = load(&sequence_file(
+            None,
+            &[master, alpha, thumbnails, small_thumbnails],
+            &[],
+        ));
+
+        assert_eq!(reader.auxiliary_tracks_for(1).unwrap(), vec![2]);
+        assert!(reader.auxiliary_tracks_for(2).unwrap().is_empty());
+        assert_eq!(reader.thumbnail_track_for(1).unwrap(), Some(3));
+        assert_eq!(reader.thumbnail_track_for(3).unwrap(), None);
+        assert!(matches!(
+            reader.auxiliary_tracks_for(9),
+            Err(HeifError::InvalidSequenceID)
+        ));
+        assert_eq!(
+            reader.track_auxiliary_type(2).unwrap().unwrap().aux_type,
+            alpha_type
+        );
+
+        let has = |track_id: u32, feature: TrackFeatureEnum| {
+            reader
+                .get_track_properties(track_id)
+                .unwrap()
+                .track_feature
+                .has_feature(feature)
+        };
+        assert!(has(1, TrackFeatureEnum::IsMasterImageSequence));
+        assert!(has(1, TrackFeatureEnum::HasLinkedAuxiliaryImageSequence));
+        assert!(has(1, TrackFeatureEnum::HasLinkedThumbnailImageSequence));
+        assert!(has(2, TrackFeatureEnum::IsAuxiliaryImageSequence));
+        assert!(!has(2, TrackFeatureEnum::IsMasterImageSequence));
+        assert!(has(3, TrackFeatureEnum::IsThumbnailImageSequence));
+        assert!(!has(3, TrackFeatureEnum::HasLinkedThumbnailImageSequence));
+    }
+
+    #[test]
+    fn test_default_tracks() {
+        let track = |track_id: u32, entry_type: &str, alternate_group: u16| {
+            let table = SampleTable::new(visual_entry(entry_type, 64, 48, &[]), &[10], 100);
+            let mut track = Track::new(track_id, "pict", table);
+            track.alternate_group = alternate_group;
+            track
+        };
+        let mut disabled = track(5, "hvc1", 0);
+        disabled.flags = 0;
+        let reader = load(&sequence_file(
+            None,
+            &[
+                // AVC and HEVC codings of the same sequence.
+                track(1, "avc1", 1),
+                track(2, "hvc1", 1),
+                // A protected track alone in its group.
+                track(3, "encv", 2),
+                track(4, "hvc1", 0),
+                disabled,
+            ],
+            &[],
+        ));
+
+        let both = capabilities(&["avc1", "hvc1"], &[]);
+        assert_eq!(reader.default_tracks(&both).unwrap(), vec![1, 4]);
+        assert_eq!(reader.default_track(&both).unwrap(), Some(1));
+        // Without an AVC decoder the HEVC alternative is chosen.
+        let hevc = capabilities(&["hvc1"], &[]);
+        assert_eq!(reader.default_tracks(&hevc).unwrap(), vec![2, 4]);
+        let avc = capabilities(&["avc1"], &[]);
+        assert_eq!(reader.default_tracks(&avc).unwrap(), vec![1]);
+        let nothing = capabilities(&["vvc1"], &[]);
+        assert!(reader.default_tracks(&nothing).unwrap().is_empty());
+        assert_eq!(reader.default_track(&nothing).unwrap(), None);
+    }
+
+    #[test]
+    fn test_timed_metadata_for_sample() {
+        let images = Track::new(
+            1,
+            "pict",
+            SampleTable::new(visual_entry("hvc1", 64, 48, &[]), &[10, 10, 10], 100),
+        );
+        let mut mett = vec![0; 6];
+        mett.extend(u16_be(1));
+        mett.extend(b"\0application/json\0");
+        // One sample per 100 ms at 90 kHz. The edit list skips the first
+        // sample, so the second one is presented with the first image.
+        let mut metadata = Track::new(
+            2,
+            "meta",
+            SampleTable::new(bx("mett", &mett), &[4, 4, 4], 9000),
+        );
+        metadata.time_scale = 90000;
+        metadata.media_header = full_box("nmhd", 0, 0, &[]);
+        metadata.extra = [tref("cdsc", &[1]), edts(300, 9000)].concat();
+        let reader = load(&sequence_file(None, &[images, metadata], &[]));
+
+        let current = |sample_id: u32| {
+            reader
+                .timed_metadata_for_sample(1, sample_id)
+                .unwrap()
+                .iter()
+                .map(|sample| (sample.track_id, sample.sample_id))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(current(0), vec![(2, 1)]);
+        assert_eq!(current(1), vec![(2, 2)]);
+        assert_eq!(current(2), vec![(2, 2)]);
+        let sample = &reader.timed_metadata_for_sample(1, 0).unwrap()[0];
+        assert_eq!(sample.sample_entry_type, "mett");
+        assert_eq!(sample.content_type, "application/json");
+        assert_eq!(sample.data, vec![17; 4]);
+    }
+
+    #[test]
+    fn test_audio_samples() {
+        let mut mp4a = vec![0; 6];
+        mp4a.extend(u16_be(1));
+        mp4a.extend(vec![0; 8]);
+        mp4a.extend(u16_be(2));
+        mp4a.extend(u16_be(16));
+        mp4a.extend(vec![0; 4]);
+        mp4a.extend(u32_be(48000 << 16));
+        let mut audio = Track::new(
+            1,
+            "soun",
+            SampleTable::new(bx("mp4a", &mp4a), &[6, 7, 8], 1024),
+        );
+        audio.time_scale = 48000;
+        audio.media_header = full_box("smhd", 0, 0, &[0; 4]);
+        // 2112 samples of AAC encoder delay.
+        audio.extra = edts(50, 2112);
+        let reader = load(&sequence_file(None, &[audio], &[]));
+
+        assert_eq!(reader.audio_tracks().unwrap(), vec![1]);
+        let format = reader.audio_format(1).unwrap();
+        assert_eq!(format.sample_entry_type, "mp4a");
+        assert_eq!(format.channel_count, 2);
+        assert_eq!(format.sample_rate, 48000);
+        let samples: Vec<AudioSample> = reader
+            .audio_samples(1)
+            .unwrap()
+            .map(|sample| sample.unwrap())
+            .collect();
+        assert_eq!(
+            samples
+                .iter()
+                .map(|sample| (
+                    sample.presentation_time_ts,
+                    sample.duration_ts,
+                    sample.data.len()
+                ))
+                .collect::<Vec<_>>(),
+            vec![(-2112, 1024, 6), (-1088, 1024, 7), (-64, 1024, 8)]
+        );
+        assert_eq!(samples[2].data, vec![2; 8]);
+    }
+
+    #[test]
+    fn test_fragments() {
+        let non_sync = 0x0001_0000;
+        let mut intra = SampleTable::new(
+            visual_entry("hvc1", 64, 48, &ccst(true, false, 0)),
+            &[],
+            100,
+        );
+        intra.extra = sgpd("stmi", &[stmi_entry(&[20])]);
+        let table = SampleTable::new(visual_entry("hvc1", 64, 48, &[]), &[], 50);
+        let mvex = bx(
+            "mvex",
+            &[trex(1, 100, 5, non_sync), trex(2, 50, 3, 0)].concat(),
+        );
+        let mut file = sequence_file(
+            None,
+            &[Track::new(1, "pict", intra), Track::new(2, "pict", table)],
+            &mvex,
+        );
+
+        // The first traf starts at the moof and its second trun goes on
+        // from the first. The second traf takes its duration and size from
+        // tfhd and starts where the data of the first one ends.
+        let first_moof = |data_offset: u32| {
+            let first = [
+                tfhd(0, 1, &[]),
+                trun(Some(data_offset), 2, &[]),
+                trun(None, 1, &[7]),
+                sbgp("stmi", None, &[(1, 0x10001), (1, 0), (1, 1)]),
+                sgpd("stmi", &[stmi_entry(&[30])]),
+            ]
+            .concat();
+            let second = [tfhd(0x18, 2, &[60, 4]), trun(None, 2, &[])].concat();
+            bx("moof", &[bx("traf", &first), bx("traf", &second)].concat())
+        };
+        let first_moof_offset = file.len() as u32;
+        let moof = first_moof(first_moof(0).len() as u32 + 8);
+        file.extend(moof);
+        let data: Vec<u8> = [(1, 5), (2, 5), (3, 7), (4, 4), (5, 4)]
+            .iter()
+            .flat_map(|(value, size)| vec![*value; *size])
+            .collect();
+        file.extend(bx("mdat", &data));
+
+        // Its traf-local stmi entry is numbered after the one of the first
+        // moof.
+        let second_moof = |data_offset: u32| {
+            let traf = [
+                tfhd(0x02_0000, 1, &[]),
+                trun(Some(data_offset), 1, &[]),
+                sbgp("stmi", None, &[(1, 0x10001)]),
+                sgpd("stmi", &[stmi_entry(&[40])]),
+            ]
+            .concat();
+            bx("moof", &bx("traf", &traf))
+        };
+        let second_moof_offset = file.len() as u32;
+        file.extend(second_moof(second_moof(0).len() as u32 + 8));
+        file.extend(bx("mdat", &[6; 5]));
+        // The third sample of track 1 by position, with the decode time of
+        // the first one, and the sample of the second moof.
+        file.extend(bx(
+            "mfra",
+            &tfra(
+                1,
+                &[
+                    (0, first_moof_offset, 1, 2, 1),
+                    (300, second_moof_offset, 1, 1, 1),
+                ],
+            ),
+        ));
+        let reader = load(&file);
+
+        let samples = |track_id: u32| {
+            reader.track_info[&track_id]
+                .samples
+                .iter()
+                .map(|sample| {
+                    (
+                        sample.decode_time,
+                        reader
+                            .get_track_sample_data(track_id, sample.decoding_order)
+                            .unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            samples(1),
+            vec![
+                (0, vec![1; 5]),
+                (100, vec![2; 5]),
+                (200, vec![3; 7]),
+                (300, vec![6; 5])
+            ]
+        );
+        assert_eq!(samples(2), vec![(0, vec![4; 4]), (60, vec![5; 4])]);
+        let track = reader.get_track_properties(2).unwrap();
+        assert_eq!(track.sample_properties[&1].sample_duration_ts, 60);
+
+        assert_eq!(reader.random_access_samples(1).unwrap(), vec![2, 3]);
+        assert_eq!(reader.random_access_samples(2).unwrap(), vec![0, 1]);
+
+        // The ccst of the sample entry applies though stbl has no samples.
+        assert!(reader.is_intra_only(1).unwrap());
+        assert!(reader.supports_frame_accurate_scrubbing(1).unwrap());
+        assert!(!reader.supports_frame_accurate_scrubbing(2).unwrap());
+
+        let items = |sample_id: u32| reader.metadata_items_for_sample(1, sample_id).unwrap();
+        assert_eq!(items(0), vec![30]);
+        assert!(items(1).is_empty());
+        assert_eq!(items(2), vec![20]);
+        assert_eq!(items(3), vec![40]);
+    }
+
+    #[test]
+    fn test_fragment_group_index_overflow() {
+        let table = SampleTable::new(visual_entry("hvc1", 64, 48, &[]), &[], 100);
+        let mvex = bx("mvex", &trex(1, 100, 5, 0));
+        let mut file = sequence_file(None, &[Track::new(1, "pict", table)], &mvex);
+        // The second traf-local index is offset by the entry of the first
+        // moof and no longer fits.
+        for index in [0x10001, u32::MAX].iter() {
+            let moof = |data_offset: u32| {
+                let traf = [
+                    tfhd(0x02_0000, 1, &[]),
+                    trun(Some(data_offset), 1, &[]),
+                    sbgp("stmi", None, &[(1, *index)]),
+                    sgpd("stmi", &[stmi_entry(&[20])]),
+                ]
+                .concat();
+                bx("moof", &bx("traf", &traf))
+            };
+            file.extend(moof(moof(0).len() as u32 + 8));
+            file.extend(bx("mdat", &[1; 5]));
+        }
+        assert!(matches!(try_load(&file), Err(HeifError::FileHeader)));
+    }
+
+    #[test]
+    fn test_track_meta_cover_image() {
+        let root_meta = Meta {
+            primary_item_id: Some(1),
+            properties: vec![ispe(2, 1)],
+            items: vec![Item::new(1, "jpeg").data(&[1, 2]).property(1, false)],
+            ..Meta::default()
+        };
+        // The cover image reuses item id 1 with its own data and properties.
+        let nclx = bx(
+            "colr",
+            &[
+                b"nclx".to_vec(),
+                u16_be(9),
+                u16_be(16),
+                u16_be(9),
+                vec![0x80],
+            ]
+            .concat(),
+        );
+        let cover_meta = Meta {
+            primary_item_id: Some(1),
+            properties: vec![ispe(2, 1), irot(1), nclx],
+            items: vec![
+                Item::new(1, "jpeg")
+                    .data(&[0xFF, 0x4F])
+                    .property(1, false)
+                    .property(2, true)
+                    .property(3, false),
+                Item::new(2, "jpeg").property(1, false).protected(),
+            ],
+            ..Meta::default()
+        };
+        let mut track = Track::new(
+            1,
+            "pict",
+            SampleTable::new(visual_entry("hvc1", 64, 48, &[]), &[10], 100),
+        );
+        track.extra = cover_meta.to_bytes();
+        let mut reader = load(&sequence_file(Some(&root_meta), &[track], &[]));
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        reader.register_decoder(
+            "jpeg",
+            Box::new(MockDecoder {
+                calls: calls.clone(),
+            }),
+        );
+
+        let context_id = reader.track_meta_context(1).unwrap().unwrap();
+        assert_ne!(context_id, reader.root_context_id());
+        assert_eq!(reader.primary_item_in_context(context_id).unwrap(), Some(1));
+        assert_eq!(
+            reader.get_transforms_in_context(context_id, 1).unwrap(),
+            vec![ImageTransform::Rotate(Rotate { angle: 90 })]
+        );
+        assert!(reader.get_transforms(1).unwrap().is_empty());
+        let colors = reader
+            .get_color_information_in_context(context_id, 1)
+            .unwrap();
+        assert_eq!(colors[0].color_primaries, 9);
+        assert!(colors[0].full_range_flag);
+        assert!(reader.get_color_information(1).unwrap().is_empty());
+
+        let cover = reader.decode_image_in_context(context_id, 1).unwrap();
+        assert_eq!((cover.width, cover.height), (1, 2));
+        assert_eq!(calls.borrow()[0].2, vec![0xFF, 0x4F]);
+        let image = reader.decode_image(1).unwrap();
+        assert_eq!((image.width, image.height), (2, 1));
+        assert_eq!(calls.borrow()[1].2, vec![1, 2]);
+
+        assert!(reader.is_protected_in_context(context_id, 2).unwrap());
+        assert!(matches!(
+            reader.decode_image_in_context(context_id, 2),
+            Err(HeifError::ProtectedItem)
+        ));
+        assert!(matches!(
+            reader.decode_image(2),
+            Err(HeifError::InvalidItemID)
+        ));
+    }
+
+    #[test]
+    fn test_grid_layout_validation() {
+        let sps: &[u8] = &[0x42, 0x01, 0xBB];
+        let other_sps: &[u8] = &[0x42, 0x01, 0xCC];
+        // A 1x2 grid of 64x64 tiles; the second tile is built by the caller.
+        let grid = |second_tile: Item| {
+            let meta = Meta {
+                primary_item_id: Some(1),
+                properties: vec![
+                    ispe(128, 64),
+                    ispe(64, 64),
+                    hvcc(&[sps]),
+                    ispe(32, 64),
+                    hvcc(&[other_sps]),
+                ],
+                items: vec![
+                    Item::new(1, "grid")
+                        .data(&[0, 0, 0, 1, 0, 128, 0, 64])
+                        .property(1, false),
+                    Item::new(2, "hvc1")
+                        .data(&[0, 0, 0, 1, 0x26])
+                        .property(2, false)
+                        .property(3, true),
+                    second_tile,
+                ],
+                references: vec![("dimg", 1, vec![2, 3])],
+                ..Meta::default()
+            };
+            // Grid items are read along with the meta box, which needs as
+            // many bytes of file after it as the grid data takes.
+            load(&[image_file(&meta), bx("free", &[0; 8])].concat())
+        };
+        let tile = || Item::new(3, "hvc1").data(&[0, 0, 0, 1, 0x26]);
+
+        let reader = grid(tile().property(2, false).property(3, true));
+        let layout = reader.grid_layout(1).unwrap();
+        assert_eq!(layout.tiles.len(), 2);
+        assert_eq!(layout.tiles[1].item_id, 3);
+
+        let reader = grid(tile().property(4, false).property(3, true));
+        assert!(matches!(
+            reader.grid_layout(1),
+            Err(HeifError::Unknown("grid tiles have different sizes"))
+        ));
+        let reader = grid(tile().property(2, false).property(5, true));
+        assert!(matches!(
+            reader.grid_layout(1),
+            Err(HeifError::Unknown(
+                "grid tiles have different decoder configurations"
+            ))
+        ));
+        let reader = grid(tile().property(2, false).property(3, true).protected());
+        assert!(matches!(
+            reader.grid_layout(1),
+            Err(HeifError::ProtectedItem)
+        ));
+    }
+
+    #[test]
+    fn test_gain_map_for() {
+        // Single channel, base color space, all fractions 1/1.
+        let mut tmap = vec![0, 0, 0, 0, 0, 0x40];
+        for _ in 0..7 {
+            tmap.extend([u32_be(1), u32_be(1)].concat());
+        }
+        let apple_gain_map = "urn:com:apple:photo:2020:aux:hdrgainmap";
+        let meta = Meta {
+            primary_item_id: Some(1),
+            properties: vec![
+                ispe(64, 64),
+                full_box("auxC", 0, 0, &[apple_gain_map.as_bytes(), &[0]].concat()),
+            ],
+            items: vec![
+                Item::new(1, "hvc1").property(1, false),
+                Item::new(2, "hvc1").property(1, false),
+                Item::new(3, "tmap").data(&tmap).property(1, false),
+                Item::new(4, "hvc1").property(1, false),
+                Item::new(5, "hvc1").property(1, false).property(2, true),
+                Item::new(6, "hvc1").property(1, false),
+            ],
+            references: vec![("dimg", 3, vec![1, 2]), ("auxl", 5, vec![4])],
+            ..Meta::default()
+        };
+        let reader = load(&image_file(&meta));
+
+        let gain_map = reader.gain_map_for(1).unwrap().unwrap();
+        assert_eq!(
+            (
+                gain_map.base_item_id,
+                gain_map.gain_map_item_id,
+                gain_map.tone_map_item_id
+            ),
+            (1, 2, Some(3))
+        );
+        let metadata = gain_map.metadata.unwrap();
+        assert!(metadata.use_base_color_space);
+        assert_eq!(metadata.channels.len(), 1);
+        assert_eq!(metadata.alternate_hdr_headroom, 1.0);
+
+        let from_tone_map = reader.gain_map_for(3).unwrap().unwrap();
+        assert_eq!(
+            (
+                from_tone_map.base_item_id,
+                from_tone_map.gain_map_item_id,
+                from_tone_map.tone_map_item_id
+            ),
+            (1, 2, Some(3))
+        );
+        assert!(from_tone_map.metadata.is_some());
+
+        let apple = reader.gain_map_for(4).unwrap().unwrap();
+        assert_eq!(
+            (
+                apple.base_item_id,
+                apple.gain_map_item_id,
+                apple.tone_map_item_id
+            ),
+            (4, 5, None)
+        );
+        assert!(apple.metadata.is_none());
+
+        // The gain map is the second input of the tmap item, not a base.
+        assert!(reader.gain_map_for(2).unwrap().is_none());
+        assert!(reader.gain_map_for(6).unwrap().is_none());
+        assert!(matches!(
+            reader.gain_map_for(9),
+            Err(HeifError::InvalidItemID)
+        ));
+    }
+
+    #[test]
+    fn test_stereo_and_bracketing_groups() {
+        let aebr = |step: i8, numerator: i8| full_box("aebr", 0, 0, &[step as u8, numerator as u8]);
+        let wbbr = full_box("wbbr", 0, 0, &[u16_be(300), vec![-2i8 as u8]].concat());
+        let meta = Meta {
+            primary_item_id: Some(1),
+            properties: vec![ispe(64, 64), aebr(2, -1), aebr(2, 1), wbbr],
+            items: vec![
+                Item::new(1, "hvc1").property(1, false),
+                Item::new(2, "hvc1").property(1, false),
+                Item::new(3, "hvc1").property(1, false).property(2, false),
+                Item::new(4, "hvc1").property(1, false).property(3, false),
+                Item::new(5, "hvc1").property(1, false).property(4, false),
+            ],
+            groups: vec![
+                entity_to_group_box("ster", 100, &[1, 2]),
+                entity_to_group_box("aebr", 101, &[3, 4]),
+                entity_to_group_box("wbbr", 102, &[5]),
+            ],
+            ..Meta::default()
+        };
+        let reader = load(&image_file(&meta));
+
+        let pair = StereoPair {
+            group_id: 100,
+            left: 1,
+            right: 2,
+        };
+        assert_eq!(reader.stereo_pair_for(1).unwrap(), Some(pair));
+        assert_eq!(reader.stereo_pair_for(2).unwrap(), Some(pair));
+        assert_eq!(reader.stereo_pair_for(3).unwrap(), None);
+
+        let groups = reader.entity_groups_containing(4).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].group_id, 101);
+        assert_eq!(
+            groups[0].group_type,
+            EntityGroupType::AutoExposureBracketing
+        );
+        assert_eq!(groups[0].entity_ids, vec![3, 4]);
+        assert_eq!(
+            reader.entity_groups_containing(5).unwrap()[0].group_type,
+            EntityGroupType::WhiteBalanceBracketing
+        );
+
+        assert_eq!(
+            reader.get_bracket_value(3).unwrap(),
+            BracketValue::AutoExposure {
+                exposure_step: 2,
+                exposure_numerator: -1
+            }
+        );
+        assert_eq!(
+            reader.get_bracket_value(4).unwrap(),
+            BracketValue::AutoExposure {
+                exposure_step: 2,
+                exposure_numerator: 1
+            }
+        );
+        assert_eq!(
+            reader.get_bracket_value(5).unwrap(),
+            BracketValue::WhiteBalance {
+                blue_amber: 300,
+                green_magenta: -2
+            }
+        );
+        assert!(matches!(
+            reader.get_bracket_value(1),
+            Err(HeifError::NotApplicable)
+        ));
+        assert!(matches!(
+            reader.get_bracket_value(9),
+            Err(HeifError::InvalidItemID)
+        ));
+
+        let three_views = Meta {
+            groups: vec![entity_to_group_box("ster", 100, &[1, 2, 3])],
+            ..meta
+        };
+        let reader = load(&image_file(&three_views));
+        assert!(matches!(
+            reader.stereo_pair_for(1),
+            Err(HeifError::FileHeader)
+        ));
+    }
+
+    #[test]
+    fn test_track_meta_item_properties() {
+        let root_meta = Meta {
+            primary_item_id: Some(1),
+            properties: vec![ispe(2, 1)],
+            items: vec![Item::new(1, "jpeg").data(&[1, 2]).property(1, false)],
+            ..Meta::default()
+        };
+        let mut tmap = vec![0, 0, 0, 0, 0, 0x40];
+        for _ in 0..7 {
+            tmap.extend([u32_be(1), u32_be(1)].concat());
+        }
+        let track_meta = Meta {
+            primary_item_id: Some(1),
+            properties: vec![
+                ispe(4, 2),
+                bx("pasp", &[u32_be(2), u32_be(1)].concat()),
+                bx("clli", &[u16_be(1000), u16_be(400)].concat()),
+                full_box("aebr", 0, 0, &[2, 1]),
+            ],
+            items: vec![
+                Item::new(1, "jpeg")
+                    .property(1, false)
+                    .property(2, false)
+                    .property(3, false)
+                    .property(4, false),
+                Item::new(2, "jpeg").property(1, false),
+                Item::new(3, "tmap").data(&tmap).property(1, false),
+            ],
+            references: vec![("dimg", 3, vec![1, 2])],
+            groups: vec![entity_to_group_box("ster", 100, &[1, 2])],
+        };
+        let mut track = Track::new(
+            1,
+            "pict",
+            SampleTable::new(visual_entry("hvc1", 64, 48, &[]), &[10], 100),
+        );
+        track.extra = track_meta.to_bytes();
+        let reader = load(&sequence_file(Some(&root_meta), &[track], &[]));
+        let context_id = reader.track_meta_context(1).unwrap().unwrap();
+
+        assert_eq!(
+            reader.display_size_in_context(context_id, 1).unwrap(),
+            (8, 2)
+        );
+        assert_eq!(reader.display_size(1).unwrap(), (2, 1));
+        assert_eq!(
+            reader
+                .get_content_light_level_in_context(context_id, 1)
+                .unwrap()
+                .max_content_light_level,
+            1000
+        );
+        assert!(matches!(
+            reader.get_content_light_level(1),
+            Err(HeifError::NotApplicable)
+        ));
+        assert_eq!(
+            reader.get_bracket_value_in_context(context_id, 1).unwrap(),
+            BracketValue::AutoExposure {
+                exposure_step: 2,
+                exposure_numerator: 1
+            }
+        );
+        assert!(matches!(
+            reader.get_bracket_value(1),
+            Err(HeifError::NotApplicable)
+        ));
+
+        let gain_map = reader
+            .gain_map_for_in_context(context_id, 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (gain_map.gain_map_item_id, gain_map.tone_map_item_id),
+            (2, Some(3))
+        );
+        assert!(reader.gain_map_for(1).unwrap().is_none());
+        assert_eq!(
+            reader.stereo_pair_for_in_context(context_id, 2).unwrap(),
+            Some(StereoPair {
+                group_id: 100,
+                left: 1,
+                right: 2
+            })
+        );
+        assert!(reader.entity_groups().unwrap().is_empty());
+        assert!(matches!(reader.stereo_pair_for(2), Ok(None)));
     }
 }
@@ -0,0 +1,176 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+use crate::{HeifError, Result};
+
+// Reads item data which a dref entry places outside the file. location is
+// the url of a url entry, or the location (else the name) of a urn entry.
+// A length of 0 reads to the end of the referenced data.
+pub trait DataResolver {
+    fn read(&self, location: &str, offset: usize, length: usize) -> Result<Vec<u8>>;
+}
+
+// Resolves relative paths and file:// urls against a local directory. Paths
+// which would leave the directory, including through symbolic links, are
+// rejected.
+#[derive(Debug)]
+pub struct DirectoryResolver {
+    directory: PathBuf,
+}
+
+impl DirectoryResolver {
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    fn resolve(&self, path: &str) -> Result<PathBuf> {
+        let path = Path::new(path);
+        if path.is_absolute()
+            || !path
+                .components()
+                .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(HeifError::Unknown("data reference leaves the directory"));
+        }
+        let directory = self
+            .directory
+            .canonicalize()
+            .map_err(|_| HeifError::FileOpen)?;
+        let resolved = directory
+            .join(path)
+            .canonicalize()
+            .map_err(|_| HeifError::FileOpen)?;
+        if !resolved.starts_with(&directory) {
+            return Err(HeifError::Unknown("data reference leaves the directory"));
+        }
+        Ok(resolved)
+    }
+}
+
+impl DataResolver for DirectoryResolver {
+    fn read(&self, location: &str, offset: usize, length: usize) -> Result<Vec<u8>> {
+        let path = location.strip_prefix("file://").unwrap_or(location);
+        if path.contains("://") {
+            return Err(HeifError::Unknown("Unsupported data reference scheme"));
+        }
+        let mut file = File::open(self.resolve(path)?).map_err(|_| HeifError::FileOpen)?;
+        file.seek(SeekFrom::Start(offset as u64))
+            .map_err(|_| HeifError::FileRead)?;
+        // The buffer grows with the data actually read, so a bogus length
+        // from the file cannot make it allocate that much up front.
+        let mut buf = Vec::new();
+        if length == 0 {
+            file.read_to_end(&mut buf)
+                .map_err(|_| HeifError::FileRead)?;
+        } else {
+            file.take(length as u64)
+                .read_to_end(&mut buf)
+                .map_err(|_| HeifError::FileRead)?;
+            if buf.len() != length {
+                return Err(HeifError::FileRead);
+            }
+        }
+        Ok(buf)
+    }
+}
+
+#[derive(Default)]
+pub struct DataReferences {
+    resolver: Option<Box<dyn DataResolver>>,
+}
+
+impl std::fmt::Debug for DataReferences {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "DataReferences {{ has_resolver: {} }}",
+            self.resolver.is_some()
+        )
+    }
+}
+
+impl DataReferences {
+    pub fn set_resolver(&mut self, resolver: Box<dyn DataResolver>) {
+        self.resolver = Some(resolver);
+    }
+
+    pub fn read(&self, location: &str, offset: usize, length: usize) -> Result<Vec<u8>> {
+        match &self.resolver {
+            Some(resolver) => resolver.read(location, offset, length),
+            None => Err(HeifError::NoDataResolver),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_directory_resolver() {
+        let directory = std::env::temp_dir().join("heif_test_directory_resolver");
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("data.bin"), b"0123456789").unwrap();
+
+        let resolver = DirectoryResolver::new(&directory);
+        assert_eq!(resolver.read("data.bin", 2, 3).unwrap(), b"234");
+        assert_eq!(resolver.read("file://data.bin", 7, 0).unwrap(), b"789");
+        assert!(resolver.read("data.bin", 8, 3).is_err());
+        assert!(resolver.read("http://example.com/data.bin", 0, 1).is_err());
+        // A length past the end fails without reserving it.
+        assert!(matches!(
+            resolver.read("data.bin", 0, usize::MAX),
+            Err(HeifError::FileRead)
+        ));
+
+        let mut references = DataReferences::default();
+        assert!(matches!(
+            references.read("data.bin", 0, 1),
+            Err(HeifError::NoDataResolver)
+        ));
+        references.set_resolver(Box::new(resolver));
+        assert_eq!(references.read("data.bin", 0, 1).unwrap(), b"0");
+    }
+
+    #[test]
+    fn test_directory_resolver_stays_in_directory() {
+        let parent = std::env::temp_dir().join("heif_test_resolver_escape");
+        let directory = parent.join("data");
+        std::fs::create_dir_all(directory.join("sub")).unwrap();
+        std::fs::write(parent.join("secret.bin"), b"secret").unwrap();
+        std::fs::write(directory.join("sub").join("data.bin"), b"data").unwrap();
+
+        let resolver = DirectoryResolver::new(&directory);
+        assert_eq!(resolver.read("./sub/data.bin", 0, 0).unwrap(), b"data");
+        for location in [
+            "../secret.bin",
+            "sub/../../secret.bin",
+            "file://../secret.bin",
+            "sub/../data.bin",
+        ] {
+            assert!(matches!(
+                resolver.read(location, 0, 0),
+                Err(HeifError::Unknown(_))
+            ));
+        }
+        let absolute = parent.join("secret.bin");
+        assert!(matches!(
+            resolver.read(absolute.to_str().unwrap(), 0, 0),
+            Err(HeifError::Unknown(_))
+        ));
+
+        #[cfg(unix)]
+        {
+            let link = directory.join("link.bin");
+            let _ = std::fs::remove_file(&link);
+            std::os::unix::fs::symlink(parent.join("secret.bin"), &link).unwrap();
+            assert!(matches!(
+                resolver.read("link.bin", 0, 0),
+                Err(HeifError::Unknown(_))
+            ));
+        }
+    }
+}
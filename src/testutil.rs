@@ -0,0 +1,508 @@
+// Builders for small synthetic files used by the reader tests.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::reader::HeifReader;
+use crate::Result;
+
+pub fn u16_be(x: u16) -> Vec<u8> {
+    x.to_be_bytes().to_vec()
+}
+
+pub fn u32_be(x: u32) -> Vec<u8> {
+    x.to_be_bytes().to_vec()
+}
+
+pub fn bx(box_type: &str, body: &[u8]) -> Vec<u8> {
+    let mut out = u32_be(8 + body.len() as u32);
+    out.extend_from_slice(box_type.as_bytes());
+    out.extend_from_slice(body);
+    out
+}
+
+pub fn full_box(box_type: &str, version: u8, flags: u32, body: &[u8]) -> Vec<u8> {
+    let mut payload = u32_be((u32::from(version) << 24) | flags);
+    payload.extend_from_slice(body);
+    bx(box_type, &payload)
+}
+
+pub fn ftyp() -> Vec<u8> {
+    bx("ftyp", b"heic\0\0\0\0mif1heicmsf1")
+}
+
+pub fn hdlr(handler_type: &str) -> Vec<u8> {
+    let mut body = u32_be(0);
+    body.extend_from_slice(handler_type.as_bytes());
+    body.extend_from_slice(&[0; 13]);
+    full_box("hdlr", 0, 0, &body)
+}
+
+// An item of a meta box, with its data stored in idat.
+pub struct Item {
+    pub item_id: u32,
+    pub item_type: &'static str,
+    pub data: Vec<u8>,
+    pub protected: bool,
+    // Property indices into the property list of the meta box, 1-based,
+    // with whether the association is essential.
+    pub properties: Vec<(u16, bool)>,
+}
+
+impl Item {
+    pub fn new(item_id: u32, item_type: &'static str) -> Self {
+        Self {
+            item_id,
+            item_type,
+            data: Vec::new(),
+            protected: false,
+            properties: Vec::new(),
+        }
+    }
+
+    pub fn data(mut self, data: &[u8]) -> Self {
+        self.data = data.to_vec();
+        self
+    }
+
+    pub fn property(mut self, index: u16, essential: bool) -> Self {
+        self.properties.push((index, essential));
+        self
+    }
+
+    pub fn protected(mut self) -> Self {
+        self.protected = true;
+        self
+    }
+}
+
+#[derive(Default)]
+pub struct Meta {
+    pub primary_item_id: Option<u32>,
+    pub items: Vec<Item>,
+    pub properties: Vec<Vec<u8>>,
+    // reference type, from item id, to item ids
+    pub references: Vec<(&'static str, u32, Vec<u32>)>,
+    pub groups: Vec<Vec<u8>>,
+}
+
+impl Meta {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut body = hdlr("pict");
+        if let Some(id) = self.primary_item_id {
+            body.extend(full_box("pitm", 0, 0, &u16_be(id as u16)));
+        }
+
+        let mut iinf = u16_be(self.items.len() as u16);
+        for item in &self.items {
+            let mut infe = u16_be(item.item_id as u16);
+            infe.extend(u16_be(u16::from(item.protected)));
+            infe.extend_from_slice(item.item_type.as_bytes());
+            infe.push(0);
+            iinf.extend(full_box("infe", 2, 0, &infe));
+        }
+        body.extend(full_box("iinf", 0, 0, &iinf));
+
+        let mut iloc = vec![0x44, 0x00];
+        iloc.extend(u16_be(self.items.len() as u16));
+        let mut idat = Vec::new();
+        for item in &self.items {
+            iloc.extend(u16_be(item.item_id as u16));
+            iloc.extend(u16_be(1)); // construction_method idat
+            iloc.extend(u16_be(0));
+            iloc.extend(u16_be(1));
+            iloc.extend(u32_be(idat.len() as u32));
+            iloc.extend(u32_be(item.data.len() as u32));
+            idat.extend_from_slice(&item.data);
+        }
+        body.extend(full_box("iloc", 1, 0, &iloc));
+        body.extend(bx("idat", &idat));
+
+        if !self.references.is_empty() {
+            let mut iref = Vec::new();
+            for (reference_type, from, to) in &self.references {
+                let mut entry = u16_be(*from as u16);
+                entry.extend(u16_be(to.len() as u16));
+                for id in to {
+                    entry.extend(u16_be(*id as u16));
+                }
+                iref.extend(bx(reference_type, &entry));
+            }
+            body.extend(full_box("iref", 0, 0, &iref));
+        }
+
+        let mut ipma = u32_be(self.items.len() as u32);
+        for item in &self.items {
+            ipma.extend(u16_be(item.item_id as u16));
+            ipma.push(item.properties.len() as u8);
+            for (index, essential) in &item.properties {
+                ipma.push((u8::from(*essential) << 7) | *index as u8);
+            }
+        }
+        let ipco = bx("ipco", &self.properties.concat());
+        let mut iprp = ipco;
+        iprp.extend(full_box("ipma", 0, 0, &ipma));
+        body.extend(bx("iprp", &iprp));
+
+        if !self.groups.is_empty() {
+            body.extend(bx("grpl", &self.groups.concat()));
+        }
+        full_box("meta", 0, 0, &body)
+    }
+}
+
+pub fn ispe(width: u32, height: u32) -> Vec<u8> {
+    let mut body = u32_be(width);
+    body.extend(u32_be(height));
+    full_box("ispe", 0, 0, &body)
+}
+
+pub fn entity_to_group_box(group_type: &str, group_id: u32, entity_ids: &[u32]) -> Vec<u8> {
+    let mut body = u32_be(group_id);
+    body.extend(u32_be(entity_ids.len() as u32));
+    for id in entity_ids {
+        body.extend(u32_be(*id));
+    }
+    full_box(group_type, 0, 0, &body)
+}
+
+// A visual sample entry of the given type and size.
+pub fn visual_entry(entry_type: &str, width: u16, height: u16, children: &[u8]) -> Vec<u8> {
+    let mut body = vec![0; 6];
+    body.extend(u16_be(1));
+    body.extend(vec![0; 16]);
+    body.extend(u16_be(width));
+    body.extend(u16_be(height));
+    body.extend(u32_be(0x0048_0000));
+    body.extend(u32_be(0x0048_0000));
+    body.extend(u32_be(0));
+    body.extend(u16_be(1));
+    body.extend(vec![0; 32]);
+    body.extend(u16_be(0x18));
+    body.extend(u16_be(0xFFFF));
+    body.extend_from_slice(children);
+    bx(entry_type, &body)
+}
+
+// A sample table with all samples in one chunk at chunk_offset.
+pub struct SampleTable {
+    pub entries: Vec<u8>,
+    pub sample_sizes: Vec<u32>,
+    pub deltas: Vec<u32>,
+    pub composition_offsets: Vec<i32>,
+    pub sync_samples: Option<Vec<u32>>,
+    pub extra: Vec<u8>,
+}
+
+impl SampleTable {
+    pub fn new(entries: Vec<u8>, sample_sizes: &[u32], delta: u32) -> Self {
+        Self {
+            entries,
+            sample_sizes: sample_sizes.to_vec(),
+            deltas: vec![delta; sample_sizes.len()],
+            composition_offsets: Vec::new(),
+            sync_samples: None,
+            extra: Vec::new(),
+        }
+    }
+
+    pub fn to_bytes(&self, chunk_offset: u32) -> Vec<u8> {
+        let count = self.sample_sizes.len() as u32;
+        let mut stsd = u32_be(1);
+        stsd.extend_from_slice(&self.entries);
+        let mut out = full_box("stsd", 0, 0, &stsd);
+
+        let mut stts = u32_be(self.deltas.len() as u32);
+        for delta in &self.deltas {
+            stts.extend(u32_be(1));
+            stts.extend(u32_be(*delta));
+        }
+        out.extend(full_box("stts", 0, 0, &stts));
+        if !self.composition_offsets.is_empty() {
+            let mut ctts = u32_be(self.composition_offsets.len() as u32);
+            for offset in &self.composition_offsets {
+                ctts.extend(u32_be(1));
+                ctts.extend(u32_be(*offset as u32));
+            }
+            out.extend(full_box("ctts", 1, 0, &ctts));
+        }
+        let mut stsc = u32_be(1);
+        stsc.extend(u32_be(1));
+        stsc.extend(u32_be(count));
+        stsc.extend(u32_be(1));
+        out.extend(full_box("stsc", 0, 0, &stsc));
+        let mut stsz = u32_be(0);
+        stsz.extend(u32_be(count));
+        for size in &self.sample_sizes {
+            stsz.extend(u32_be(*size));
+        }
+        out.extend(full_box("stsz", 0, 0, &stsz));
+        let mut stco = u32_be(1);
+        stco.extend(u32_be(chunk_offset));
+        out.extend(full_box("stco", 0, 0, &stco));
+        if let Some(sync_samples) = &self.sync_samples {
+            let mut stss = u32_be(sync_samples.len() as u32);
+            for sample in sync_samples {
+                stss.extend(u32_be(*sample));
+            }
+            out.extend(full_box("stss", 0, 0, &stss));
+        }
+        out.extend_from_slice(&self.extra);
+        bx("stbl", &out)
+    }
+}
+
+pub struct Track {
+    pub track_id: u32,
+    pub handler: &'static str,
+    pub flags: u32,
+    pub alternate_group: u16,
+    pub time_scale: u32,
+    pub sample_table: SampleTable,
+    // Boxes placed in trak before mdia, e.g. tref, edts or meta.
+    pub extra: Vec<u8>,
+    pub media_header: Vec<u8>,
+}
+
+impl Track {
+    pub fn new(track_id: u32, handler: &'static str, sample_table: SampleTable) -> Self {
+        Self {
+            track_id,
+            handler,
+            flags: 3,
+            alternate_group: 0,
+            time_scale: 1000,
+            sample_table,
+            extra: Vec::new(),
+            media_header: full_box("vmhd", 0, 1, &[0; 8]),
+        }
+    }
+
+    pub fn data_size(&self) -> u32 {
+        self.sample_table.sample_sizes.iter().sum()
+    }
+
+    pub fn to_bytes(&self, chunk_offset: u32) -> Vec<u8> {
+        let mut tkhd = u32_be(0);
+        tkhd.extend(u32_be(0));
+        tkhd.extend(u32_be(self.track_id));
+        tkhd.extend(u32_be(0));
+        tkhd.extend(u32_be(0));
+        tkhd.extend(vec![0; 8]);
+        tkhd.extend(u16_be(0));
+        tkhd.extend(u16_be(self.alternate_group));
+        tkhd.extend(u16_be(0));
+        tkhd.extend(u16_be(0));
+        for value in [0x10000, 0, 0, 0, 0x10000, 0, 0, 0, 0x4000_0000] {
+            tkhd.extend(u32_be(value));
+        }
+        tkhd.extend(u32_be(64 << 16));
+        tkhd.extend(u32_be(48 << 16));
+        let mut mdhd = u32_be(0);
+        mdhd.extend(u32_be(0));
+        mdhd.extend(u32_be(self.time_scale));
+        mdhd.extend(u32_be(0));
+        mdhd.extend(u16_be(0x55C4));
+        mdhd.extend(u16_be(0));
+        let dinf = bx(
+            "dinf",
+            &full_box(
+                "dref",
+                0,
+                0,
+                &[u32_be(1), full_box("url ", 0, 1, &[])].concat(),
+            ),
+        );
+        let minf = bx(
+            "minf",
+            &[
+                self.media_header.clone(),
+                dinf,
+                self.sample_table.to_bytes(chunk_offset),
+            ]
+            .concat(),
+        );
+        let mdia = bx(
+            "mdia",
+            &[full_box("mdhd", 0, 0, &mdhd), hdlr(self.handler), minf].concat(),
+        );
+        bx(
+            "trak",
+            &[
+                full_box("tkhd", 0, self.flags, &tkhd),
+                self.extra.clone(),
+                mdia,
+            ]
+            .concat(),
+        )
+    }
+}
+
+pub fn tref(reference_type: &str, track_ids: &[u32]) -> Vec<u8> {
+    let ids: Vec<u8> = track_ids.iter().flat_map(|id| u32_be(*id)).collect();
+    bx("tref", &bx(reference_type, &ids))
+}
+
+// An edit list with one entry starting the presentation at media_time.
+pub fn edts(segment_duration: u32, media_time: i32) -> Vec<u8> {
+    let mut elst = u32_be(1);
+    elst.extend(u32_be(segment_duration));
+    elst.extend(u32_be(media_time as u32));
+    elst.extend(u16_be(1));
+    elst.extend(u16_be(0));
+    bx("edts", &full_box("elst", 0, 0, &elst))
+}
+
+fn mvhd() -> Vec<u8> {
+    let mut body = u32_be(0);
+    body.extend(u32_be(0));
+    body.extend(u32_be(1000));
+    body.extend(u32_be(0));
+    body.extend(u32_be(0x10000));
+    body.extend(u16_be(0x100));
+    body.extend(vec![0; 10]);
+    for value in [0x10000, 0, 0, 0, 0x10000, 0, 0, 0, 0x4000_0000] {
+        body.extend(u32_be(value));
+    }
+    body.extend(vec![0; 24]);
+    body.extend(u32_be(200));
+    full_box("mvhd", 0, 0, &body)
+}
+
+// ftyp, an optional root meta, then moov with the tracks (plus extra moov
+// children) and one mdat holding the samples of each track in order.
+pub fn sequence_file(meta: Option<&Meta>, tracks: &[Track], moov_extra: &[u8]) -> Vec<u8> {
+    let mut head = ftyp();
+    if let Some(meta) = meta {
+        head.extend(meta.to_bytes());
+    }
+    let moov = |mdat_start: u32| {
+        let mut body = mvhd();
+        let mut offset = mdat_start + 8;
+        for track in tracks {
+            body.extend(track.to_bytes(offset));
+            offset += track.data_size();
+        }
+        body.extend_from_slice(moov_extra);
+        bx("moov", &body)
+    };
+    let moov_len = moov(0).len() as u32;
+    let mut out = head.clone();
+    out.extend(moov(head.len() as u32 + moov_len));
+    let mut mdat = Vec::new();
+    for (track_index, track) in tracks.iter().enumerate() {
+        for (sample, size) in track.sample_table.sample_sizes.iter().enumerate() {
+            let value = (track_index * 16 + sample) as u8;
+            mdat.extend(vec![value; *size as usize]);
+        }
+    }
+    out.extend(bx("mdat", &mdat));
+    out
+}
+
+pub fn image_file(meta: &Meta) -> Vec<u8> {
+    [ftyp(), meta.to_bytes()].concat()
+}
+
+static FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+pub fn load(data: &[u8]) -> HeifReader {
+    try_load(data).unwrap()
+}
+
+pub fn try_load(data: &[u8]) -> Result<HeifReader> {
+    let path = std::env::temp_dir().join(format!(
+        "heif_test_{}_{}.heic",
+        std::process::id(),
+        FILE_COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+    std::fs::write(&path, data).unwrap();
+    let mut reader = HeifReader::default();
+    let result = reader.load(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+    result.map(|_| reader)
+}
+
+pub fn irot(angle: u8) -> Vec<u8> {
+    bx("irot", &[angle])
+}
+
+pub fn imir(axis: u8) -> Vec<u8> {
+    bx("imir", &[axis])
+}
+
+// An sbgp of (sample_count, group_description_index) runs, version 1 when it
+// has a grouping_type_parameter.
+pub fn sbgp(grouping_type: &str, parameter: Option<u32>, runs: &[(u32, u32)]) -> Vec<u8> {
+    let mut body = grouping_type.as_bytes().to_vec();
+    if let Some(parameter) = parameter {
+        body.extend(u32_be(parameter));
+    }
+    body.extend(u32_be(runs.len() as u32));
+    for (sample_count, index) in runs {
+        body.extend(u32_be(*sample_count));
+        body.extend(u32_be(*index));
+    }
+    full_box("sbgp", u8::from(parameter.is_some()), 0, &body)
+}
+
+// A version 1 sgpd with a length before each entry.
+pub fn sgpd(grouping_type: &str, entries: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = grouping_type.as_bytes().to_vec();
+    body.extend(u32_be(0));
+    body.extend(u32_be(entries.len() as u32));
+    for entry in entries {
+        body.extend(u32_be(entry.len() as u32));
+        body.extend_from_slice(entry);
+    }
+    full_box("sgpd", 1, 0, &body)
+}
+
+// Sample defaults of a track in movie fragments.
+pub fn trex(track_id: u32, duration: u32, size: u32, flags: u32) -> Vec<u8> {
+    let body = [track_id, 1, duration, size, flags]
+        .iter()
+        .flat_map(|value| u32_be(*value))
+        .collect::<Vec<u8>>();
+    full_box("trex", 0, 0, &body)
+}
+
+// A tfhd with the optional fields its flags select, in file order.
+pub fn tfhd(flags: u32, track_id: u32, fields: &[u32]) -> Vec<u8> {
+    let mut body = u32_be(track_id);
+    for field in fields {
+        body.extend(u32_be(*field));
+    }
+    full_box("tfhd", 0, flags, &body)
+}
+
+// A trun with an optional data offset and per-sample sizes. Other sample
+// fields come from the defaults.
+pub fn trun(data_offset: Option<u32>, sample_count: u32, sizes: &[u32]) -> Vec<u8> {
+    let mut flags = 0;
+    let mut body = u32_be(sample_count);
+    if let Some(offset) = data_offset {
+        flags |= 0x01;
+        body.extend(u32_be(offset));
+    }
+    if !sizes.is_empty() {
+        flags |= 0x200;
+        for size in sizes {
+            body.extend(u32_be(*size));
+        }
+    }
+    full_box("trun", 0, flags, &body)
+}
+
+// A tfra with one byte traf, trun and sample numbers. Entries are (time,
+// moof_offset, traf_number, trun_number, sample_number).
+pub fn tfra(track_id: u32, entries: &[(u32, u32, u8, u8, u8)]) -> Vec<u8> {
+    let mut body = u32_be(track_id);
+    body.extend(u32_be(0));
+    body.extend(u32_be(entries.len() as u32));
+    for (time, moof_offset, traf, trun, sample) in entries {
+        body.extend(u32_be(*time));
+        body.extend(u32_be(*moof_offset));
+        body.extend([*traf, *trun, *sample]);
+    }
+    full_box("tfra", 0, 0, &body)
+}
@@ -0,0 +1,696 @@
+use crate::bbox::meta::iprp::cmpd::{COMPONENT_TYPE_CB, COMPONENT_TYPE_CR};
+use crate::bbox::meta::iprp::uncc::*;
+use crate::data::{PixelBuffer, PixelComponent, PixelLayout};
+use crate::{HeifError, Result};
+
+const MAX_BIT_DEPTH: u16 = 16;
+const MAX_BLOCK_SIZE: u8 = 8;
+
+#[derive(Debug)]
+struct Plane {
+    component_index: u16,
+    component_type: u16,
+    bit_depth: u8,
+    subsampling_x: u32,
+    subsampling_y: u32,
+    width: u32,
+    height: u32,
+    data: Vec<u16>,
+}
+
+// One sample to be read from the item data: the uncC component describing
+// its storage, and the plane position it is written to.
+#[derive(Debug, Clone, Copy)]
+struct SampleSlot {
+    component: usize,
+    x: u32,
+    y: u32,
+}
+
+struct SampleReader<'a> {
+    data: &'a [u8],
+    uncc: &'a UncompressedFrameConfigBox,
+    byte_offset: usize,
+    bit_offset: u8,
+}
+
+impl<'a> SampleReader<'a> {
+    fn new(data: &'a [u8], uncc: &'a UncompressedFrameConfigBox) -> Self {
+        Self {
+            data,
+            uncc,
+            byte_offset: 0,
+            bit_offset: 0,
+        }
+    }
+
+    fn flush(&mut self) {
+        if self.bit_offset != 0 {
+            self.byte_offset += 1;
+            self.bit_offset = 0;
+        }
+    }
+
+    // Current position after skipping any partially read byte.
+    fn mark(&mut self) -> usize {
+        self.flush();
+        self.byte_offset
+    }
+
+    // Skips padding so that the bytes read since start are a multiple of size.
+    fn align(&mut self, start: usize, size: u32) {
+        self.flush();
+        if size != 0 {
+            let used = self.byte_offset - start;
+            let size = size as usize;
+            self.byte_offset += (size - used % size) % size;
+        }
+    }
+
+    fn read_bits(&mut self, n: u32) -> Result<u64> {
+        let mut value = 0u64;
+        for _ in 0..n {
+            let byte = match self.data.get(self.byte_offset) {
+                Some(b) => *b,
+                None => return Err(HeifError::EOF),
+            };
+            let bit = (byte >> (7 - self.bit_offset)) & 1;
+            value = (value << 1) | u64::from(bit);
+            self.bit_offset += 1;
+            if self.bit_offset == 8 {
+                self.bit_offset = 0;
+                self.byte_offset += 1;
+            }
+        }
+        Ok(value)
+    }
+
+    fn read_integer(&mut self, size: usize, little_endian: bool) -> Result<u64> {
+        let bytes = match self.data.get(self.byte_offset..self.byte_offset + size) {
+            Some(b) => b,
+            None => return Err(HeifError::EOF),
+        };
+        self.byte_offset += size;
+        let fold = |acc: u64, b: &u8| (acc << 8) | u64::from(*b);
+        if little_endian {
+            Ok(bytes.iter().rev().fold(0, fold))
+        } else {
+            Ok(bytes.iter().fold(0, fold))
+        }
+    }
+
+    // The lowest bits set, for 0 to 64 bits.
+    fn low_bits_mask(bits: u32) -> u64 {
+        u64::MAX.checked_shr(64 - bits).unwrap_or(0)
+    }
+
+    // Number of bits a component occupies in the stream.
+    fn slot_bits(component: &UncompressedComponent) -> u32 {
+        if component.component_align_size != 0 {
+            u32::from(component.component_align_size) * 8
+        } else {
+            u32::from(component.bit_depth())
+        }
+    }
+
+    fn read_sample(&mut self, component: &UncompressedComponent) -> Result<u16> {
+        let mask = (1u64 << component.bit_depth()) - 1;
+        let bits = Self::slot_bits(component);
+        let value = if component.component_align_size != 0 {
+            self.flush();
+            self.read_integer(
+                component.component_align_size as usize,
+                self.uncc.components_little_endian(),
+            )?
+        } else if bits % 8 == 0 && self.bit_offset == 0 {
+            self.read_integer(bits as usize / 8, self.uncc.components_little_endian())?
+        } else {
+            self.read_bits(bits)?
+        };
+        Ok((value & mask) as u16)
+    }
+
+    // Reads the given components in order. When blocks are in use as many
+    // consecutive components as fit are packed into each block.
+    fn read_samples(&mut self, components: &[&UncompressedComponent]) -> Result<Vec<u16>> {
+        let block_size = self.uncc.block_size();
+        if block_size == 0 {
+            return components.iter().map(|c| self.read_sample(c)).collect();
+        }
+        self.flush();
+        let block_bits = u32::from(block_size) * 8;
+        let mut values = Vec::with_capacity(components.len());
+        let mut i = 0;
+        while i < components.len() {
+            let mut used = 0;
+            let mut count = 0;
+            while i + count < components.len()
+                && used + Self::slot_bits(components[i + count]) <= block_bits
+            {
+                used += Self::slot_bits(components[i + count]);
+                count += 1;
+            }
+            if count == 0 {
+                return Err(HeifError::Unknown("component does not fit in a block"));
+            }
+            let mut block =
+                self.read_integer(block_size as usize, self.uncc.block_little_endian())?;
+            if self.uncc.block_pad_lsb() {
+                block >>= block_bits - used;
+            }
+            let mut shift = used;
+            for component in &components[i..i + count] {
+                let bits = Self::slot_bits(component);
+                let mask = (1u64 << component.bit_depth()) - 1;
+                // A slot may fill a whole 8 byte block.
+                let value = if self.uncc.block_reversed() {
+                    let v = block & Self::low_bits_mask(bits);
+                    block = block.checked_shr(bits).unwrap_or(0);
+                    v
+                } else {
+                    shift -= bits;
+                    (block >> shift) & Self::low_bits_mask(bits)
+                };
+                values.push((value & mask) as u16);
+            }
+            i += count;
+        }
+        Ok(values)
+    }
+}
+
+struct PlaneWriter<'a> {
+    components: &'a [UncompressedComponent],
+    component_planes: Vec<usize>,
+    planes: Vec<Plane>,
+    row_align_size: u32,
+}
+
+impl<'a> PlaneWriter<'a> {
+    fn read_group(&mut self, reader: &mut SampleReader, slots: &[SampleSlot]) -> Result<()> {
+        let slot_components: Vec<&UncompressedComponent> = slots
+            .iter()
+            .map(|s| &self.components[s.component])
+            .collect();
+        let values = reader.read_samples(&slot_components)?;
+        for (slot, value) in slots.iter().zip(values) {
+            let plane = &mut self.planes[self.component_planes[slot.component]];
+            plane.data[slot.y as usize * plane.width as usize + slot.x as usize] = value;
+        }
+        Ok(())
+    }
+
+    fn read_row(&mut self, reader: &mut SampleReader, slots: &[SampleSlot]) -> Result<()> {
+        let row_start = reader.mark();
+        self.read_group(reader, slots)?;
+        reader.align(row_start, self.row_align_size);
+        Ok(())
+    }
+}
+
+fn chroma_subsampling(sampling_type: u8) -> Result<(u32, u32)> {
+    match sampling_type {
+        SAMPLING_TYPE_NO_SUBSAMPLING => Ok((1, 1)),
+        SAMPLING_TYPE_422 => Ok((2, 1)),
+        SAMPLING_TYPE_420 => Ok((2, 2)),
+        SAMPLING_TYPE_411 => Ok((4, 1)),
+        _ => Err(HeifError::Unknown("invalid sampling_type")),
+    }
+}
+
+fn is_chroma(component_type: u16) -> bool {
+    component_type == COMPONENT_TYPE_CB || component_type == COMPONENT_TYPE_CR
+}
+
+// Decodes the data of an unci item. component_types holds the type of each
+// cmpd entry, or the types implied by the uncC profile.
+pub fn decode_uncompressed_image(
+    data: &[u8],
+    component_types: &[u16],
+    uncc: &UncompressedFrameConfigBox,
+    width: u32,
+    height: u32,
+    layout: PixelLayout,
+) -> Result<PixelBuffer> {
+    let components = uncc.components();
+    if components.is_empty() {
+        return Err(HeifError::UnsupportedCodeType);
+    }
+    let (chroma_x, chroma_y) = chroma_subsampling(uncc.sampling_type())?;
+    if uncc.block_size() > MAX_BLOCK_SIZE {
+        return Err(HeifError::Unknown("unsupported block_size"));
+    }
+
+    let mut planes: Vec<Plane> = Vec::new();
+    let mut component_planes = Vec::new();
+    for component in components {
+        if component.component_format != COMPONENT_FORMAT_UNSIGNED {
+            return Err(HeifError::Unknown("unsupported component_format"));
+        }
+        if component.bit_depth() > MAX_BIT_DEPTH {
+            return Err(HeifError::Unknown("unsupported component bit depth"));
+        }
+        if component.component_align_size != 0
+            && u32::from(component.component_align_size) * 8 < u32::from(component.bit_depth())
+        {
+            return Err(HeifError::Unknown("component_align_size is too small"));
+        }
+        let component_type = match component_types.get(component.component_index as usize) {
+            Some(t) => *t,
+            None => return Err(HeifError::Unknown("invalid component_index")),
+        };
+        if let Some(p) = planes
+            .iter()
+            .position(|p| p.component_index == component.component_index)
+        {
+            component_planes.push(p);
+            continue;
+        }
+        let (subsampling_x, subsampling_y) = if is_chroma(component_type) {
+            (chroma_x, chroma_y)
+        } else {
+            (1, 1)
+        };
+        let plane_width = width / subsampling_x;
+        let plane_height = height / subsampling_y;
+        component_planes.push(planes.len());
+        planes.push(Plane {
+            component_index: component.component_index,
+            component_type,
+            // At most MAX_BIT_DEPTH, checked above.
+            bit_depth: component.bit_depth() as u8,
+            subsampling_x,
+            subsampling_y,
+            width: plane_width,
+            height: plane_height,
+            data: vec![0; plane_width as usize * plane_height as usize],
+        });
+    }
+
+    let tile_cols = uncc.num_tile_cols();
+    let tile_rows = uncc.num_tile_rows();
+    if !width.is_multiple_of(tile_cols) || !height.is_multiple_of(tile_rows) {
+        return Err(HeifError::Unknown(
+            "image size is not a multiple of the tile size",
+        ));
+    }
+    let tile_width = width / tile_cols;
+    let tile_height = height / tile_rows;
+    if !tile_width.is_multiple_of(chroma_x) || !tile_height.is_multiple_of(chroma_y) {
+        return Err(HeifError::Unknown(
+            "tile size does not match the chroma subsampling",
+        ));
+    }
+
+    let mut reader = SampleReader::new(data, uncc);
+    let mut writer = PlaneWriter {
+        components,
+        component_planes,
+        planes,
+        row_align_size: uncc.row_align_size(),
+    };
+
+    // Rows of a single component inside a tile, in plane coordinates.
+    let component_rows = |component: usize, tile_x: u32, tile_y: u32| -> Vec<Vec<SampleSlot>> {
+        let (sx, sy) = if is_chroma(component_types[components[component].component_index as usize])
+        {
+            (chroma_x, chroma_y)
+        } else {
+            (1, 1)
+        };
+        let x0 = tile_x * tile_width / sx;
+        let y0 = tile_y * tile_height / sy;
+        (y0..y0 + tile_height / sy)
+            .map(|y| {
+                (x0..x0 + tile_width / sx)
+                    .map(|x| SampleSlot { component, x, y })
+                    .collect()
+            })
+            .collect()
+    };
+
+    let tiles: Vec<(u32, u32)> = (0..tile_rows)
+        .flat_map(|ty| (0..tile_cols).map(move |tx| (tx, ty)))
+        .collect();
+
+    match uncc.interleave_type() {
+        INTERLEAVE_TYPE_COMPONENT => {
+            for &(tx, ty) in &tiles {
+                let tile_start = reader.mark();
+                for component in 0..components.len() {
+                    for row in component_rows(component, tx, ty) {
+                        writer.read_row(&mut reader, &row)?;
+                    }
+                }
+                reader.align(tile_start, uncc.tile_align_size());
+            }
+        }
+        INTERLEAVE_TYPE_TILE_COMPONENT => {
+            for component in 0..components.len() {
+                for &(tx, ty) in &tiles {
+                    let tile_start = reader.mark();
+                    for row in component_rows(component, tx, ty) {
+                        writer.read_row(&mut reader, &row)?;
+                    }
+                    reader.align(tile_start, uncc.tile_align_size());
+                }
+            }
+        }
+        INTERLEAVE_TYPE_PIXEL => {
+            if chroma_x != 1 || chroma_y != 1 {
+                return Err(HeifError::Unknown(
+                    "pixel interleave requires no chroma subsampling",
+                ));
+            }
+            for &(tx, ty) in &tiles {
+                let tile_start = reader.mark();
+                for y in ty * tile_height..(ty + 1) * tile_height {
+                    let pixels: Vec<Vec<SampleSlot>> = (tx * tile_width..(tx + 1) * tile_width)
+                        .map(|x| {
+                            (0..components.len())
+                                .map(|component| SampleSlot { component, x, y })
+                                .collect()
+                        })
+                        .collect();
+                    if uncc.pixel_size() == 0 {
+                        writer.read_row(&mut reader, &pixels.concat())?;
+                        continue;
+                    }
+                    let row_start = reader.mark();
+                    for pixel in pixels {
+                        let pixel_start = reader.mark();
+                        writer.read_group(&mut reader, &pixel)?;
+                        reader.align(pixel_start, uncc.pixel_size());
+                    }
+                    reader.align(row_start, uncc.row_align_size());
+                }
+                reader.align(tile_start, uncc.tile_align_size());
+            }
+        }
+        INTERLEAVE_TYPE_ROW => {
+            for &(tx, ty) in &tiles {
+                let tile_start = reader.mark();
+                for y in 0..tile_height {
+                    for component in 0..components.len() {
+                        let sy = if is_chroma(
+                            component_types[components[component].component_index as usize],
+                        ) {
+                            chroma_y
+                        } else {
+                            1
+                        };
+                        if y % sy != 0 {
+                            continue;
+                        }
+                        let rows = component_rows(component, tx, ty);
+                        writer.read_row(&mut reader, &rows[(y / sy) as usize])?;
+                    }
+                }
+                reader.align(tile_start, uncc.tile_align_size());
+            }
+        }
+        INTERLEAVE_TYPE_MIXED => {
+            let (chroma, luma): (Vec<usize>, Vec<usize>) = (0..components.len())
+                .partition(|c| is_chroma(component_types[components[*c].component_index as usize]));
+            for &(tx, ty) in &tiles {
+                let tile_start = reader.mark();
+                for &component in &luma {
+                    for row in component_rows(component, tx, ty) {
+                        writer.read_row(&mut reader, &row)?;
+                    }
+                }
+                if !chroma.is_empty() {
+                    let chroma_rows: Vec<Vec<Vec<SampleSlot>>> =
+                        chroma.iter().map(|c| component_rows(*c, tx, ty)).collect();
+                    for r in 0..chroma_rows[0].len() {
+                        let mut row = Vec::new();
+                        for x in 0..chroma_rows[0][r].len() {
+                            for rows in &chroma_rows {
+                                row.push(rows[r][x]);
+                            }
+                        }
+                        writer.read_row(&mut reader, &row)?;
+                    }
+                }
+                reader.align(tile_start, uncc.tile_align_size());
+            }
+        }
+        INTERLEAVE_TYPE_MULTI_Y => {
+            if chroma_y != 1 || chroma_x == 1 {
+                return Err(HeifError::Unknown(
+                    "multi-Y interleave requires 4:2:2 or 4:1:1 sampling",
+                ));
+            }
+            for &(tx, ty) in &tiles {
+                let tile_start = reader.mark();
+                for y in ty * tile_height..(ty + 1) * tile_height {
+                    let mut row = Vec::new();
+                    for x in (tx * tile_width..(tx + 1) * tile_width).step_by(chroma_x as usize) {
+                        let mut luma_offset = 0;
+                        for component in 0..components.len() {
+                            let component_type =
+                                component_types[components[component].component_index as usize];
+                            if is_chroma(component_type) {
+                                row.push(SampleSlot {
+                                    component,
+                                    x: x / chroma_x,
+                                    y,
+                                });
+                            } else {
+                                row.push(SampleSlot {
+                                    component,
+                                    x: x + luma_offset,
+                                    y,
+                                });
+                                luma_offset += 1;
+                            }
+                        }
+                        if luma_offset != chroma_x {
+                            return Err(HeifError::Unknown(
+                                "multi-Y component count does not match the sampling",
+                            ));
+                        }
+                    }
+                    writer.read_row(&mut reader, &row)?;
+                }
+                reader.align(tile_start, uncc.tile_align_size());
+            }
+        }
+        _ => return Err(HeifError::Unknown("invalid interleave_type")),
+    }
+
+    let planes = writer.planes;
+    let data = match layout {
+        PixelLayout::Planar => planes.iter().flat_map(|p| p.data.iter().copied()).collect(),
+        PixelLayout::Interleaved => {
+            let mut data = Vec::with_capacity(width as usize * height as usize * planes.len());
+            for y in 0..height {
+                for x in 0..width {
+                    for p in &planes {
+                        let px = x / p.subsampling_x;
+                        let py = y / p.subsampling_y;
+                        data.push(p.data[py as usize * p.width as usize + px as usize]);
+                    }
+                }
+            }
+            data
+        }
+    };
+    let components = planes
+        .iter()
+        .map(|p| match layout {
+            PixelLayout::Planar => PixelComponent {
+                component_type: p.component_type,
+                bit_depth: p.bit_depth,
+                width: p.width,
+                height: p.height,
+            },
+            PixelLayout::Interleaved => PixelComponent {
+                component_type: p.component_type,
+                bit_depth: p.bit_depth,
+                width,
+                height,
+            },
+        })
+        .collect();
+    Ok(PixelBuffer {
+        width,
+        height,
+        layout,
+        components,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bbox::header::BoxHeader;
+    use crate::bbox::meta::iprp::cmpd::*;
+    use crate::bit::BitStream;
+
+    fn parse_uncc(body: Vec<u8>) -> UncompressedFrameConfigBox {
+        let mut bytes = vec![0, 0, 0, body.len() as u8 + 8, b'u', b'n', b'c', b'C'];
+        bytes.extend(body);
+        let mut stream = BitStream::new(bytes);
+        let header = BoxHeader::from_stream(&mut stream).unwrap();
+        UncompressedFrameConfigBox::from_stream_header(&mut stream, header).unwrap()
+    }
+
+    // components: (component_index, bit depth, align size)
+    fn uncc_v0(
+        components: &[(u8, u16, u8)],
+        sampling_type: u8,
+        interleave_type: u8,
+        block_size: u8,
+        flags: u8,
+        row_align_size: u8,
+    ) -> UncompressedFrameConfigBox {
+        let mut body = vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, components.len() as u8];
+        for (index, depth, align) in components {
+            body.extend(&[0, *index, (depth - 1) as u8, 0, *align]);
+        }
+        body.extend(&[sampling_type, interleave_type, block_size, flags]);
+        body.extend(&[0, 0, 0, 0, 0, 0, 0, row_align_size]);
+        body.extend(&[0; 12]);
+        parse_uncc(body)
+    }
+
+    #[test]
+    fn test_component_interleave_with_row_padding() {
+        let uncc = uncc_v0(
+            &[(0, 8, 0), (1, 8, 0), (2, 8, 0)],
+            SAMPLING_TYPE_NO_SUBSAMPLING,
+            INTERLEAVE_TYPE_COMPONENT,
+            0,
+            0,
+            4,
+        );
+        let data = vec![
+            1, 2, 0, 0, 3, 4, 0, 0, // R
+            5, 6, 0, 0, 7, 8, 0, 0, // G
+            9, 10, 0, 0, 11, 12, 0, 0, // B
+        ];
+        let types = [
+            COMPONENT_TYPE_RED,
+            COMPONENT_TYPE_GREEN,
+            COMPONENT_TYPE_BLUE,
+        ];
+        let image =
+            decode_uncompressed_image(&data, &types, &uncc, 2, 2, PixelLayout::Planar).unwrap();
+        assert_eq!(image.data, (1..=12).collect::<Vec<u16>>());
+        assert_eq!(image.components.len(), 3);
+        assert_eq!(image.components[2].component_type, COMPONENT_TYPE_BLUE);
+
+        let image = decode_uncompressed_image(&data, &types, &uncc, 2, 2, PixelLayout::Interleaved)
+            .unwrap();
+        assert_eq!(&image.data[..6], &[1, 5, 9, 2, 6, 10]);
+    }
+
+    #[test]
+    fn test_pixel_interleave_10bit_blocks() {
+        // Little endian 32-bit blocks, first component in the lowest bits.
+        let uncc = uncc_v0(
+            &[(0, 10, 0), (1, 10, 0), (2, 10, 0)],
+            SAMPLING_TYPE_NO_SUBSAMPLING,
+            INTERLEAVE_TYPE_PIXEL,
+            4,
+            0b0011_0000,
+            0,
+        );
+        let pixel = |r: u32, g: u32, b: u32| (r | (g << 10) | (b << 20)).to_le_bytes();
+        let mut data = pixel(1023, 512, 1).to_vec();
+        data.extend(&pixel(0, 1, 2));
+        let types = [
+            COMPONENT_TYPE_RED,
+            COMPONENT_TYPE_GREEN,
+            COMPONENT_TYPE_BLUE,
+        ];
+        let image = decode_uncompressed_image(&data, &types, &uncc, 2, 1, PixelLayout::Interleaved)
+            .unwrap();
+        assert_eq!(image.data, vec![1023, 512, 1, 0, 1, 2]);
+        assert_eq!(image.components[0].bit_depth, 10);
+    }
+
+    #[test]
+    fn test_nv12_profile() {
+        let uncc = parse_uncc(vec![1, 0, 0, 0, b'n', b'v', b'1', b'2']);
+        let types = uncc.profile_component_types().unwrap();
+        let data = vec![10, 20, 30, 40, 100, 200];
+        let image =
+            decode_uncompressed_image(&data, &types, &uncc, 2, 2, PixelLayout::Planar).unwrap();
+        assert_eq!(image.data, vec![10, 20, 30, 40, 100, 200]);
+        assert_eq!(image.components[1].width, 1);
+
+        let image = decode_uncompressed_image(&data, &types, &uncc, 2, 2, PixelLayout::Interleaved)
+            .unwrap();
+        assert_eq!(
+            image.data,
+            vec![10, 100, 200, 20, 100, 200, 30, 100, 200, 40, 100, 200]
+        );
+    }
+
+    #[test]
+    fn test_multi_y_422() {
+        // Cb Y0 Cr Y1
+        let uncc = uncc_v0(
+            &[(1, 8, 0), (0, 8, 0), (2, 8, 0), (0, 8, 0)],
+            SAMPLING_TYPE_422,
+            INTERLEAVE_TYPE_MULTI_Y,
+            0,
+            0,
+            0,
+        );
+        let types = [COMPONENT_TYPE_Y, COMPONENT_TYPE_CB, COMPONENT_TYPE_CR];
+        let data = vec![128, 16, 64, 235];
+        let image =
+            decode_uncompressed_image(&data, &types, &uncc, 2, 1, PixelLayout::Planar).unwrap();
+        assert_eq!(image.components.len(), 3);
+        assert_eq!(image.components[0].component_type, COMPONENT_TYPE_CB);
+        assert_eq!(image.data, vec![128, 16, 235, 64]);
+    }
+
+    #[test]
+    fn test_component_filling_a_block() {
+        let types = [COMPONENT_TYPE_Y];
+        // The bits above the bit depth are dropped.
+        let data = vec![0, 0, 0, 0, 0, 0, 1, 0x2A, 0xFF, 0, 0, 0, 0, 0, 0, 0x2B];
+        // One 8-bit component aligned to 8 bytes, so its slot is 64 bits,
+        // read with either bit order.
+        for flags in [0, 0b0001_0000] {
+            let uncc = uncc_v0(
+                &[(0, 8, 8)],
+                SAMPLING_TYPE_NO_SUBSAMPLING,
+                INTERLEAVE_TYPE_PIXEL,
+                8,
+                flags,
+                0,
+            );
+            let image =
+                decode_uncompressed_image(&data, &types, &uncc, 2, 1, PixelLayout::Planar).unwrap();
+            assert_eq!(image.data, vec![0x2A, 0x2B]);
+        }
+    }
+
+    #[test]
+    fn test_unsupported_bit_depth() {
+        let uncc = uncc_v0(
+            &[(0, 256, 0)],
+            SAMPLING_TYPE_NO_SUBSAMPLING,
+            INTERLEAVE_TYPE_COMPONENT,
+            0,
+            0,
+            0,
+        );
+        assert_eq!(uncc.components()[0].bit_depth(), 256);
+        assert!(decode_uncompressed_image(
+            &[0; 64],
+            &[COMPONENT_TYPE_Y],
+            &uncc,
+            1,
+            1,
+            PixelLayout::Planar
+        )
+        .is_err());
+    }
+}